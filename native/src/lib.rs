@@ -10,6 +10,7 @@ mod editor;
 mod ext;
 mod objects;
 mod project_settings;
+mod rail_navigation;
 mod resources;
 mod road_navigation;
 mod scripts;
@@ -130,6 +130,252 @@ impl Road {
     }
 }
 
+#[derive(TryFromPrimitive)]
+#[repr(u8)]
+pub(crate) enum Rail {
+    LeftRight = 0x2C,
+    TopBottom = 0x2D,
+    HighTopBottom = 0x2E,
+    LeftHighRight = 0x2F,
+    TopHighBottom = 0x30,
+    HighLeftRight = 0x31,
+    TopRight = 0x32,
+    BottomRight = 0x33,
+    BottomLeft = 0x34,
+    TopLeft = 0x35,
+    RightTopBottom = 0x36,
+    LeftBottomRight = 0x37,
+    TopLeftBottom = 0x38,
+    LeftTopRight = 0x39,
+    LeftTopBottomRight = 0x3A,
+    // second set of slopes that SC2K uses where rails climb onto raised terrain.
+    RampHighTopBottom = 0x3B,
+    RampLeftHighRight = 0x3C,
+    RampTopHighBottom = 0x3D,
+    RampHighLeftRight = 0x3E,
+    LeftRightPowerTopBottom = 0x47,
+    TopBottomPowerLeftRight = 0x48,
+}
+
+impl Rail {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::LeftRight => "res://resources/Objects/Networks/Rail/left_right.tscn",
+            Self::TopBottom => "res://resources/Objects/Networks/Rail/top_bottom.tscn",
+            Self::HighTopBottom | Self::RampHighTopBottom => {
+                "res://resources/Objects/Networks/Rail/high_top_bottom.tscn"
+            }
+            Self::LeftHighRight | Self::RampLeftHighRight => {
+                "res://resources/Objects/Networks/Rail/left_high_right.tscn"
+            }
+            Self::TopHighBottom | Self::RampTopHighBottom => {
+                "res://resources/Objects/Networks/Rail/top_high_bottom.tscn"
+            }
+            Self::HighLeftRight | Self::RampHighLeftRight => {
+                "res://resources/Objects/Networks/Rail/high_left_right.tscn"
+            }
+            Self::TopRight => "res://resources/Objects/Networks/Rail/top_right.tscn",
+            Self::BottomRight => "res://resources/Objects/Networks/Rail/bottom_right.tscn",
+            Self::BottomLeft => "res://resources/Objects/Networks/Rail/bottom_left.tscn",
+            Self::TopLeft => "res://resources/Objects/Networks/Rail/top_left.tscn",
+            Self::RightTopBottom => "res://resources/Objects/Networks/Rail/right_top_bottom.tscn",
+            Self::LeftBottomRight => "res://resources/Objects/Networks/Rail/left_bottom_right.tscn",
+            Self::TopLeftBottom => "res://resources/Objects/Networks/Rail/top_left_bottom.tscn",
+            Self::LeftTopRight => "res://resources/Objects/Networks/Rail/left_top_right.tscn",
+            Self::LeftTopBottomRight => {
+                "res://resources/Objects/Networks/Rail/left_top_bottom_right.tscn"
+            }
+            Self::LeftRightPowerTopBottom => {
+                "res://resources/Objects/Networks/Rail/left_right_power_top_bottom.tscn"
+            }
+            Self::TopBottomPowerLeftRight => {
+                "res://resources/Objects/Networks/Rail/top_bottom_power_left_right.tscn"
+            }
+        }
+    }
+}
+
+/// Level crossings of a road and a rail track.
+#[derive(TryFromPrimitive)]
+#[repr(u8)]
+pub(crate) enum RailCrossing {
+    RoadLeftRightRailTopBottom = 0x45,
+    RoadTopBottomRailLeftRight = 0x46,
+}
+
+impl RailCrossing {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::RoadLeftRightRailTopBottom => {
+                "res://resources/Objects/Networks/Rail/road_left_right_rail_top_bottom.tscn"
+            }
+            Self::RoadTopBottomRailLeftRight => {
+                "res://resources/Objects/Networks/Rail/road_top_bottom_rail_left_right.tscn"
+            }
+        }
+    }
+}
+
+#[derive(TryFromPrimitive)]
+#[repr(u8)]
+pub(crate) enum RailBridge {
+    Pylon = 0x5A,
+    Deck = 0x5B,
+}
+
+impl RailBridge {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pylon => "res://resources/Objects/Networks/Rail/bridge_pylon.tscn",
+            Self::Deck => "res://resources/Objects/Networks/Rail/bridge_deck.tscn",
+        }
+    }
+}
+
+/// Transitions between a rail track on the surface and the subway. The track leaves the tile
+/// through the named side.
+#[derive(TryFromPrimitive)]
+#[repr(u8)]
+pub(crate) enum SubwayTransition {
+    Top = 0x6C,
+    Right = 0x6D,
+    Bottom = 0x6E,
+    Left = 0x6F,
+}
+
+impl SubwayTransition {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Top => "res://resources/Objects/Networks/Rail/subway_top.tscn",
+            Self::Right => "res://resources/Objects/Networks/Rail/subway_right.tscn",
+            Self::Bottom => "res://resources/Objects/Networks/Rail/subway_bottom.tscn",
+            Self::Left => "res://resources/Objects/Networks/Rail/subway_left.tscn",
+        }
+    }
+}
+
+/// Portals of road tunnels. The tunnel runs underground in a straight line to the portal on the
+/// other end.
+#[derive(TryFromPrimitive)]
+#[repr(u8)]
+pub(crate) enum Tunnel {
+    Top = 0x3F,
+    Right = 0x40,
+    Bottom = 0x41,
+    Left = 0x42,
+}
+
+impl Tunnel {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Top => "res://resources/Objects/Networks/Road/tunnel_top.tscn",
+            Self::Right => "res://resources/Objects/Networks/Road/tunnel_right.tscn",
+            Self::Bottom => "res://resources/Objects/Networks/Road/tunnel_bottom.tscn",
+            Self::Left => "res://resources/Objects/Networks/Road/tunnel_left.tscn",
+        }
+    }
+}
+
+/// Highways are two tiles wide, every tile of the highway carries the piece.
+#[derive(TryFromPrimitive)]
+#[repr(u8)]
+pub(crate) enum Highway {
+    LeftRight = 0x49,
+    TopBottom = 0x4A,
+    LeftRightRoadTopBottom = 0x4B,
+    TopBottomRoadLeftRight = 0x4C,
+    LeftRightRailTopBottom = 0x4D,
+    TopBottomRailLeftRight = 0x4E,
+    LeftRightPowerTopBottom = 0x4F,
+    TopBottomPowerLeftRight = 0x50,
+    HighTopBottom = 0x61,
+    LeftHighRight = 0x62,
+    TopHighBottom = 0x63,
+    HighLeftRight = 0x64,
+    TopRight = 0x65,
+    BottomRight = 0x66,
+    BottomLeft = 0x67,
+    TopLeft = 0x68,
+    LeftTopBottomRight = 0x69,
+}
+
+impl Highway {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::LeftRight => "res://resources/Objects/Networks/Highway/left_right.tscn",
+            Self::TopBottom => "res://resources/Objects/Networks/Highway/top_bottom.tscn",
+            Self::LeftRightRoadTopBottom => {
+                "res://resources/Objects/Networks/Highway/left_right_road_top_bottom.tscn"
+            }
+            Self::TopBottomRoadLeftRight => {
+                "res://resources/Objects/Networks/Highway/top_bottom_road_left_right.tscn"
+            }
+            Self::LeftRightRailTopBottom => {
+                "res://resources/Objects/Networks/Highway/left_right_rail_top_bottom.tscn"
+            }
+            Self::TopBottomRailLeftRight => {
+                "res://resources/Objects/Networks/Highway/top_bottom_rail_left_right.tscn"
+            }
+            Self::LeftRightPowerTopBottom => {
+                "res://resources/Objects/Networks/Highway/left_right_power_top_bottom.tscn"
+            }
+            Self::TopBottomPowerLeftRight => {
+                "res://resources/Objects/Networks/Highway/top_bottom_power_left_right.tscn"
+            }
+            Self::HighTopBottom => "res://resources/Objects/Networks/Highway/high_top_bottom.tscn",
+            Self::LeftHighRight => "res://resources/Objects/Networks/Highway/left_high_right.tscn",
+            Self::TopHighBottom => "res://resources/Objects/Networks/Highway/top_high_bottom.tscn",
+            Self::HighLeftRight => "res://resources/Objects/Networks/Highway/high_left_right.tscn",
+            Self::TopRight => "res://resources/Objects/Networks/Highway/top_right.tscn",
+            Self::BottomRight => "res://resources/Objects/Networks/Highway/bottom_right.tscn",
+            Self::BottomLeft => "res://resources/Objects/Networks/Highway/bottom_left.tscn",
+            Self::TopLeft => "res://resources/Objects/Networks/Highway/top_left.tscn",
+            Self::LeftTopBottomRight => {
+                "res://resources/Objects/Networks/Highway/left_top_bottom_right.tscn"
+            }
+        }
+    }
+}
+
+/// Ramps between the raised highway and a road on the ground.
+#[derive(TryFromPrimitive)]
+#[repr(u8)]
+pub(crate) enum HighwayOnRamp {
+    TopLeft = 0x5D,
+    TopRight = 0x5E,
+    BottomLeft = 0x5F,
+    BottomRight = 0x60,
+}
+
+impl HighwayOnRamp {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::TopLeft => "res://resources/Objects/Networks/Highway/on_ramp_top_left.tscn",
+            Self::TopRight => "res://resources/Objects/Networks/Highway/on_ramp_top_right.tscn",
+            Self::BottomLeft => "res://resources/Objects/Networks/Highway/on_ramp_bottom_left.tscn",
+            Self::BottomRight => {
+                "res://resources/Objects/Networks/Highway/on_ramp_bottom_right.tscn"
+            }
+        }
+    }
+}
+
+#[derive(TryFromPrimitive)]
+#[repr(u8)]
+pub(crate) enum HighwayBridge {
+    LeftRight = 0x6A,
+    TopBottom = 0x6B,
+}
+
+impl HighwayBridge {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::LeftRight => "res://resources/Objects/Networks/Highway/bridge_left_right.tscn",
+            Self::TopBottom => "res://resources/Objects/Networks/Highway/bridge_top_bottom.tscn",
+        }
+    }
+}
+
 #[derive(TryFromPrimitive)]
 #[repr(u8)]
 pub(crate) enum SuspensionBridge {
@@ -220,6 +466,49 @@ fn networks(id: u8) -> Option<&'static str> {
                 .as_ref()
                 .map(PylonBridge::as_str)
         })
+        .or_else(|| Rail::try_from_primitive(id).ok().as_ref().map(Rail::as_str))
+        .or_else(|| {
+            RailCrossing::try_from_primitive(id)
+                .ok()
+                .as_ref()
+                .map(RailCrossing::as_str)
+        })
+        .or_else(|| {
+            RailBridge::try_from_primitive(id)
+                .ok()
+                .as_ref()
+                .map(RailBridge::as_str)
+        })
+        .or_else(|| {
+            SubwayTransition::try_from_primitive(id)
+                .ok()
+                .as_ref()
+                .map(SubwayTransition::as_str)
+        })
+        .or_else(|| {
+            Tunnel::try_from_primitive(id)
+                .ok()
+                .as_ref()
+                .map(Tunnel::as_str)
+        })
+        .or_else(|| {
+            Highway::try_from_primitive(id)
+                .ok()
+                .as_ref()
+                .map(Highway::as_str)
+        })
+        .or_else(|| {
+            HighwayOnRamp::try_from_primitive(id)
+                .ok()
+                .as_ref()
+                .map(HighwayOnRamp::as_str)
+        })
+        .or_else(|| {
+            HighwayBridge::try_from_primitive(id)
+                .ok()
+                .as_ref()
+                .map(HighwayBridge::as_str)
+        })
 }
 
 #[derive(TryFromPrimitive, Clone, Copy)]
@@ -296,7 +585,16 @@ pub(crate) enum Buildings {
     Warehouse = 0x82,
     AirportCivilianControlTower = 0xE1,
     StationFire = 0xD3,
+    PowerplantHydro1 = 0xC6,
+    PowerplantHydro2 = 0xC7,
+    PowerplantWind = 0xC8,
+    PowerplantGas = 0xC9,
+    PowerplantOil = 0xCA,
+    PowerplantNuclear = 0xCB,
+    PowerplantSolar = 0xCC,
     PowerplantMicrowave = 0xCD,
+    PowerplantFusion = 0xCE,
+    PowerplantCoal = 0xCF,
     ResortHotel = 0x97,
     ApartmentsLarge1 = 0xAE,
     ApartmentsLarge2 = 0xAF,
@@ -331,159 +629,234 @@ pub(crate) enum Buildings {
 }
 
 impl Buildings {
+    /// Path of the building scene, if the building has one.
     #[expect(clippy::too_many_lines)]
-    fn as_str(self) -> &'static str {
+    fn scene(self) -> Option<&'static str> {
         match self {
-            Self::ParkSmall => "res://resources/Objects/Buildings/park_small.tscn",
-            Self::TreeSingle => "res://resources/Objects/Buildings/tree_single.tscn",
-            Self::HomeMiddleClass1 => "res://resources/Objects/Buildings/home_middle_class_1.tscn",
-            Self::HomeMiddleClass2 => "res://resources/Objects/Buildings/home_middle_class_2.tscn",
-            Self::HomeMiddleClass3 => "res://resources/Objects/Buildings/home_middle_class_3.tscn",
-            Self::HomeMiddleClass4 => "res://resources/Objects/Buildings/home_middle_class_4.tscn",
-            Self::HomeMiddleClass5 => "res://resources/Objects/Buildings/home_middle_class_5.tscn",
-            Self::Church => "res://resources/Objects/Buildings/church.tscn",
+            Self::ParkSmall => Some("res://resources/Objects/Buildings/park_small.tscn"),
+            Self::TreeSingle => Some("res://resources/Objects/Buildings/tree_single.tscn"),
+            Self::HomeMiddleClass1 => {
+                Some("res://resources/Objects/Buildings/home_middle_class_1.tscn")
+            }
+            Self::HomeMiddleClass2 => {
+                Some("res://resources/Objects/Buildings/home_middle_class_2.tscn")
+            }
+            Self::HomeMiddleClass3 => {
+                Some("res://resources/Objects/Buildings/home_middle_class_3.tscn")
+            }
+            Self::HomeMiddleClass4 => {
+                Some("res://resources/Objects/Buildings/home_middle_class_4.tscn")
+            }
+            Self::HomeMiddleClass5 => {
+                Some("res://resources/Objects/Buildings/home_middle_class_5.tscn")
+            }
+            Self::Church => Some("res://resources/Objects/Buildings/church.tscn"),
             Self::OfficeBuildingMedium1 => {
-                "res://resources/Objects/Buildings/office_building_medium_1.tscn"
+                Some("res://resources/Objects/Buildings/office_building_medium_1.tscn")
             }
             Self::OfficeBuildingMedium2 => {
-                "res://resources/Objects/Buildings/office_building_medium_2.tscn"
+                Some("res://resources/Objects/Buildings/office_building_medium_2.tscn")
             }
             Self::OfficeBuildingMedium3 => {
-                "res://resources/Objects/Buildings/office_building_medium_3.tscn"
+                Some("res://resources/Objects/Buildings/office_building_medium_3.tscn")
             }
             Self::OfficeBuildingMedium4 => {
-                "res://resources/Objects/Buildings/office_building_medium_4.tscn"
+                Some("res://resources/Objects/Buildings/office_building_medium_4.tscn")
             }
             Self::OfficeBuildingMedium5 => {
-                "res://resources/Objects/Buildings/office_building_medium_5.tscn"
+                Some("res://resources/Objects/Buildings/office_building_medium_5.tscn")
             }
             Self::OfficeBuildingMedium6 => {
-                "res://resources/Objects/Buildings/office_building_medium_6.tscn"
+                Some("res://resources/Objects/Buildings/office_building_medium_6.tscn")
             }
             Self::AbandonedBuilding1 => {
-                "res://resources/Objects/Buildings/abandoned_building_1.tscn"
+                Some("res://resources/Objects/Buildings/abandoned_building_1.tscn")
             }
             Self::AbandonedBuilding2 => {
-                "res://resources/Objects/Buildings/abandoned_building_2.tscn"
+                Some("res://resources/Objects/Buildings/abandoned_building_2.tscn")
             }
             Self::AbandonedBuilding3 => {
-                "res://resources/Objects/Buildings/abandoned_building_3.tscn"
+                Some("res://resources/Objects/Buildings/abandoned_building_3.tscn")
             }
             Self::AbandonedBuilding4 => {
-                "res://resources/Objects/Buildings/abandoned_building_4.tscn"
+                Some("res://resources/Objects/Buildings/abandoned_building_4.tscn")
             }
             Self::AbandonedBuilding5 => {
-                "res://resources/Objects/Buildings/abandoned_building_5.tscn"
+                Some("res://resources/Objects/Buildings/abandoned_building_5.tscn")
             }
             Self::AbandonedBuilding6 => {
-                "res://resources/Objects/Buildings/abandoned_building_6.tscn"
-            }
-            Self::HomeUpperClass1 => "res://resources/Objects/Buildings/home_upper_class_1.tscn",
-            Self::HomeUpperClass2 => "res://resources/Objects/Buildings/home_upper_class_2.tscn",
-            Self::HomeUpperClass3 => "res://resources/Objects/Buildings/home_upper_class_3.tscn",
-            Self::HomeUpperClass4 => "res://resources/Objects/Buildings/home_upper_class_4.tscn",
-            Self::Tarmac => "res://resources/Objects/Ground/tarmac.tscn",
-            Self::TarmacRadar => "res://resources/Objects/Buildings/tarmac_radar.tscn",
+                Some("res://resources/Objects/Buildings/abandoned_building_6.tscn")
+            }
+            Self::HomeUpperClass1 => {
+                Some("res://resources/Objects/Buildings/home_upper_class_1.tscn")
+            }
+            Self::HomeUpperClass2 => {
+                Some("res://resources/Objects/Buildings/home_upper_class_2.tscn")
+            }
+            Self::HomeUpperClass3 => {
+                Some("res://resources/Objects/Buildings/home_upper_class_3.tscn")
+            }
+            Self::HomeUpperClass4 => {
+                Some("res://resources/Objects/Buildings/home_upper_class_4.tscn")
+            }
+            Self::Tarmac => Some("res://resources/Objects/Ground/tarmac.tscn"),
+            Self::TarmacRadar => Some("res://resources/Objects/Buildings/tarmac_radar.tscn"),
             Self::Construction1 | Self::Construction2 => {
-                "res://resources/Objects/Buildings/construction_1-2.tscn"
-            }
-            Self::Construction3 => "res://resources/Objects/Buildings/construction_3.tscn",
-            Self::Construction4 => "res://resources/Objects/Buildings/construction_4.tscn",
-            Self::Construction5 => "res://resources/Objects/Buildings/construction_5.tscn",
-            Self::Construction6 => "res://resources/Objects/Buildings/construction_6.tscn",
-            Self::Construction7 => "res://resources/Objects/Buildings/construction_7.tscn",
-            Self::Construction8 => "res://resources/Objects/Buildings/construction_8.tscn",
-            Self::AirportWarehouse => "res://resources/Objects/Buildings/airport_warehouse.tscn",
-            Self::AirportBuilding1 => "res://resources/Objects/Buildings/airport_building_1.tscn",
-            Self::AirportBuilding2 => "res://resources/Objects/Buildings/airport_building_2.tscn",
-            Self::AirportHangar1 => "res://resources/Objects/Buildings/airport_hangar_1.tscn",
-            Self::AirportRunway => "res://resources/Objects/Buildings/airport_runway.tscn",
+                Some("res://resources/Objects/Buildings/construction_1-2.tscn")
+            }
+            Self::Construction3 => Some("res://resources/Objects/Buildings/construction_3.tscn"),
+            Self::Construction4 => Some("res://resources/Objects/Buildings/construction_4.tscn"),
+            Self::Construction5 => Some("res://resources/Objects/Buildings/construction_5.tscn"),
+            Self::Construction6 => Some("res://resources/Objects/Buildings/construction_6.tscn"),
+            Self::Construction7 => Some("res://resources/Objects/Buildings/construction_7.tscn"),
+            Self::Construction8 => Some("res://resources/Objects/Buildings/construction_8.tscn"),
+            Self::AirportWarehouse => {
+                Some("res://resources/Objects/Buildings/airport_warehouse.tscn")
+            }
+            Self::AirportBuilding1 => {
+                Some("res://resources/Objects/Buildings/airport_building_1.tscn")
+            }
+            Self::AirportBuilding2 => {
+                Some("res://resources/Objects/Buildings/airport_building_2.tscn")
+            }
+            Self::AirportHangar1 => Some("res://resources/Objects/Buildings/airport_hangar_1.tscn"),
+            Self::AirportRunway => Some("res://resources/Objects/Buildings/airport_runway.tscn"),
             Self::AirportRunwayIntersection => {
-                "res://resources/Objects/Buildings/airport_runway_intersection.tscn"
+                Some("res://resources/Objects/Buildings/airport_runway_intersection.tscn")
             }
-            Self::Hangar2 => "res://resources/Objects/Buildings/hangar_2.tscn",
+            Self::Hangar2 => Some("res://resources/Objects/Buildings/hangar_2.tscn"),
             Self::CondominiumsMedium1 => {
-                "res://resources/Objects/Buildings/condominiums_medium_1.tscn"
+                Some("res://resources/Objects/Buildings/condominiums_medium_1.tscn")
             }
             Self::CondominiumsMedium2 => {
-                "res://resources/Objects/Buildings/condominiums_medium_2.tscn"
+                Some("res://resources/Objects/Buildings/condominiums_medium_2.tscn")
             }
             Self::CondominiumsMedium3 => {
-                "res://resources/Objects/Buildings/condominiums_medium_3.tscn"
+                Some("res://resources/Objects/Buildings/condominiums_medium_3.tscn")
             }
             Self::CondominiumsLarge1 => {
-                "res://resources/Objects/Buildings/condominiums_large_1.tscn"
+                Some("res://resources/Objects/Buildings/condominiums_large_1.tscn")
             }
             Self::CondominiumsLarge2 => {
-                "res://resources/Objects/Buildings/condominiums_large_2.tscn"
-            }
-            Self::FactorySmall1 => "res://resources/Objects/Buildings/factory_small_1.tscn",
-            Self::FactorySmall2 => "res://resources/Objects/Buildings/factory_small_2.tscn",
-            Self::FactorySmall3 => "res://resources/Objects/Buildings/factory_small_3.tscn",
-            Self::FactorySmall4 => "res://resources/Objects/Buildings/factory_small_4.tscn",
-            Self::FactorySmall5 => "res://resources/Objects/Buildings/factory_small_5.tscn",
-            Self::FactorySmall6 => "res://resources/Objects/Buildings/factory_small_6.tscn",
-            Self::StationPolice => "res://resources/Objects/Buildings/station_police.tscn",
-            Self::ApartmentsMedium1 => "res://resources/Objects/Buildings/apartments_medium_1.tscn",
-            Self::ApartmentsMedium2 => "res://resources/Objects/Buildings/apartments_medium_2.tscn",
-            Self::ToyStore => "res://resources/Objects/Buildings/toy_store.tscn",
+                Some("res://resources/Objects/Buildings/condominiums_large_2.tscn")
+            }
+            Self::FactorySmall1 => Some("res://resources/Objects/Buildings/factory_small_1.tscn"),
+            Self::FactorySmall2 => Some("res://resources/Objects/Buildings/factory_small_2.tscn"),
+            Self::FactorySmall3 => Some("res://resources/Objects/Buildings/factory_small_3.tscn"),
+            Self::FactorySmall4 => Some("res://resources/Objects/Buildings/factory_small_4.tscn"),
+            Self::FactorySmall5 => Some("res://resources/Objects/Buildings/factory_small_5.tscn"),
+            Self::FactorySmall6 => Some("res://resources/Objects/Buildings/factory_small_6.tscn"),
+            Self::StationPolice => Some("res://resources/Objects/Buildings/station_police.tscn"),
+            Self::ApartmentsMedium1 => {
+                Some("res://resources/Objects/Buildings/apartments_medium_1.tscn")
+            }
+            Self::ApartmentsMedium2 => {
+                Some("res://resources/Objects/Buildings/apartments_medium_2.tscn")
+            }
+            Self::ToyStore => Some("res://resources/Objects/Buildings/toy_store.tscn"),
             Self::IndustrialSubstation => {
-                "res://resources/Objects/Buildings/industrial_substation.tscn"
-            }
-            Self::OfficesSmall1 => "res://resources/Objects/Buildings/offices_small_1.tscn",
-            Self::OfficesSmall2 => "res://resources/Objects/Buildings/offices_small_2.tscn",
-            Self::OfficesHistoric => "res://resources/Objects/Buildings/offices_historic.tscn",
-            Self::WaterPump => "res://resources/Objects/Buildings/water_pump.tscn",
-            Self::StationHospital => "res://resources/Objects/Buildings/station_hospital.tscn",
-            Self::ConvenienceStore => "res://resources/Objects/Buildings/convenience_store.tscn",
-            Self::StationGas1 => "res://resources/Objects/Buildings/station_gas_1.tscn",
-            Self::StationGas2 => "res://resources/Objects/Buildings/station_gas_2.tscn",
-            Self::HomeLowerClass1 => "res://resources/Objects/Buildings/home_lower_class_1.tscn",
-            Self::HomeLowerClass2 => "res://resources/Objects/Buildings/home_lower_class_2.tscn",
-            Self::HomeLowerClass3 => "res://resources/Objects/Buildings/home_lower_class_3.tscn",
-            Self::Warehouse => "res://resources/Objects/Buildings/warehouse.tscn",
+                Some("res://resources/Objects/Buildings/industrial_substation.tscn")
+            }
+            Self::OfficesSmall1 => Some("res://resources/Objects/Buildings/offices_small_1.tscn"),
+            Self::OfficesSmall2 => Some("res://resources/Objects/Buildings/offices_small_2.tscn"),
+            Self::OfficesHistoric => {
+                Some("res://resources/Objects/Buildings/offices_historic.tscn")
+            }
+            Self::WaterPump => Some("res://resources/Objects/Buildings/water_pump.tscn"),
+            Self::StationHospital => {
+                Some("res://resources/Objects/Buildings/station_hospital.tscn")
+            }
+            Self::ConvenienceStore => {
+                Some("res://resources/Objects/Buildings/convenience_store.tscn")
+            }
+            Self::StationGas1 => Some("res://resources/Objects/Buildings/station_gas_1.tscn"),
+            Self::StationGas2 => Some("res://resources/Objects/Buildings/station_gas_2.tscn"),
+            Self::HomeLowerClass1 => {
+                Some("res://resources/Objects/Buildings/home_lower_class_1.tscn")
+            }
+            Self::HomeLowerClass2 => {
+                Some("res://resources/Objects/Buildings/home_lower_class_2.tscn")
+            }
+            Self::HomeLowerClass3 => {
+                Some("res://resources/Objects/Buildings/home_lower_class_3.tscn")
+            }
+            Self::Warehouse => Some("res://resources/Objects/Buildings/warehouse.tscn"),
             Self::AirportCivilianControlTower => {
-                "res://resources/Objects/Buildings/airport_civilian_control_tower.tscn"
+                Some("res://resources/Objects/Buildings/airport_civilian_control_tower.tscn")
             }
-            Self::StationFire => "res://resources/Objects/Buildings/station_fire.tscn",
+            Self::StationFire => Some("res://resources/Objects/Buildings/station_fire.tscn"),
             Self::PowerplantMicrowave => {
-                "res://resources/Objects/Buildings/powerplant_microwave.tscn"
-            }
-            Self::ResortHotel => "res://resources/Objects/Buildings/resort_hotel.tscn",
-            Self::ApartmentsLarge1 => "res://resources/Objects/Buildings/apartments_large_1.tscn",
-            Self::ApartmentsLarge2 => "res://resources/Objects/Buildings/apartments_large_2.tscn",
-            Self::ApartmentsSmall1 => "res://resources/Objects/Buildings/apartments_small_1.tscn",
-            Self::ApartmentsSmall2 => "res://resources/Objects/Buildings/apartments_small_2.tscn",
-            Self::ApartmentsSmall3 => "res://resources/Objects/Buildings/apartments_small_3.tscn",
-            Self::TreeCouple => "res://resources/Objects/Buildings/tree_couple.tscn",
-            Self::ChemicalStorage => "res://resources/Objects/Buildings/chemical_storage.tscn",
+                Some("res://resources/Objects/Buildings/powerplant_microwave.tscn")
+            }
+            Self::ResortHotel => Some("res://resources/Objects/Buildings/resort_hotel.tscn"),
+            Self::ApartmentsLarge1 => {
+                Some("res://resources/Objects/Buildings/apartments_large_1.tscn")
+            }
+            Self::ApartmentsLarge2 => {
+                Some("res://resources/Objects/Buildings/apartments_large_2.tscn")
+            }
+            Self::ApartmentsSmall1 => {
+                Some("res://resources/Objects/Buildings/apartments_small_1.tscn")
+            }
+            Self::ApartmentsSmall2 => {
+                Some("res://resources/Objects/Buildings/apartments_small_2.tscn")
+            }
+            Self::ApartmentsSmall3 => {
+                Some("res://resources/Objects/Buildings/apartments_small_3.tscn")
+            }
+            Self::TreeCouple => Some("res://resources/Objects/Buildings/tree_couple.tscn"),
+            Self::ChemicalStorage => {
+                Some("res://resources/Objects/Buildings/chemical_storage.tscn")
+            }
             Self::ChemicalProcessing1 => {
-                "res://resources/Objects/Buildings/chemical_processing_1.tscn"
+                Some("res://resources/Objects/Buildings/chemical_processing_1.tscn")
             }
             Self::ChemicalProcessing2 => {
-                "res://resources/Objects/Buildings/chemical_processing_2.tscn"
-            }
-            Self::School => "res://resources/Objects/Buildings/school.tscn",
-            Self::Library => "res://resources/Objects/Buildings/library.tscn",
-            Self::Marina => "res://resources/Objects/Buildings/marina.tscn",
-            Self::WarehouseLarge1 => "res://resources/Objects/Buildings/warehouse_large_1.tscn",
-            Self::WarehouseLarge2 => "res://resources/Objects/Buildings/warehouse_large_2.tscn",
-            Self::WarehouseSmall1 => "res://resources/Objects/Buildings/warehouse_small_1.tscn",
-            Self::WarehouseSmall2 => "res://resources/Objects/Buildings/warehouse_small_2.tscn",
-            Self::WarehouseMedium => "res://resources/Objects/Buildings/warehouse_medium.tscn",
-            Self::BbInn => "res://resources/Objects/Buildings/bb_inn.tscn",
-            Self::College => "res://resources/Objects/Buildings/college.tscn",
-            Self::ArcologyPlymouth => "res://resources/Objects/Buildings/arcology_plymouth.tscn",
-            Self::ArcologyForest => "res://resources/Objects/Buildings/arcology_forest.tscn",
-            Self::ArcologyDarco => "res://resources/Objects/Buildings/arcology_darco.tscn",
-            Self::ArcologyLaunch => "res://resources/Objects/Buildings/arcology_launch.tscn",
-            Self::MayorsHouse => "res://resources/Objects/Buildings/mayors_house.tscn",
-            Self::Museum => "res://resources/Objects/Buildings/museum.tscn",
-            Self::OfficeRetail => "res://resources/Objects/Buildings/office_retail.tscn",
-            Self::ParkingLot => "res://resources/Objects/Buildings/parking_lot.tscn",
-            Self::ShoppingCentre => "res://resources/Objects/Buildings/shopping_centre.tscn",
-            Self::Theatre => "res://resources/Objects/Buildings/theatre.tscn",
-            Self::WaterTreatment => "res://resources/Objects/Buildings/water_treatment.tscn",
+                Some("res://resources/Objects/Buildings/chemical_processing_2.tscn")
+            }
+            Self::School => Some("res://resources/Objects/Buildings/school.tscn"),
+            Self::Library => Some("res://resources/Objects/Buildings/library.tscn"),
+            Self::Marina => Some("res://resources/Objects/Buildings/marina.tscn"),
+            Self::WarehouseLarge1 => {
+                Some("res://resources/Objects/Buildings/warehouse_large_1.tscn")
+            }
+            Self::WarehouseLarge2 => {
+                Some("res://resources/Objects/Buildings/warehouse_large_2.tscn")
+            }
+            Self::WarehouseSmall1 => {
+                Some("res://resources/Objects/Buildings/warehouse_small_1.tscn")
+            }
+            Self::WarehouseSmall2 => {
+                Some("res://resources/Objects/Buildings/warehouse_small_2.tscn")
+            }
+            Self::WarehouseMedium => {
+                Some("res://resources/Objects/Buildings/warehouse_medium.tscn")
+            }
+            Self::BbInn => Some("res://resources/Objects/Buildings/bb_inn.tscn"),
+            Self::College => Some("res://resources/Objects/Buildings/college.tscn"),
+            Self::ArcologyPlymouth => {
+                Some("res://resources/Objects/Buildings/arcology_plymouth.tscn")
+            }
+            Self::ArcologyForest => Some("res://resources/Objects/Buildings/arcology_forest.tscn"),
+            Self::ArcologyDarco => Some("res://resources/Objects/Buildings/arcology_darco.tscn"),
+            Self::ArcologyLaunch => Some("res://resources/Objects/Buildings/arcology_launch.tscn"),
+            Self::MayorsHouse => Some("res://resources/Objects/Buildings/mayors_house.tscn"),
+            Self::Museum => Some("res://resources/Objects/Buildings/museum.tscn"),
+            Self::OfficeRetail => Some("res://resources/Objects/Buildings/office_retail.tscn"),
+            Self::ParkingLot => Some("res://resources/Objects/Buildings/parking_lot.tscn"),
+            Self::ShoppingCentre => Some("res://resources/Objects/Buildings/shopping_centre.tscn"),
+            Self::Theatre => Some("res://resources/Objects/Buildings/theatre.tscn"),
+            Self::WaterTreatment => Some("res://resources/Objects/Buildings/water_treatment.tscn"),
+            // only the power grid knows about these plants so far.
+            Self::PowerplantHydro1
+            | Self::PowerplantHydro2
+            | Self::PowerplantWind
+            | Self::PowerplantGas
+            | Self::PowerplantOil
+            | Self::PowerplantNuclear
+            | Self::PowerplantSolar
+            | Self::PowerplantFusion
+            | Self::PowerplantCoal => None,
         }
     }
 }
@@ -503,7 +876,7 @@ impl PartialEq<Buildings> for u8 {
 fn buildings(id: u8) -> Option<&'static str> {
     Buildings::try_from_primitive(id)
         .ok()
-        .map(Buildings::as_str)
+        .and_then(Buildings::scene)
 }
 
 fn load(parser: fn(u8) -> Option<&'static str>, object_id: u8) -> Option<Gd<PackedScene>> {
@@ -522,3 +895,35 @@ pub fn load_network(object_id: u8) -> Option<Gd<PackedScene>> {
 pub fn load_building(object_id: u8) -> Option<Gd<PackedScene>> {
     load(buildings, object_id)
 }
+
+/// Checks if there is a scene for the building id.
+pub fn has_building(object_id: u8) -> bool {
+    buildings(object_id).is_some()
+}
+
+/// Checks if the building id is one of the power plants.
+pub(crate) fn is_power_plant(object_id: u8) -> bool {
+    matches!(
+        Buildings::try_from_primitive(object_id),
+        Ok(Buildings::PowerplantHydro1
+            | Buildings::PowerplantHydro2
+            | Buildings::PowerplantWind
+            | Buildings::PowerplantGas
+            | Buildings::PowerplantOil
+            | Buildings::PowerplantNuclear
+            | Buildings::PowerplantSolar
+            | Buildings::PowerplantMicrowave
+            | Buildings::PowerplantFusion
+            | Buildings::PowerplantCoal)
+    )
+}
+
+/// Checks if the building scene has fire emission points. Buildings without them can not burn.
+pub(crate) fn can_burn(object_id: u8) -> bool {
+    matches!(
+        Buildings::try_from_primitive(object_id),
+        Ok(Buildings::HomeMiddleClass3
+            | Buildings::OfficeBuildingMedium4
+            | Buildings::CondominiumsMedium2)
+    )
+}
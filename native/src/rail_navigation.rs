@@ -0,0 +1,405 @@
+/*
+ * Copyright (c) SimChopper; Jovan Gerodetti and contributors.
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! Navigation graph of the rail network.
+//!
+//! Tracks are modelled like roads, every piece connects a set of tile sides and trains can only
+//! leave a piece through the sides that are not behind them.
+
+use std::cell::OnceCell;
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use godot::builtin::Vector3;
+use godot::classes::Node3D;
+use godot::obj::Gd;
+use godot::prelude::{godot_api, GodotClass};
+use num_enum::TryFromPrimitive;
+use rand::distr::Uniform;
+use rand::Rng;
+
+use crate::objects::scene_object_registry::{
+    Highway, Rail, RailBridge, RailCrossing, SubwayTransition,
+};
+use crate::road_navigation::find_route;
+use crate::road_navigation::lanes::{self, Lane, Side};
+use crate::util::logger;
+use crate::world::city_data::{Building, TileCoords};
+use crate::world::city_loader::CityHandle;
+
+/// Sides of a tile that are connected by a rail piece. Rail pieces are named like the road pieces,
+/// see [`lanes::road_sides`].
+///
+/// Rail bridges do not encode their orientation, they span the axis of their neighbouring pieces.
+fn rail_sides(building_id: u8) -> Option<&'static [Side]> {
+    if let Ok(crossing) = RailCrossing::try_from_primitive(building_id) {
+        return Some(match crossing {
+            RailCrossing::RoadLeftRightRailTopBottom => &[Side::Right, Side::Left],
+            RailCrossing::RoadTopBottomRailLeftRight => &[Side::Top, Side::Bottom],
+        });
+    }
+
+    // tracks that pass below a highway.
+    match Highway::try_from_primitive(building_id) {
+        Ok(Highway::LeftRightRailTopBottom) => return Some(&[Side::Right, Side::Left]),
+        Ok(Highway::TopBottomRailLeftRight) => return Some(&[Side::Top, Side::Bottom]),
+        _ => (),
+    }
+
+    if let Ok(transition) = SubwayTransition::try_from_primitive(building_id) {
+        return Some(match transition {
+            SubwayTransition::Top => &[Side::Right],
+            SubwayTransition::Right => &[Side::Top],
+            SubwayTransition::Bottom => &[Side::Left],
+            SubwayTransition::Left => &[Side::Bottom],
+        });
+    }
+
+    let rail = Rail::try_from_primitive(building_id).ok()?;
+
+    let sides: &[Side] = match rail {
+        Rail::LeftRight
+        | Rail::LeftHighRight
+        | Rail::HighLeftRight
+        | Rail::RampLeftHighRight
+        | Rail::RampHighLeftRight
+        | Rail::LeftRightPowerTopBottom => &[Side::Top, Side::Bottom],
+        Rail::TopBottom
+        | Rail::HighTopBottom
+        | Rail::TopHighBottom
+        | Rail::RampHighTopBottom
+        | Rail::RampTopHighBottom
+        | Rail::TopBottomPowerLeftRight => &[Side::Right, Side::Left],
+        Rail::TopRight => &[Side::Top, Side::Right],
+        Rail::BottomRight => &[Side::Right, Side::Bottom],
+        Rail::BottomLeft => &[Side::Bottom, Side::Left],
+        Rail::TopLeft => &[Side::Top, Side::Left],
+        Rail::RightTopBottom => &[Side::Top, Side::Right, Side::Left],
+        Rail::LeftBottomRight => &[Side::Top, Side::Right, Side::Bottom],
+        Rail::TopLeftBottom => &[Side::Right, Side::Bottom, Side::Left],
+        Rail::LeftTopRight => &[Side::Top, Side::Bottom, Side::Left],
+        Rail::LeftTopBottomRight => &Side::ALL,
+    };
+
+    Some(sides)
+}
+
+/// Sides of a rail bridge. Bridges span the axis along which they have neighbouring rail pieces.
+fn bridge_sides(tile_coords: TileCoords, is_rail: impl Fn(TileCoords) -> bool) -> [Side; 2] {
+    let vertical = [Side::Top, Side::Bottom]
+        .into_iter()
+        .filter_map(|side| side.neighbor(tile_coords))
+        .any(is_rail);
+
+    if vertical {
+        [Side::Top, Side::Bottom]
+    } else {
+        [Side::Left, Side::Right]
+    }
+}
+
+struct RailNode {
+    building: Building,
+    object: Gd<Node3D>,
+    sides: OnceLock<Box<[Side]>>,
+}
+
+#[derive(Default)]
+pub(crate) struct RailNavigation {
+    network: BTreeMap<TileCoords, RailNode>,
+}
+
+impl RailNavigation {
+    pub fn insert_node(&mut self, building: Building, object: Gd<Node3D>) {
+        let tile_coords = building.tile_coords;
+        let node = RailNode {
+            building,
+            object,
+            sides: OnceLock::new(),
+        };
+
+        self.network.insert(tile_coords, node);
+    }
+
+    /// World position of the track in the center of the node. Pieces that are not level mark
+    /// their center with a `TrackCenter` node.
+    pub fn position(&self, tile_coords: TileCoords) -> Option<Vector3> {
+        let node = self.network.get(&tile_coords)?;
+
+        let position = node
+            .object
+            .try_get_node_as::<Node3D>("TrackCenter")
+            .map_or_else(
+                || node.object.get_global_position(),
+                |center| center.get_global_position(),
+            );
+
+        Some(position)
+    }
+
+    fn sides(&self, tile_coords: TileCoords) -> Option<&[Side]> {
+        let node = self.network.get(&tile_coords)?;
+
+        let sides = node.sides.get_or_init(|| {
+            let building_id = node.building.id;
+
+            if let Some(sides) = rail_sides(building_id) {
+                return sides.into();
+            }
+
+            if RailBridge::try_from_primitive(building_id).is_err() {
+                logger::warn!("building {:#x} is not a rail piece", building_id);
+                return Box::default();
+            }
+
+            bridge_sides(tile_coords, |neighbor| self.network.contains_key(&neighbor)).into()
+        });
+
+        Some(sides)
+    }
+
+    /// Plans the shortest route from the start to the target node. Trains never reverse unless
+    /// the track ends. If no route can be found for the heading, the train is allowed to reverse
+    /// at the start.
+    pub fn plan_route(
+        &self,
+        start: TileCoords,
+        heading: Option<Side>,
+        target: TileCoords,
+    ) -> Option<Vec<TileCoords>> {
+        if !self.network.contains_key(&target) {
+            return None;
+        }
+
+        let route_from = |entered_from| {
+            find_route(
+                Lane::new(start, entered_from),
+                |lane| lane.tile == target,
+                |lane| lane.tile.0.abs_diff(target.0) + lane.tile.1.abs_diff(target.1),
+                |lane| lanes::successors(lane, |tile| self.sides(tile)),
+            )
+        };
+
+        let route = heading
+            .and_then(|heading| route_from(Some(heading.opposite())))
+            .or_else(|| route_from(None))?;
+
+        Some(route.into_iter().map(|lane| lane.tile).collect())
+    }
+
+    pub fn get_random_node(&self) -> Option<TileCoords> {
+        if self.network.is_empty() {
+            return None;
+        }
+
+        let distribution =
+            Uniform::new(0, self.network.len()).expect("network contains at least one node");
+        let index = rand::rng().sample(distribution);
+
+        self.network.keys().nth(index).copied()
+    }
+}
+
+/// Configuration resource to setup rail navigation for trains.
+#[derive(GodotClass)]
+#[class(base = Resource, init)]
+pub struct RailNavigationConfig {
+    instance: OnceCell<RailNavigation>,
+}
+
+impl RailNavigationConfig {
+    pub(crate) fn rail_navigation(&self) -> &RailNavigation {
+        self.instance.get_or_init(RailNavigation::default)
+    }
+
+    pub(crate) fn rail_navigation_mut(&mut self) -> &mut RailNavigation {
+        // make sure instance is initialized.
+        self.rail_navigation();
+
+        self.instance.get_mut().expect("we just initialized")
+    }
+}
+
+#[godot_api]
+impl RailNavigationConfig {
+    /// Insert the network section of a loaded city at the given tile coordinates into the rail
+    /// navigation graph.
+    #[func]
+    #[expect(clippy::needless_pass_by_value)]
+    pub fn insert_city_node(
+        &mut self,
+        city: Gd<CityHandle>,
+        x: u32,
+        y: u32,
+        scene_node: Gd<Node3D>,
+    ) {
+        let Some(building) = city.bind().city().networks.get(&(x, y)).cloned() else {
+            logger::error!("city has no network section at x = {}, y = {}", x, y);
+            return;
+        };
+
+        self.rail_navigation_mut().insert_node(building, scene_node);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+    use std::path::Path;
+
+    use num_enum::TryFromPrimitive;
+
+    use super::{bridge_sides, rail_sides};
+    use crate::objects::scene_object_registry::{
+        Highway, Rail, RailBridge, RailCrossing, Road, SubwayTransition,
+    };
+    use crate::road_navigation::find_route;
+    use crate::road_navigation::lanes::{successors, Lane, Side};
+    use crate::world::city_data::TileCoords;
+    use crate::world::city_loader::load_city_file;
+
+    /// Resolves the sides of all pieces like the navigation graph does.
+    fn network(pieces: &[(TileCoords, u8)]) -> BTreeMap<TileCoords, Vec<Side>> {
+        let ids: BTreeMap<TileCoords, u8> = pieces.iter().copied().collect();
+
+        ids.iter()
+            .map(|(tile, id)| {
+                let sides = if RailBridge::try_from_primitive(*id).is_ok() {
+                    bridge_sides(*tile, |neighbor| ids.contains_key(&neighbor)).to_vec()
+                } else {
+                    rail_sides(*id).unwrap().to_vec()
+                };
+
+                (*tile, sides)
+            })
+            .collect()
+    }
+
+    fn route(
+        network: &BTreeMap<TileCoords, Vec<Side>>,
+        start: TileCoords,
+        target: TileCoords,
+    ) -> Option<Vec<TileCoords>> {
+        let route = find_route(
+            Lane::new(start, None),
+            |lane| lane.tile == target,
+            |_| 0,
+            |lane| successors(lane, |tile| network.get(&tile).map(Vec::as_slice)),
+        )?;
+
+        Some(route.into_iter().map(|lane| lane.tile).collect())
+    }
+
+    #[test]
+    fn only_rail_pieces_have_sides() {
+        assert_eq!(rail_sides(Road::TopBottom as u8), None);
+        assert_eq!(rail_sides(Highway::TopBottom as u8), None);
+        assert_eq!(rail_sides(RailBridge::Deck as u8), None);
+        assert_eq!(
+            rail_sides(Rail::LeftTopBottomRight as u8),
+            Some(Side::ALL.as_slice())
+        );
+    }
+
+    #[test]
+    fn rail_pieces_of_a_bundled_map_agree_with_their_neighbours() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../resources/Maps/tokyo.sc2.mpz");
+        let city = load_city_file(&path).expect("tokyo map is valid");
+
+        let tracks: BTreeMap<TileCoords, &[Side]> = city
+            .networks
+            .iter()
+            .filter(|(_, piece)| Rail::try_from_primitive(piece.id).is_ok())
+            .map(|(tile, piece)| (*tile, rail_sides(piece.id).unwrap()))
+            .collect();
+
+        assert!(!tracks.is_empty());
+
+        for (tile, sides) in &tracks {
+            for side in *sides {
+                let Some(neighbor_sides) = side.neighbor(*tile).and_then(|tile| tracks.get(&tile))
+                else {
+                    continue;
+                };
+
+                assert!(
+                    neighbor_sides.contains(&side.opposite()),
+                    "track at {tile:?} connects {side:?}, but its neighbour does not connect back",
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn crossings_continue_the_track() {
+        // a track along the x-axis that crosses a road and passes below a highway.
+        let track = network(&[
+            ((0, 0), Rail::TopBottom as u8),
+            ((1, 0), RailCrossing::RoadLeftRightRailTopBottom as u8),
+            ((2, 0), Highway::LeftRightRailTopBottom as u8),
+            ((3, 0), Rail::TopBottom as u8),
+        ]);
+
+        assert_eq!(
+            route(&track, (0, 0), (3, 0)),
+            Some(vec![(0, 0), (1, 0), (2, 0), (3, 0)])
+        );
+
+        // the road of the crossing is not part of the rail network.
+        let along_road = network(&[
+            ((0, 0), Rail::LeftRight as u8),
+            ((0, 1), RailCrossing::RoadLeftRightRailTopBottom as u8),
+        ]);
+
+        assert_eq!(route(&along_road, (0, 0), (0, 1)), None);
+    }
+
+    #[test]
+    fn bridges_span_their_neighbours() {
+        let along_x = network(&[
+            ((0, 1), Rail::TopBottom as u8),
+            ((1, 1), RailBridge::Pylon as u8),
+            ((2, 1), RailBridge::Deck as u8),
+            ((3, 1), Rail::TopBottom as u8),
+        ]);
+
+        assert_eq!(along_x.get(&(2, 1)), Some(&vec![Side::Left, Side::Right]));
+        assert_eq!(
+            route(&along_x, (0, 1), (3, 1)),
+            Some(vec![(0, 1), (1, 1), (2, 1), (3, 1)])
+        );
+
+        let along_y = network(&[
+            ((1, 0), Rail::LeftRight as u8),
+            ((1, 1), RailBridge::Deck as u8),
+            ((1, 2), Rail::LeftRight as u8),
+        ]);
+
+        assert_eq!(along_y.get(&(1, 1)), Some(&vec![Side::Top, Side::Bottom]));
+        assert_eq!(
+            route(&along_y, (1, 0), (1, 2)),
+            Some(vec![(1, 0), (1, 1), (1, 2)])
+        );
+    }
+
+    #[test]
+    fn subway_transitions_lead_into_the_tunnel() {
+        let network = network(&[
+            ((0, 0), Rail::TopBottom as u8),
+            ((1, 0), SubwayTransition::Top as u8),
+            ((2, 0), Rail::TopBottom as u8),
+        ]);
+        let sides_of = |tile: TileCoords| network.get(&tile).map(Vec::as_slice);
+
+        // the transition only connects to the track on the side that leads above ground.
+        assert_eq!(
+            successors(Lane::new((1, 0), None), sides_of),
+            vec![Lane::new((2, 0), Some(Side::Left))]
+        );
+        assert_eq!(route(&network, (0, 0), (1, 0)), None);
+    }
+}
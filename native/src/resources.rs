@@ -7,9 +7,21 @@
 
 mod input_device;
 mod items;
+mod map_catalog;
+mod mission_board;
+mod mission_chain;
+mod wallet;
 mod water_decal_tracker;
+mod water_tank;
 mod world_constants;
 
 pub(crate) use input_device::InputDevice;
+pub use items::helicopter_upgrade::HelicopterUpgrade;
+pub use map_catalog::MapCatalog;
+pub use mission_board::MissionBoard;
+pub(crate) use mission_board::MissionUpdate;
+pub use mission_chain::{ChainedMission, MissionChain};
+pub use wallet::Wallet;
 pub use water_decal_tracker::WaterDecalTracker;
+pub use water_tank::WaterTank;
 pub use world_constants::*;
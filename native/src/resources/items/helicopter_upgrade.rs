@@ -12,7 +12,7 @@ use godot::prelude::GodotClass;
 
 #[derive(GodotClass)]
 #[class(base = Resource, init)]
-struct HelicopterUpgrade {
+pub struct HelicopterUpgrade {
     /// Name of the upgrade
     #[export]
     name: GString,
@@ -26,9 +26,20 @@ struct HelicopterUpgrade {
     #[var(hint = INPUT_NAME)]
     action: StringName,
 
+    /// Optional input action which switches the mode of the game object of this upgrade.
+    #[export]
+    #[var(hint = INPUT_NAME)]
+    secondary_action: StringName,
+
     /// The price of this upgrade.
     #[export]
     price: u32,
 
     base: Base<Resource>,
 }
+
+impl HelicopterUpgrade {
+    pub(crate) fn price(&self) -> u32 {
+        self.price
+    }
+}
@@ -0,0 +1,335 @@
+/*
+ * Copyright (c) SimChopper; Jovan Gerodetti and contributors.
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use godot::builtin::{Array, GString};
+use godot::classes::{DirAccess, FileAccess, RefCounted, Resource};
+use godot::obj::{Base, Gd};
+use godot::prelude::{godot_api, GodotClass};
+use godot::task;
+use kanal::ReceiveError;
+
+use crate::util::async_support::{godot_future, GodotFuture};
+use crate::util::logger;
+use crate::world::city_data::{SimulatorSettings, TryFromValue};
+use crate::world::city_loader::msgpack::Reader;
+use crate::world::city_loader::{decompress_map, CityLoaderError};
+use crate::world::sc2_file::city_name;
+
+const MAP_EXTENSION: &str = ".sc2.mpz";
+
+/// Catalog of all city maps that are shipped with the game.
+#[derive(GodotClass)]
+#[class(base = Resource, init)]
+pub struct MapCatalog {
+    /// Directory that is searched for `.sc2.mpz` maps, including its sub directories.
+    #[export(dir)]
+    maps_directory: GString,
+
+    /// The map that is loaded when no other map has been picked.
+    #[export(file = "*.mpz")]
+    default_map: GString,
+
+    base: Base<Resource>,
+}
+
+#[godot_api]
+impl MapCatalog {
+    /// Read the header of every map in the catalog. The returned future completes with an array
+    /// of [`MapInfo`] objects.
+    #[func]
+    fn load_maps_async(&self) -> Gd<GodotFuture> {
+        let maps = self.load_maps();
+        let (resolve, future) = godot_future::<Array<Gd<MapInfo>>>();
+
+        task::spawn(async move {
+            resolve(maps.await.into_iter().collect());
+        });
+
+        future
+    }
+
+    #[func]
+    pub fn default_map(&self) -> GString {
+        self.default_map.clone()
+    }
+}
+
+impl MapCatalog {
+    /// Read the header of every map in the catalog on a background thread.
+    pub(crate) fn load_maps(&self) -> impl Future<Output = Vec<Gd<MapInfo>>> + 'static {
+        let maps_directory = self.maps_directory.to_string();
+        let (tx, rx) = kanal::bounded::<Vec<MapHeader>>(1);
+
+        std::thread::spawn(move || {
+            let mut paths = Vec::new();
+
+            collect_map_paths(&maps_directory, &mut paths);
+            paths.sort();
+
+            let headers = paths
+                .into_iter()
+                .filter_map(|path| {
+                    read_map_header(&path)
+                        .inspect_err(|err| {
+                            logger::error!("failed to read map header of \"{}\": {}", path, err);
+                        })
+                        .ok()
+                })
+                .collect();
+
+            if let Err(err) = tx.send(headers) {
+                logger::error!("Failed to send map headers: {}", err);
+            }
+        });
+
+        async move {
+            match rx.as_async().recv().await {
+                Ok(headers) => headers.into_iter().map(MapInfo::new_gd).collect(),
+                Err(ReceiveError::SendClosed | ReceiveError::Closed) => {
+                    logger::error!("map catalog thread has disconnected before completing!");
+                    Vec::new()
+                }
+            }
+        }
+    }
+}
+
+/// Collects the paths of all maps inside the directory and its sub directories.
+fn collect_map_paths(directory: &str, paths: &mut Vec<String>) {
+    let directory = directory.trim_end_matches('/');
+
+    paths.extend(
+        DirAccess::get_files_at(directory)
+            .as_slice()
+            .iter()
+            .map(ToString::to_string)
+            .filter(|file| file.ends_with(MAP_EXTENSION))
+            .map(|file| format!("{directory}/{file}")),
+    );
+
+    for sub_directory in DirAccess::get_directories_at(directory).as_slice() {
+        collect_map_paths(&format!("{directory}/{sub_directory}"), paths);
+    }
+}
+
+struct MapHeader {
+    path: String,
+    name: String,
+    size: u32,
+    sea_level: u32,
+    building_count: u32,
+}
+
+/// Reads the top level fields of a map, without decoding the tile data.
+fn read_map_header(path: &str) -> Result<MapHeader, CityLoaderError> {
+    let bytes = FileAccess::get_file_as_bytes(path);
+
+    if bytes.is_empty() {
+        return Err(CityLoaderError::Read(path.to_owned()));
+    }
+
+    parse_map_header(bytes.as_slice(), path)
+}
+
+fn parse_map_header(bytes: &[u8], path: &str) -> Result<MapHeader, CityLoaderError> {
+    let data = decompress_map(bytes)?;
+    let mut reader = Reader::new(&data);
+    let mut header = MapHeader {
+        path: path.to_owned(),
+        name: String::new(),
+        size: 0,
+        sea_level: 0,
+        building_count: 0,
+    };
+
+    for _ in 0..reader.read_map_len()? {
+        match reader.read_raw_str()? {
+            b"city_name" => header.name = display_name(reader.read_raw_str()?, path),
+            b"city_size" => {
+                header.size = u32::try_from(reader.read_int()?)
+                    .map_err(|_| CityLoaderError::InvalidHeader("city_size"))?;
+            }
+            b"simulator_settings" => {
+                let settings = reader.read_value()?;
+
+                header.sea_level = SimulatorSettings::try_from_value(&settings)?.sea_level;
+            }
+            b"buildings" => header.building_count = count_buildings(&mut reader)?,
+            _ => reader.skip_value()?,
+        }
+    }
+
+    Ok(header)
+}
+
+/// Counts the buildings of a map without decoding them. Empty tiles are stored as buildings with
+/// the id 0.
+fn count_buildings(reader: &mut Reader<'_>) -> Result<u32, CityLoaderError> {
+    let mut count = 0_u32;
+
+    for _ in 0..reader.read_map_len()? {
+        // the tile coordinates of the building.
+        reader.skip_value()?;
+
+        let mut building_id = 0;
+
+        for _ in 0..reader.read_map_len()? {
+            if reader.read_raw_str()? == b"building_id" {
+                building_id = reader.read_int()?;
+            } else {
+                reader.skip_value()?;
+            }
+        }
+
+        count += u32::from(building_id != 0);
+    }
+
+    Ok(count)
+}
+
+/// The city name is stored with its length in front, see [`city_name`]. The converted maps lose
+/// the length of the name, they use their file name instead.
+fn display_name(raw: &[u8], path: &str) -> String {
+    if let Some(name) = city_name(raw) {
+        return name;
+    }
+
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+
+    file_name
+        .strip_suffix(MAP_EXTENSION)
+        .unwrap_or(file_name)
+        .to_owned()
+}
+
+/// Summary of a map in the [`MapCatalog`].
+#[derive(GodotClass)]
+#[class(base = RefCounted, no_init)]
+pub struct MapInfo {
+    path: GString,
+    name: GString,
+    size: u32,
+    sea_level: u32,
+    building_count: u32,
+}
+
+impl MapInfo {
+    fn new_gd(header: MapHeader) -> Gd<Self> {
+        Gd::from_object(Self {
+            path: header.path.into(),
+            name: header.name.into(),
+            size: header.size,
+            sea_level: header.sea_level,
+            building_count: header.building_count,
+        })
+    }
+}
+
+#[godot_api]
+impl MapInfo {
+    #[func]
+    pub fn path(&self) -> GString {
+        self.path.clone()
+    }
+
+    #[func]
+    pub fn name(&self) -> GString {
+        self.name.clone()
+    }
+
+    #[func]
+    fn size(&self) -> u32 {
+        self.size
+    }
+
+    #[func]
+    fn sea_level(&self) -> u32 {
+        self.sea_level
+    }
+
+    #[func]
+    fn building_count(&self) -> u32 {
+        self.building_count
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use super::{count_buildings, display_name, parse_map_header, MAP_EXTENSION};
+    use crate::world::city_loader::msgpack::Reader;
+
+    const PATH: &str = "res://resources/Maps/scenarios/volcano.sc2.mpz";
+
+    #[test]
+    fn name_ends_after_its_length() {
+        assert_eq!(
+            display_name(b"\x07VOLCANO\0PlaceWindow::DrawHouse", PATH),
+            "VOLCANO"
+        );
+        assert_eq!(display_name(b"\x06triciaHlwJ(", PATH), "tricia");
+        assert_eq!(display_name(b"\x0ACape Wells\xFFyy", PATH), "Cape Wells");
+    }
+
+    #[test]
+    fn missing_name_uses_the_file_name() {
+        assert_eq!(display_name(b"\0garbage", PATH), "volcano");
+        assert_eq!(display_name(b"\x02  ", PATH), "volcano");
+        // the length of the name has been stripped.
+        assert_eq!(
+            display_name(b"VOLCANOPlaceWindow::DrawHouse", PATH),
+            "volcano"
+        );
+    }
+
+    #[test]
+    fn bundled_maps_are_named_after_their_file() {
+        let maps_directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("../resources/Maps");
+        let directories = [maps_directory.clone(), maps_directory.join("career")];
+
+        let paths: Vec<_> = directories
+            .iter()
+            .flat_map(|directory| std::fs::read_dir(directory).expect("maps directory exists"))
+            .map(|entry| entry.expect("map entry is readable").path())
+            .filter(|path| path.to_string_lossy().ends_with(MAP_EXTENSION))
+            .collect();
+
+        assert!(!paths.is_empty());
+
+        for path in paths {
+            let bytes = std::fs::read(&path).expect("map is readable");
+            let path = path.to_string_lossy();
+            let header = parse_map_header(&bytes, &path).expect("map header is valid");
+            let file_name = path.rsplit('/').next().unwrap_or(&path);
+
+            assert_eq!(
+                Some(header.name.as_str()),
+                file_name.strip_suffix(MAP_EXTENSION)
+            );
+        }
+    }
+
+    #[test]
+    fn empty_tiles_are_not_counted_as_buildings() {
+        // {[0, 0]: {"building_id": 0x70, "size": 1}, [1, 0]: {"building_id": 0}}
+        let parts: [&[u8]; 7] = [
+            &[0x82, 0x92, 0x00, 0x00, 0x82, 0xAB],
+            b"building_id",
+            &[0x70, 0xA4],
+            b"size",
+            &[0x01, 0x92, 0x01, 0x00, 0x81, 0xAB],
+            b"building_id",
+            &[0x00],
+        ];
+        let buildings = parts.concat();
+
+        let count = count_buildings(&mut Reader::new(&buildings)).expect("buildings are valid");
+
+        assert_eq!(count, 1);
+    }
+}
@@ -0,0 +1,144 @@
+/*
+ * Copyright (c) SimChopper; Jovan Gerodetti and contributors.
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::collections::{BTreeSet, VecDeque};
+
+use godot::builtin::GString;
+use godot::classes::Resource;
+use godot::obj::Base;
+use godot::prelude::godot_api;
+use godot::register::GodotClass;
+
+use crate::world::city_data::TileCoords;
+use crate::world::missions::{Mission, MissionEvent, MissionState};
+
+/// Changes of the mission chain that have to be presented to the player.
+#[derive(Debug)]
+pub(crate) enum MissionUpdate {
+    Started(Mission),
+    Completed(Mission),
+    Failed(Mission),
+}
+
+/// Shared state of the mission chain. Buildings report their events to the board, while the
+/// missions node advances the chain.
+#[derive(GodotClass)]
+#[class(base = Resource, init)]
+pub struct MissionBoard {
+    missions: VecDeque<Mission>,
+    events: Vec<MissionEvent>,
+    pending_ignitions: BTreeSet<TileCoords>,
+    extinguished_fires: u32,
+    base: Base<Resource>,
+}
+
+impl MissionBoard {
+    /// Replaces the current mission chain.
+    pub(crate) fn set_missions(&mut self, missions: Vec<Mission>) {
+        self.missions = missions.into();
+        self.events.clear();
+        self.pending_ignitions.clear();
+        self.extinguished_fires = 0;
+    }
+
+    /// The remaining mission chain, starting with the active mission.
+    pub(crate) fn missions(&self) -> impl Iterator<Item = &Mission> {
+        self.missions.iter()
+    }
+
+    pub(crate) fn active_mission(&self) -> Option<&Mission> {
+        self.missions
+            .front()
+            .filter(|mission| mission.state() == MissionState::Active)
+    }
+
+    pub(crate) fn report(&mut self, event: MissionEvent) {
+        if let MissionEvent::FireExtinguished(_) = event {
+            self.extinguished_fires += 1;
+        }
+
+        if self.active_mission().is_none() {
+            return;
+        }
+
+        self.events.push(event);
+    }
+
+    /// Number of fires that have been extinguished since the last call, inside and outside of
+    /// missions.
+    pub(crate) fn take_extinguished_fires(&mut self) -> u32 {
+        std::mem::take(&mut self.extinguished_fires)
+    }
+
+    /// Checks if the active mission wants the building to catch fire. Every ignition can only be
+    /// taken once.
+    pub(crate) fn take_ignition(&mut self, building: TileCoords) -> bool {
+        self.pending_ignitions.remove(&building)
+    }
+
+    /// Applies all reported events and the current in-game time to the mission chain.
+    pub(crate) fn update(&mut self, now: f64) -> Vec<MissionUpdate> {
+        let mut updates = Vec::new();
+        let events = std::mem::take(&mut self.events);
+
+        let Some(mission) = self.missions.front_mut() else {
+            return updates;
+        };
+
+        if mission.state() == MissionState::Pending {
+            mission.start(now);
+            self.pending_ignitions
+                .extend(mission.ignitions.iter().copied());
+            updates.push(MissionUpdate::Started(mission.clone()));
+        }
+
+        for event in events {
+            mission.handle_event(event);
+        }
+
+        mission.update(now);
+
+        match mission.state() {
+            MissionState::Succeeded => {
+                updates.push(MissionUpdate::Completed(mission.clone()));
+            }
+            MissionState::Failed => {
+                updates.push(MissionUpdate::Failed(mission.clone()));
+            }
+            MissionState::Pending | MissionState::Active => return updates,
+        }
+
+        self.missions.pop_front();
+        self.pending_ignitions.clear();
+        updates
+    }
+}
+
+#[godot_api]
+impl MissionBoard {
+    /// Title of the active mission, empty if there is none.
+    #[func]
+    fn active_title(&self) -> GString {
+        self.active_mission()
+            .map(|mission| GString::from(&mission.title()))
+            .unwrap_or_default()
+    }
+
+    /// Description of the active mission, empty if there is none.
+    #[func]
+    fn active_description(&self) -> GString {
+        self.active_mission()
+            .map(|mission| GString::from(&mission.description()))
+            .unwrap_or_default()
+    }
+
+    /// Number of missions left in the chain, including the active one.
+    #[func]
+    fn remaining_missions(&self) -> u32 {
+        self.missions.len().try_into().unwrap_or(u32::MAX)
+    }
+}
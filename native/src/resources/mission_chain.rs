@@ -0,0 +1,103 @@
+/*
+ * Copyright (c) SimChopper; Jovan Gerodetti and contributors.
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use godot::builtin::{Array, GString, Vector2i};
+use godot::classes::Resource;
+use godot::obj::{Base, Gd};
+use godot::register::{Export, GodotClass, GodotConvert, Var};
+
+use crate::world::missions::{MissionDefinition, ObjectiveKind};
+
+#[derive(GodotConvert, Var, Export, Debug, Clone, Copy, Default)]
+#[godot(via = i64)]
+pub enum MissionObjective {
+    #[default]
+    ExtinguishFires,
+    ReachBuilding,
+    ClearRiot,
+}
+
+impl From<MissionObjective> for ObjectiveKind {
+    fn from(value: MissionObjective) -> Self {
+        match value {
+            MissionObjective::ExtinguishFires => Self::ExtinguishFires,
+            MissionObjective::ReachBuilding => Self::ReachBuilding,
+            MissionObjective::ClearRiot => Self::ClearRiot,
+        }
+    }
+}
+
+/// A hand written mission of a [`MissionChain`].
+#[derive(GodotClass)]
+#[class(base = Resource, init)]
+pub struct ChainedMission {
+    #[export]
+    objective: MissionObjective,
+
+    /// Tile of the target building, or any tile inside the target sector of a fire mission.
+    #[export]
+    target: Vector2i,
+
+    /// Number of fires of a fire mission.
+    #[export]
+    #[init(val = 1)]
+    fire_count: u32,
+
+    /// Time limit in in-game minutes.
+    #[export]
+    #[init(val = 60.0)]
+    time_limit: f64,
+
+    #[export]
+    #[init(val = 500)]
+    reward: u32,
+
+    base: Base<Resource>,
+}
+
+impl ChainedMission {
+    /// The mission definition, if the target is inside the city grid.
+    pub(crate) fn definition(&self) -> Option<MissionDefinition> {
+        let target = (
+            u32::try_from(self.target.x).ok()?,
+            u32::try_from(self.target.y).ok()?,
+        );
+
+        Some(MissionDefinition {
+            objective: self.objective.into(),
+            target,
+            fire_count: self.fire_count,
+            time_limit: self.time_limit,
+            reward: self.reward,
+        })
+    }
+}
+
+/// A fixed chain of missions for one map. It replaces the randomly planned missions of the map.
+#[derive(GodotClass)]
+#[class(base = Resource, init)]
+pub struct MissionChain {
+    /// The map the missions have been written for.
+    #[export(file = "*.mpz")]
+    map: GString,
+
+    /// The missions in the order they are played.
+    #[export]
+    missions: Array<Gd<ChainedMission>>,
+
+    base: Base<Resource>,
+}
+
+impl MissionChain {
+    pub(crate) fn map(&self) -> &GString {
+        &self.map
+    }
+
+    pub(crate) fn missions(&self) -> impl Iterator<Item = Gd<ChainedMission>> + '_ {
+        self.missions.iter_shared()
+    }
+}
@@ -0,0 +1,163 @@
+/*
+ * Copyright (c) SimChopper; Jovan Gerodetti and contributors.
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use godot::builtin::{Array, GString};
+use godot::classes::{ConfigFile, Resource};
+use godot::global::Error;
+use godot::meta::ToGodot;
+use godot::obj::{Base, Gd, NewGd, WithUserSignals};
+use godot::prelude::godot_api;
+use godot::register::GodotClass;
+
+use crate::resources::items::helicopter_upgrade::HelicopterUpgrade;
+use crate::util::logger;
+
+/// The money of the player. Every change of the balance is persisted immediately.
+#[derive(GodotClass)]
+#[class(base = Resource, init)]
+pub struct Wallet {
+    /// Money that is earned for every extinguished fire.
+    #[export]
+    #[init(val = 100)]
+    fire_reward: u32,
+
+    /// Config file in which the balance is persisted.
+    #[export]
+    #[init(val = GString::from("user://wallet.cfg"))]
+    save_path: GString,
+
+    balance: u32,
+
+    base: Base<Resource>,
+}
+
+impl Wallet {
+    const SECTION: &str = "wallet";
+    const BALANCE_KEY: &str = "balance";
+
+    pub(crate) fn fire_reward(&self) -> u32 {
+        self.fire_reward
+    }
+
+    pub(crate) fn set_balance(&mut self, balance: u32) {
+        self.balance = balance;
+        self.persist();
+        self.signals().balance_changed().emit(balance);
+    }
+
+    fn persist(&self) {
+        let mut config = ConfigFile::new_gd();
+
+        config.set_value(Self::SECTION, Self::BALANCE_KEY, &self.balance.to_variant());
+
+        let result = config.save(&self.save_path);
+
+        if result != Error::OK {
+            logger::error!(
+                "Failed to persist wallet to {}: {:?}",
+                self.save_path,
+                result
+            );
+        }
+    }
+}
+
+#[godot_api]
+impl Wallet {
+    /// Is emitted whenever money is earned or spent.
+    #[signal]
+    fn balance_changed(balance: u32);
+
+    /// Is emitted after an upgrade has been bought.
+    #[signal]
+    fn upgrade_purchased(upgrade: Gd<HelicopterUpgrade>);
+
+    /// Is emitted when the player can not afford an upgrade.
+    #[signal]
+    fn purchase_declined(upgrade: Gd<HelicopterUpgrade>);
+
+    #[func]
+    pub fn balance(&self) -> u32 {
+        self.balance
+    }
+
+    #[func]
+    pub fn earn(&mut self, amount: u32) {
+        self.set_balance(self.balance.saturating_add(amount));
+    }
+
+    #[func]
+    #[expect(clippy::needless_pass_by_value)]
+    pub fn can_afford(&self, upgrade: Gd<HelicopterUpgrade>) -> bool {
+        upgrade.bind().price() <= self.balance
+    }
+
+    /// Buys an upgrade and moves it from the available to the owned upgrades.
+    #[func]
+    #[expect(clippy::needless_pass_by_value)]
+    pub fn purchase(
+        &mut self,
+        upgrade: Gd<HelicopterUpgrade>,
+        mut available: Array<Gd<HelicopterUpgrade>>,
+        mut owned: Array<Gd<HelicopterUpgrade>>,
+    ) -> bool {
+        if !available.contains(&upgrade) {
+            logger::warn!("Upgrade is not available for purchase!");
+            return false;
+        }
+
+        if owned.contains(&upgrade) {
+            logger::warn!("Upgrade is already owned!");
+            return false;
+        }
+
+        let price = upgrade.bind().price();
+
+        if price > self.balance {
+            self.signals().purchase_declined().emit(&upgrade);
+            return false;
+        }
+
+        available.erase(&upgrade);
+        owned.push(&upgrade);
+
+        self.set_balance(self.balance - price);
+        self.signals().upgrade_purchased().emit(&upgrade);
+
+        true
+    }
+
+    /// Restores the persisted balance.
+    #[func]
+    pub fn restore(&mut self) {
+        let mut config = ConfigFile::new_gd();
+
+        let result = config.load(&self.save_path);
+
+        if result == Error::ERR_FILE_NOT_FOUND {
+            return;
+        }
+
+        if result != Error::OK {
+            logger::error!(
+                "Failed to restore wallet from {}: {:?}",
+                self.save_path,
+                result
+            );
+            return;
+        }
+
+        let balance = config
+            .get_value(Self::SECTION, Self::BALANCE_KEY)
+            .try_to::<u32>()
+            .inspect_err(|err| logger::error!("Persisted balance is invalid: {}", err))
+            .unwrap_or_default();
+
+        self.balance = balance;
+        self.signals().balance_changed().emit(balance);
+    }
+}
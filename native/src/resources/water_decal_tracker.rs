@@ -5,35 +5,268 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/.
  */
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap};
 
-use godot::builtin::Aabb;
+use godot::builtin::{Aabb, Color, Vector2, Vector3};
 use godot::classes::{Decal, IResource, Resource};
 use godot::obj::{Base, Gd};
 use godot::prelude::godot_api;
 use godot::register::GodotClass;
 
+use crate::util::spatial_index::SpatialIndex;
+use crate::world::drying::{self, Surface};
+
+/// Items that have a fixed position, sorted into a [`SpatialIndex`].
+///
+/// Items are identified by the order in which they have been inserted, so the oldest item can
+/// always be found.
+#[derive(Debug)]
+struct PointIndex<T> {
+    grid: SpatialIndex<Vector3, u64>,
+    /// All items by their insertion order.
+    items: BTreeMap<u64, (Vector3, T)>,
+    next_id: u64,
+}
+
+impl<T> PointIndex<T> {
+    fn new(cell_size: f32) -> Self {
+        Self {
+            grid: SpatialIndex::new(cell_size),
+            items: BTreeMap::new(),
+            next_id: 0,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    fn insert(&mut self, position: Vector3, item: T) -> u64 {
+        let id = self.next_id;
+
+        self.next_id += 1;
+        self.grid.insert(position, id);
+        self.items.insert(id, (position, item));
+
+        id
+    }
+
+    fn remove(&mut self, id: u64) -> Option<T> {
+        let (position, item) = self.items.remove(&id)?;
+
+        self.grid.remove(position, &id);
+
+        Some(item)
+    }
+
+    /// Moves the item to the end of the insertion order, as if it had just been inserted. Returns
+    /// the new id of the item.
+    fn refresh(&mut self, id: u64) -> Option<u64> {
+        let (position, item) = self.items.remove(&id)?;
+
+        self.grid.remove(position, &id);
+
+        Some(self.insert(position, item))
+    }
+
+    /// Removes the item that has been inserted first.
+    fn pop_oldest(&mut self) -> Option<T> {
+        let id = *self.items.keys().next()?;
+
+        self.remove(id)
+    }
+
+    /// Ids and positions of all items within the radius around the point.
+    fn entries(&self, point: Vector3, radius: f32) -> impl Iterator<Item = (u64, Vector3)> + '_ {
+        self.grid
+            .within_radius(point, radius)
+            .filter_map(|id| Some((*id, self.items.get(id)?.0)))
+    }
+
+    /// Positions of all items inside the area.
+    fn query(&self, area: Aabb) -> impl Iterator<Item = Vector3> + '_ {
+        self.entries(area.center(), area.size.length() / 2.0)
+            .map(|(_, position)| position)
+            .filter(move |position| area.contains_point(*position))
+    }
+
+    fn get_mut(&mut self, id: u64) -> Option<&mut T> {
+        self.items.get_mut(&id).map(|(_, item)| item)
+    }
+
+    fn iter_mut(&mut self) -> impl Iterator<Item = (u64, Vector3, &mut T)> {
+        self.items
+            .iter_mut()
+            .map(|(id, (position, item))| (*id, *position, item))
+    }
+
+    fn values(&self) -> impl Iterator<Item = &T> {
+        self.items.values().map(|(_, item)| item)
+    }
+}
+
+/// A wet patch of a surface that is shown by a decal.
+#[derive(Debug)]
+struct WetPatch {
+    decal: Gd<Decal>,
+    surface: Surface,
+    wetness: f32,
+    /// Size of the decal when the patch was fresh.
+    size: Vector3,
+    color: Color,
+}
+
+impl WetPatch {
+    fn new(decal: &Gd<Decal>, surface: Surface) -> Self {
+        Self {
+            decal: decal.clone(),
+            surface,
+            wetness: 1.0,
+            size: decal.get_size(),
+            color: decal.get_modulate(),
+        }
+    }
+
+    /// Shows the wetness of the patch through the size and opacity of the decal.
+    fn update_decal(&mut self) {
+        if !self.decal.is_instance_valid() {
+            return;
+        }
+
+        let scale = drying::patch_scale(self.wetness);
+        let color = Color {
+            a: self.color.a * drying::opacity(self.wetness),
+            ..self.color
+        };
+
+        self.decal.set_modulate(color);
+        self.decal.set_size(Vector3::new(
+            self.size.x * scale,
+            self.size.y,
+            self.size.z * scale,
+        ));
+    }
+}
+
+/// Keeps track of all water decals in the world.
+///
+/// Decals do not move once they have been placed, so their position is only read once, when they
+/// are inserted. Every decal is a wet patch that dries depending on the weather and nearby fires.
+/// When there are more decals than the budget allows, the oldest decals are freed.
 #[derive(GodotClass)]
 #[class(base = Resource)]
 pub struct WaterDecalTracker {
-    decals: HashSet<Gd<Decal>>,
+    /// Maximum number of decals that can exist at the same time.
+    #[export]
+    budget: u32,
+
+    index: PointIndex<WetPatch>,
+    ids: HashMap<Gd<Decal>, u64>,
     base: Base<Resource>,
 }
 
 impl WaterDecalTracker {
-    pub(crate) fn insert(&mut self, decal: &Gd<Decal>) {
-        self.decals.insert(decal.clone());
+    /// Edge length of the grid cells, roughly the size of a large decal.
+    const CELL_SIZE: f32 = 8.0;
+
+    pub(crate) fn insert(&mut self, decal: &Gd<Decal>, surface: Surface) {
+        if let Some(id) = self.ids.remove(decal) {
+            self.index.remove(id);
+        }
+
+        let id = self
+            .index
+            .insert(decal.get_global_position(), WetPatch::new(decal, surface));
+
+        self.ids.insert(decal.clone(), id);
+
+        let budget = usize::try_from(self.budget).unwrap_or(usize::MAX);
+
+        while self.index.len() > budget {
+            let Some(oldest) = self.index.pop_oldest() else {
+                break;
+            };
+
+            self.ids.remove(&oldest.decal);
+            Self::free_decal(oldest.decal);
+        }
+    }
+
+    /// Soaks the wet patch closest to the point again, if there is one within the extent. Returns
+    /// `false` if a new patch has to be created.
+    ///
+    /// The patch counts as the newest one afterwards, so patches that are still being sprayed are
+    /// not freed to stay within the budget.
+    pub(crate) fn soak(&mut self, point: Vector3, extent: f32, delta: f32) -> bool {
+        let closest = self
+            .index
+            .entries(point, extent)
+            .map(|(id, position)| (id, position.distance_to(point)))
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+
+        let Some(id) = closest.and_then(|(id, _)| self.index.refresh(id)) else {
+            return false;
+        };
+
+        let Some(patch) = self.index.get_mut(id) else {
+            return false;
+        };
+
+        self.ids.insert(patch.decal.clone(), id);
+        patch.wetness = drying::soak(patch.wetness, delta);
+        patch.update_decal();
+        true
+    }
+
+    /// Dries all patches. Fires are given by their position and strength, only their distance on
+    /// the ground plane is relevant.
+    pub(crate) fn dry(&mut self, delta: f32, sun_brightness: f32, fires: &[(Vector3, f32)]) {
+        let mut dried = Vec::new();
+
+        for (id, position, patch) in self.index.iter_mut() {
+            let heat: f32 = fires
+                .iter()
+                .map(|(fire, strength)| {
+                    let distance = Vector2::new(fire.x - position.x, fire.z - position.z);
+
+                    drying::heat(distance.length(), *strength)
+                })
+                .sum();
+
+            patch.wetness -= drying::drying_rate(patch.surface, sun_brightness, heat) * delta;
+
+            if patch.wetness <= 0.0 {
+                dried.push(id);
+            } else {
+                patch.update_decal();
+            }
+        }
+
+        for patch in dried.into_iter().filter_map(|id| self.index.remove(id)) {
+            self.ids.remove(&patch.decal);
+            Self::free_decal(patch.decal);
+        }
     }
 
-    pub(crate) fn get_decals_at_point(&self, point: Aabb) -> Box<[&Gd<Decal>]> {
-        self.decals
-            .iter()
-            .filter(|decal| point.contains_point(decal.get_global_position()))
-            .collect()
+    fn free_decal(mut decal: Gd<Decal>) {
+        if decal.is_instance_valid() {
+            decal.queue_free();
+        }
+    }
+
+    /// Positions of all decals inside the area.
+    pub(crate) fn get_decals_at_point(&self, area: Aabb) -> impl Iterator<Item = Vector3> + '_ {
+        self.index.query(area)
+    }
+
+    pub(crate) fn decals(&self) -> impl Iterator<Item = &Gd<Decal>> {
+        self.index.values().map(|patch| &patch.decal)
     }
 
     pub(crate) fn free(&mut self, decal: &Gd<Decal>) {
-        self.decals.remove(decal);
+        if let Some(id) = self.ids.remove(decal) {
+            self.index.remove(id);
+        }
     }
 }
 
@@ -41,8 +274,86 @@ impl WaterDecalTracker {
 impl IResource for WaterDecalTracker {
     fn init(base: Base<Self::Base>) -> Self {
         Self {
-            decals: HashSet::default(),
+            budget: 2000,
+            index: PointIndex::new(Self::CELL_SIZE),
+            ids: HashMap::default(),
             base,
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use godot::builtin::{Aabb, Vector3};
+
+    use super::PointIndex;
+
+    fn area(center: Vector3, size: f32) -> Aabb {
+        Aabb {
+            position: center - Vector3::splat(size / 2.0),
+            size: Vector3::splat(size),
+        }
+    }
+
+    #[test]
+    fn query_finds_points_across_cells() {
+        let mut index = PointIndex::new(4.0);
+
+        index.insert(Vector3::new(3.5, 0.0, 3.5), 1);
+        index.insert(Vector3::new(4.5, 0.0, 4.5), 2);
+        index.insert(Vector3::new(-0.5, 0.0, -0.5), 3);
+        index.insert(Vector3::new(20.0, 0.0, 20.0), 4);
+
+        let found: Vec<_> = index
+            .query(area(Vector3::new(4.0, 0.0, 4.0), 2.0))
+            .collect();
+
+        assert_eq!(
+            found,
+            vec![Vector3::new(3.5, 0.0, 3.5), Vector3::new(4.5, 0.0, 4.5)]
+        );
+        assert_eq!(index.query(area(Vector3::ZERO, 2.0)).count(), 1);
+    }
+
+    #[test]
+    fn removed_points_are_not_found() {
+        let mut index = PointIndex::new(4.0);
+
+        let id = index.insert(Vector3::ONE, 1);
+        index.insert(Vector3::ONE, 2);
+
+        assert_eq!(index.remove(id), Some(1));
+        assert_eq!(index.remove(id), None);
+        assert_eq!(index.query(area(Vector3::ONE, 1.0)).count(), 1);
+    }
+
+    #[test]
+    fn oldest_points_are_evicted_first() {
+        let mut index = PointIndex::new(4.0);
+
+        for item in 0..4u8 {
+            index.insert(Vector3::new(f32::from(item), 0.0, 0.0), item);
+        }
+
+        index.remove(1);
+
+        assert_eq!(index.pop_oldest(), Some(0));
+        assert_eq!(index.pop_oldest(), Some(2));
+        assert_eq!(index.len(), 1);
+    }
+
+    #[test]
+    fn refreshed_points_are_evicted_last() {
+        let mut index = PointIndex::new(4.0);
+
+        let first = index.insert(Vector3::ZERO, 0);
+        index.insert(Vector3::ONE, 1);
+
+        let refreshed = index.refresh(first).expect("point exists");
+
+        assert_eq!(index.refresh(first), None);
+        assert_eq!(index.query(area(Vector3::ZERO, 1.0)).count(), 1);
+        assert_eq!(index.pop_oldest(), Some(1));
+        assert_eq!(index.remove(refreshed), Some(0));
+    }
+}
@@ -0,0 +1,162 @@
+/*
+ * Copyright (c) SimChopper; Jovan Gerodetti and contributors.
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::collections::BTreeMap;
+
+use godot::builtin::Vector3;
+use godot::classes::Resource;
+use godot::obj::{Base, Gd, OnEditor, WithUserSignals};
+use godot::prelude::godot_api;
+use godot::register::GodotClass;
+
+use crate::resources::WorldConstants;
+use crate::world::city_coords_feature::CityCoordsFeature;
+use crate::world::city_data::TileCoords;
+use crate::world::city_loader::CityHandle;
+
+/// The water tank of the helicopter. The water canon drains the tank, it is refilled by hovering
+/// low over the sea or surface water.
+#[derive(GodotClass)]
+#[class(base = Resource, init)]
+pub struct WaterTank {
+    /// Capacity of the tank in liters.
+    #[export]
+    #[init(val = 1000.0)]
+    capacity: f32,
+
+    /// Liters per second the water canon uses.
+    #[export]
+    #[init(val = 40.0)]
+    flow_rate: f32,
+
+    /// Liters per second that are taken up while refilling.
+    #[export]
+    #[init(val = 200.0)]
+    refill_rate: f32,
+
+    /// Maximum height above the water surface in meters from which the tank can be refilled.
+    #[export]
+    #[init(val = 12.0)]
+    refill_height: f32,
+
+    #[export]
+    world_constants: OnEditor<Gd<WorldConstants>>,
+
+    /// Liters that are missing to a full tank, so the tank starts out full whatever its capacity.
+    missing: f32,
+
+    /// Height of the water surface of all tiles water can be taken from.
+    water_surfaces: BTreeMap<TileCoords, f32>,
+    city_coords_feature: Option<CityCoordsFeature>,
+
+    base: Base<Resource>,
+}
+
+impl WaterTank {
+    fn set_missing(&mut self, missing: f32) {
+        let missing = missing.clamp(0.0, self.capacity);
+
+        if (missing - self.missing).abs() < f32::EPSILON {
+            return;
+        }
+
+        self.missing = missing;
+
+        let (level, capacity) = (self.level(), self.capacity);
+
+        self.signals().level_changed().emit(level, capacity);
+    }
+
+    /// Fills the tank up to the level, e.g. when a saved session is restored.
+    pub(crate) fn set_level(&mut self, level: f32) {
+        self.set_missing(self.capacity - level);
+    }
+
+    /// Takes water for `delta` seconds of firing. Returns `false` once the tank is empty.
+    pub(crate) fn drain(&mut self, delta: f32) -> bool {
+        if self.is_empty() {
+            return false;
+        }
+
+        self.set_missing(self.missing + self.flow_rate * delta);
+        true
+    }
+
+    /// Refills the tank for `delta` seconds if the location is low enough above a water source.
+    /// Returns `true` while the tank is being refilled.
+    pub(crate) fn refill(&mut self, location: Vector3, delta: f32) -> bool {
+        if self.missing <= 0.0 || location.x < 0.0 || location.y < 0.0 || location.z < 0.0 {
+            return false;
+        }
+
+        let Some(city_coords_feature) = self.city_coords_feature.as_ref() else {
+            return false;
+        };
+
+        let (x, y, _) = city_coords_feature.tile_coordinates(location);
+
+        let is_low = self
+            .water_surfaces
+            .get(&(x, y))
+            .is_some_and(|surface| location.y - surface <= self.refill_height);
+
+        if !is_low {
+            return false;
+        }
+
+        self.set_missing(self.missing - self.refill_rate * delta);
+        true
+    }
+}
+
+#[godot_api]
+impl WaterTank {
+    /// Is emitted whenever the amount of water in the tank changes.
+    #[signal]
+    fn level_changed(level: f32, capacity: f32);
+
+    /// Liters of water in the tank.
+    #[func]
+    pub fn level(&self) -> f32 {
+        self.capacity - self.missing
+    }
+
+    #[func]
+    pub fn is_empty(&self) -> bool {
+        self.level() <= 0.0
+    }
+
+    /// Locates the water sources of the city and fills the tank.
+    #[func]
+    #[expect(clippy::needless_pass_by_value)]
+    pub fn set_city(&mut self, city: Gd<CityHandle>) {
+        let city = city.bind().city().clone();
+        let city_coords_feature = CityCoordsFeature::new(
+            (*self.world_constants).clone(),
+            city.simulator_settings.sea_level,
+        );
+
+        self.water_surfaces = city
+            .tilelist
+            .values()
+            .filter(|tile| tile.is_water_source())
+            .map(|tile| {
+                let (x, y) = tile.coordinates;
+                let surface = city_coords_feature.get_world_coords(x, y, tile.altitude);
+
+                (tile.coordinates, surface.y)
+            })
+            .collect();
+
+        self.city_coords_feature = Some(city_coords_feature);
+        self.missing = 0.0;
+
+        let (level, capacity) = (self.level(), self.capacity);
+
+        self.signals().level_changed().emit(level, capacity);
+    }
+}
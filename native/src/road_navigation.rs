@@ -5,14 +5,16 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/.
  */
 
+mod intersections;
+pub(crate) mod lanes;
+
 use std::cell::OnceCell;
-use std::cmp::Ordering;
-use std::collections::BTreeMap;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap};
 use std::sync::OnceLock;
 
 use anyhow::{anyhow, Result};
-use godot::builtin::math::ApproxEq;
-use godot::builtin::{Transform3D, VarDictionary, Vector3};
+use godot::builtin::{Transform3D, VarDictionary, Vector2, Vector3};
 use godot::classes::Node3D;
 use godot::global::snappedf;
 use godot::obj::{Gd, OnEditor};
@@ -21,13 +23,26 @@ use num::ToPrimitive;
 use rand::distr::Uniform;
 use rand::Rng;
 
+use crate::util::logger;
+use crate::util::spatial_index::SpatialIndex;
 use crate::world::city_coords_feature::CityCoordsFeature;
 use crate::world::city_data::TryFromDictionary;
+use crate::world::city_loader::CityHandle;
 use crate::{
     resources::WorldConstants,
     world::city_data::{Building, TileCoords},
 };
 
+use intersections::Intersection;
+use lanes::Lane;
+pub(crate) use lanes::Side;
+
+/// Tunnel portals further apart than this number of tiles are not linked.
+const MAX_TUNNEL_LENGTH: u32 = 128;
+
+/// Edge length of the cells of the node index in tiles.
+const INDEX_CELL_TILES: f32 = 4.0;
+
 enum Corners {
     BottomRight,
     BottomLeft,
@@ -90,6 +105,9 @@ struct NavNode {
     building: Building,
     object: Gd<Node3D>,
     neighbors: OnceLock<Box<[(u32, u32)]>>,
+    sides: OnceLock<Box<[Side]>>,
+    intersection: OnceCell<Option<Intersection>>,
+    tunnel_exit: OnceCell<Option<TileCoords>>,
 }
 
 #[derive(Clone)]
@@ -167,6 +185,16 @@ impl<'n> NavNodeRef<'n> {
         self.node.building.tile_coords
     }
 
+    /// Whether the location is right in front of the node, where vehicles have to stop if they
+    /// are not allowed to enter it.
+    pub fn is_at_stop_line(&self, location: Vector3) -> bool {
+        let center = self.node.object.get_global_position();
+        let distance = Vector3::new(location.x - center.x, 0.0, location.z - center.z).length();
+        let tile_size = f32::from(self.world_constants.bind().tile_size());
+
+        distance > tile_size * 0.5 && distance < tile_size * 0.75
+    }
+
     pub fn has_arrived(&self, location: Vector3, direction: Vector3) -> bool {
         let target = self.get_global_transform(direction).origin;
 
@@ -182,6 +210,8 @@ impl<'n> NavNodeRef<'n> {
 
 pub(crate) struct RoadNavigation {
     network: BTreeMap<TileCoords, NavNode>,
+    /// Locations of all nodes, to find the nodes around a point.
+    index: SpatialIndex<Vector2, TileCoords>,
     world_constants: Gd<WorldConstants>,
     rand_distribution: Uniform<usize>,
     city_coords_feature: CityCoordsFeature,
@@ -189,8 +219,11 @@ pub(crate) struct RoadNavigation {
 
 impl RoadNavigation {
     pub fn new(world_constants: Gd<WorldConstants>) -> Self {
+        let tile_size = f32::from(world_constants.bind().tile_size());
+
         Self {
             network: BTreeMap::default(),
+            index: SpatialIndex::new(tile_size * INDEX_CELL_TILES),
             world_constants: world_constants.clone(),
             rand_distribution: Uniform::new(0, 1).expect("we have constant bounds"),
             city_coords_feature: CityCoordsFeature::new(world_constants, 0),
@@ -199,13 +232,21 @@ impl RoadNavigation {
 
     pub fn insert_node(&mut self, node: Building, object: Gd<Node3D>) {
         let tile_coords = node.tile_coords;
+        let position = self.tile_position(tile_coords);
         let node = NavNode {
             building: node,
             object,
             neighbors: OnceLock::new(),
+            sides: OnceLock::new(),
+            intersection: OnceCell::new(),
+            tunnel_exit: OnceCell::new(),
         };
 
-        self.network.insert(tile_coords, node);
+        if self.network.insert(tile_coords, node).is_some() {
+            self.index.remove(position, &tile_coords);
+        }
+
+        self.index.insert(position, tile_coords);
         self.rand_distribution =
             Uniform::new(0, self.network.len()).expect("lower bound is always zero");
     }
@@ -223,197 +264,210 @@ impl RoadNavigation {
             .expect("we are absolutely sure the node")
     }
 
+    /// All nodes that are connected to the given node. Two nodes are only connected if both road
+    /// pieces connect their shared side.
     pub fn get_neighbors(&self, tile_coords: TileCoords) -> Option<&[TileCoords]> {
         let cache = &self.network.get(&tile_coords)?.neighbors;
 
         let neighbors = cache.get_or_init(|| {
-            let (x, y) = tile_coords;
-
-            let neighbors = [
-                y.checked_sub(1).map(|y| (x, y)),
-                x.checked_sub(1).map(|x| (x, y)),
-                Some((x + 1, y)),
-                Some((x, y + 1)),
-            ]
-            .into_iter()
-            .flatten()
-            .filter_map(|tile_coords| self.network.get(&tile_coords))
-            .map(|node| node.building.tile_coords)
-            .collect();
-
-            neighbors
+            self.lane_successors(Lane::new(tile_coords, None))
+                .into_iter()
+                .map(|lane| lane.tile)
+                .collect()
         });
 
         Some(neighbors)
     }
 
-    pub fn get_nearest_node(&self, global_translation: Vector3) -> Option<NavNodeRef<'_>> {
-        let own_coords = {
-            let (x, y, _altitude) = self
-                .city_coords_feature
-                .tile_coordinates(global_translation);
+    /// The sides of the tile that are connected by the road piece of the node.
+    fn sides(&self, tile_coords: TileCoords) -> Option<&[Side]> {
+        let node = self.network.get(&tile_coords)?;
 
-            (x, y)
-        };
+        let sides = node.sides.get_or_init(|| {
+            let building_id = node.building.id;
 
-        // fas track, pick node at global transform
-        if let Some(node) = self.try_node(own_coords) {
-            return Some(node);
-        }
+            if let Some(sides) = lanes::highway_corner_sides(tile_coords, |tile| {
+                self.network.get(&tile).map(|node| node.building.id)
+            }) {
+                return sides.into();
+            }
 
-        let (mut low, mut low_node) = self.network.first_key_value()?;
-        let (mut high, mut high_node) = self.network.last_key_value()?;
+            if let Some(sides) = lanes::road_sides(building_id) {
+                return sides.into();
+            }
 
-        loop {
-            let distance_low = {
-                let v = global_translation - low_node.object.get_global_position();
+            if !lanes::is_bridge(building_id) {
+                logger::warn!("building {:#x} is not a road piece", building_id);
+                return Box::default();
+            }
 
-                Vector3::new(v.x, 0.0, v.z)
-            };
+            // bridges span the axis along which they have neighbours.
+            let vertical = [Side::Top, Side::Bottom]
+                .into_iter()
+                .filter_map(|side| side.neighbor(tile_coords))
+                .any(|neighbor| self.network.contains_key(&neighbor));
 
-            let distance_high = {
-                let v = high_node.object.get_global_position() - global_translation;
+            if vertical {
+                [Side::Top, Side::Bottom].into()
+            } else {
+                [Side::Left, Side::Right].into()
+            }
+        });
 
-                Vector3::new(v.x, 0.0, v.z)
-            };
+        Some(sides)
+    }
 
-            if distance_low.is_zero_approx() {
-                break self.try_node(*low);
-            }
+    /// All lanes a vehicle can move to from the given lane. Vehicles that cross a highway
+    /// overpass have to go straight on.
+    fn lane_successors(&self, lane: Lane) -> Vec<Lane> {
+        let successors = lanes::linked_successors(
+            lane,
+            |tile| self.sides(tile),
+            |tile, side| self.linked_tile(tile, side),
+        );
 
-            if distance_high.is_zero_approx() {
-                break self.try_node(*high);
-            }
+        let overpass = self
+            .network
+            .get(&lane.tile)
+            .is_some_and(|node| lanes::is_overpass(node.building.id));
 
-            let (new_low, new_low_node) = {
-                let ordering = low.cmp(&own_coords);
+        let (true, Some(entered_from)) = (overpass, lane.entered_from) else {
+            return successors;
+        };
 
-                let range = match ordering {
-                    Ordering::Less | Ordering::Equal => (*low)..=own_coords,
-                    Ordering::Greater => own_coords..=*low,
-                };
+        successors
+            .into_iter()
+            .filter(|next| Side::towards(lane.tile, next.tile) == Some(entered_from.opposite()))
+            .collect()
+    }
 
-                let mut node_range = self.network.range(range);
+    /// The tile that is linked to the given side of a node. The portal of a tunnel is linked to
+    /// the portal at the other end of the tunnel.
+    fn linked_tile(&self, tile_coords: TileCoords, side: Side) -> Option<TileCoords> {
+        let node = self.network.get(&tile_coords)?;
 
-                let maybe_node = match ordering {
-                    Ordering::Less => node_range.next_back(),
-                    Ordering::Equal | Ordering::Greater => node_range.next(),
-                };
+        if lanes::tunnel_side(node.building.id) != Some(side) {
+            return side.neighbor(tile_coords);
+        }
 
-                let Some((new, node)) = maybe_node else {
-                    break self.try_node(*high);
-                };
+        *node.tunnel_exit.get_or_init(|| {
+            let mut tile = tile_coords;
 
-                (new, node)
-            };
+            for _ in 0..MAX_TUNNEL_LENGTH {
+                tile = side.neighbor(tile)?;
 
-            let (new_high, new_high_node) = {
-                let ordering = own_coords.cmp(high);
+                let is_exit = self.network.get(&tile).is_some_and(|node| {
+                    lanes::tunnel_side(node.building.id) == Some(side.opposite())
+                });
 
-                let range = match ordering {
-                    Ordering::Less | Ordering::Equal => own_coords..=*high,
-                    Ordering::Greater => *high..=own_coords,
-                };
+                if is_exit {
+                    return Some(tile);
+                }
+            }
 
-                let mut node_range = self.network.range(range);
+            logger::warn!("tunnel at {:?} has no exit", tile_coords);
+            None
+        })
+    }
 
-                let maybe_node = match ordering {
-                    Ordering::Less => node_range.next(),
-                    Ordering::Equal | Ordering::Greater => node_range.next_back(),
-                };
+    /// The controller of a node that connects three or more roads.
+    fn intersection(&self, tile_coords: TileCoords) -> Option<&Intersection> {
+        let node = self.network.get(&tile_coords)?;
 
-                let Some((new, node)) = maybe_node else {
-                    break self.try_node(*high);
-                };
+        node.intersection
+            .get_or_init(|| {
+                if lanes::is_highway(node.building.id) {
+                    return None;
+                }
 
-                (new, node)
-            };
+                let sides: Vec<_> = self
+                    .get_neighbors(tile_coords)?
+                    .iter()
+                    .filter_map(|neighbor| Side::towards(tile_coords, *neighbor))
+                    .collect();
 
-            if new_low > new_high {
-                high = new_low;
-                high_node = new_low_node;
-                low = new_high;
-                low_node = new_low_node;
-                continue;
-            }
+                Intersection::new(tile_coords, &sides)
+            })
+            .as_ref()
+    }
 
-            if new_low == new_high {
-                break self.try_node(*new_low);
-            }
+    pub fn is_intersection(&self, tile_coords: TileCoords) -> bool {
+        self.intersection(tile_coords).is_some()
+    }
 
-            if low == new_low && high == new_high {
-                match distance_low
-                    .length_squared()
-                    .total_cmp(&distance_high.length_squared())
-                {
-                    Ordering::Less | Ordering::Equal => {
-                        break self.try_node(*low);
-                    }
-
-                    Ordering::Greater => {
-                        break self.try_node(*high);
-                    }
-                }
-            }
+    /// Whether a vehicle that is about to enter the node through the given side has the right of
+    /// way. `now` is the current time in ms.
+    pub fn may_enter(&self, tile_coords: TileCoords, from: Side, vehicle: u64, now: u64) -> bool {
+        self.intersection(tile_coords)
+            .is_none_or(|intersection| intersection.may_enter(vehicle, from, now))
+    }
 
-            low = new_low;
-            low_node = new_low_node;
-            high = new_high;
-            high_node = new_high_node;
+    /// Notifies the node that the vehicle has left it.
+    pub fn leave(&self, tile_coords: TileCoords, vehicle: u64) {
+        if let Some(intersection) = self.intersection(tile_coords) {
+            intersection.leave(vehicle);
         }
     }
 
-    pub fn get_next_node<'n>(
-        &'n self,
-        current: &NavNodeRef<'n>,
-        target: &NavNodeRef<'n>,
-        actor_orientation: Vector3,
-    ) -> NavNodeRef<'n> {
-        let current_location = current.get_global_transform(Vector3::ZERO).origin;
-        let target_location = target.get_global_transform(Vector3::ZERO).origin;
-
-        // current and target might be the same if we arrived at the target node.
-        if current_location
-            .distance_squared_to(target_location)
-            .approx_eq(&0.0)
-        {
-            return target.clone();
+    /// The node that is closest to the location.
+    pub fn get_nearest_node(&self, global_translation: Vector3) -> Option<NavNodeRef<'_>> {
+        let tile_coords = self.index.nearest(ground_position(global_translation))?;
+
+        self.try_node(*tile_coords)
+    }
+
+    /// All nodes within the given number of tiles around a tile.
+    pub fn get_nodes_around(
+        &self,
+        tile_coords: TileCoords,
+        radius: u8,
+    ) -> impl Iterator<Item = NavNodeRef<'_>> + '_ {
+        let tile_size = f32::from(self.world_constants.bind().tile_size());
+
+        self.index
+            .within_radius(
+                self.tile_position(tile_coords),
+                f32::from(radius) * tile_size,
+            )
+            .filter_map(|tile_coords| self.try_node(*tile_coords))
+    }
+
+    /// Center of a tile on the ground plane.
+    fn tile_position(&self, (x, y): TileCoords) -> Vector2 {
+        ground_position(self.city_coords_feature.get_building_coords(x, y, 0, 1))
+    }
+
+    /// Plans the shortest legal route from the start to the target node. The route begins with
+    /// the start node and ends with the target node.
+    ///
+    /// Vehicles are not allowed to turn around on the road unless they reached a dead end. A
+    /// vehicle that is heading to one side of the start tile, therefore can not leave it through
+    /// the opposite side. If no route can be found for the heading, the vehicle is allowed to turn
+    /// on the spot.
+    pub fn plan_route(
+        &self,
+        start: TileCoords,
+        heading: Option<Side>,
+        target: TileCoords,
+    ) -> Option<Vec<TileCoords>> {
+        if !self.network.contains_key(&target) {
+            return None;
         }
 
-        let dir_target =
-            current_location.direction_to(target.get_global_transform(Vector3::ZERO).origin);
-
-        let neighbors = self
-            .get_neighbors(current.tile_coords())
-            .expect("curent node must exist");
-
-        let (_, next) =
-            neighbors
-                .iter()
-                .fold((360.0, current.to_owned()), |(closest, next), coords| {
-                    let Some(neighbor) = self.try_node(*coords) else {
-                        return (closest, next);
-                    };
-
-                    let neighbor_location = neighbor.get_global_transform(Vector3::ZERO).origin;
-                    let dir = current_location.direction_to(neighbor_location);
-                    let angle_actor_orientation = dir.angle_to(actor_orientation);
-                    let angle = dir.angle_to(dir_target);
-
-                    // multiplying the angle between the target and the neighbor with the
-                    // angle between the current actor orientation and the required actor
-                    // orientation, adds bias towards a neighbor that is in the direction
-                    // of the actors current orientation.
-                    let weight = angle * (angle_actor_orientation / 2.0);
-
-                    if closest < weight {
-                        return (closest, next);
-                    }
-
-                    (weight, neighbor)
-                });
+        let route_from = |entered_from| {
+            find_route(
+                Lane::new(start, entered_from),
+                |lane| lane.tile == target,
+                |lane| lane.tile.0.abs_diff(target.0) + lane.tile.1.abs_diff(target.1),
+                |lane| self.lane_successors(lane),
+            )
+        };
 
-        next
+        let route = heading
+            .and_then(|heading| route_from(Some(heading.opposite())))
+            .or_else(|| route_from(None))?;
+
+        Some(route.into_iter().map(|lane| lane.tile).collect())
     }
 
     pub fn get_random_node(&self) -> NavNodeRef<'_> {
@@ -432,6 +486,57 @@ impl RoadNavigation {
     }
 }
 
+/// Projects a location onto the ground plane.
+fn ground_position(location: Vector3) -> Vector2 {
+    Vector2::new(location.x, location.z)
+}
+
+/// A* search for the shortest route between two nodes of a graph on the tile grid. All edges
+/// have the same cost, so the remaining distance must never exceed the number of steps to the
+/// target. Edges that link distant tiles, like tunnels, can make the route longer than necessary.
+pub(crate) fn find_route<N: Copy + Ord, S: IntoIterator<Item = N>>(
+    start: N,
+    is_target: impl Fn(N) -> bool,
+    distance: impl Fn(N) -> u32,
+    successors: impl Fn(N) -> S,
+) -> Option<Vec<N>> {
+    let mut open = BinaryHeap::from([Reverse((distance(start), start))]);
+    let mut closed = BTreeSet::new();
+    let mut costs = BTreeMap::from([(start, 0u32)]);
+    let mut came_from = BTreeMap::new();
+
+    while let Some(Reverse((_, node))) = open.pop() {
+        if is_target(node) {
+            let mut route = vec![node];
+
+            while let Some(previous) = came_from.get(route.last()?) {
+                route.push(*previous);
+            }
+
+            route.reverse();
+            return Some(route);
+        }
+
+        if !closed.insert(node) {
+            continue;
+        }
+
+        let cost = costs[&node] + 1;
+
+        for successor in successors(node) {
+            if costs.get(&successor).is_some_and(|known| *known <= cost) {
+                continue;
+            }
+
+            costs.insert(successor, cost);
+            came_from.insert(successor, node);
+            open.push(Reverse((cost + distance(successor), successor)));
+        }
+    }
+
+    None
+}
+
 /// Configuration resource to setup road navigation for vehicles.
 #[derive(GodotClass)]
 #[class(base = Resource, init)]
@@ -468,4 +573,120 @@ impl RoadNavigationConfig {
 
         self.road_navigation_mut().insert_node(building, scene_node);
     }
+
+    /// Insert the network section of a loaded city at the given tile coordinates into the road
+    /// navigation graph.
+    #[func]
+    #[expect(clippy::needless_pass_by_value)]
+    pub fn insert_city_node(
+        &mut self,
+        city: Gd<CityHandle>,
+        x: u32,
+        y: u32,
+        scene_node: Gd<Node3D>,
+    ) {
+        let Some(building) = city.bind().city().networks.get(&(x, y)).cloned() else {
+            logger::error!("city has no network section at x = {}, y = {}", x, y);
+            return;
+        };
+
+        self.road_navigation_mut().insert_node(building, scene_node);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use super::find_route;
+    use crate::world::city_data::TileCoords;
+
+    /// Builds the adjacency of a road network from a map, every `#` is a road tile.
+    fn network(map: &[&str]) -> BTreeMap<TileCoords, Vec<TileCoords>> {
+        let tiles: Vec<TileCoords> = map
+            .iter()
+            .zip(0..)
+            .flat_map(|(row, y)| {
+                row.chars()
+                    .zip(0..)
+                    .filter(|(tile, _)| *tile == '#')
+                    .map(move |(_, x)| (x, y))
+            })
+            .collect();
+
+        tiles
+            .iter()
+            .map(|(x, y)| {
+                let neighbors = tiles
+                    .iter()
+                    .filter(|(nx, ny)| nx.abs_diff(*x) + ny.abs_diff(*y) == 1)
+                    .copied()
+                    .collect();
+
+                ((*x, *y), neighbors)
+            })
+            .collect()
+    }
+
+    fn route(map: &[&str], start: TileCoords, target: TileCoords) -> Option<Vec<TileCoords>> {
+        let network = network(map);
+
+        find_route(
+            start,
+            |tile| tile == target,
+            |(x, y)| x.abs_diff(target.0) + y.abs_diff(target.1),
+            |tile| network.get(&tile).cloned().unwrap_or_default(),
+        )
+    }
+
+    #[test]
+    fn takes_detours() {
+        // the road north of the start is blocked, the route has to go around the block.
+        let map = [
+            "#####", //
+            "#...#", //
+            "#.#.#", //
+            "#.#..", //
+            "###..",
+        ];
+
+        let route = route(&map, (2, 2), (4, 0)).unwrap();
+
+        assert_eq!(route.first(), Some(&(2, 2)));
+        assert_eq!(route.last(), Some(&(4, 0)));
+        assert_eq!(route.len(), 13);
+        assert!(route
+            .windows(2)
+            .all(|step| step[0].0.abs_diff(step[1].0) + step[0].1.abs_diff(step[1].1) == 1));
+    }
+
+    #[test]
+    fn finds_shortest_route() {
+        let map = [
+            "#####", //
+            "#...#", //
+            "#####", //
+            "....#",
+        ];
+
+        let route = route(&map, (0, 1), (4, 3)).unwrap();
+
+        assert_eq!(route.len(), 7);
+        assert_eq!(route.first(), Some(&(0, 1)));
+        assert_eq!(route.last(), Some(&(4, 3)));
+    }
+
+    #[test]
+    fn start_is_target() {
+        assert_eq!(route(&["#"], (0, 0), (0, 0)), Some(vec![(0, 0)]));
+    }
+
+    #[test]
+    fn unreachable_target() {
+        let map = [
+            "##.##", //
+        ];
+
+        assert_eq!(route(&map, (0, 0), (4, 0)), None);
+    }
 }
@@ -0,0 +1,230 @@
+/*
+ * Copyright (c) SimChopper; Jovan Gerodetti and contributors.
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! Right of way at road intersections.
+//!
+//! Crossings of four roads are controlled by traffic lights that alternate between the two axes
+//! of the crossing. T-junctions are controlled by stop signs, vehicles on the through road have
+//! priority over vehicles that come from the side road and only one vehicle crosses the junction
+//! at a time.
+
+use std::cell::Cell;
+
+use super::lanes::Side;
+use crate::world::city_data::TileCoords;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Control {
+    Signals,
+    /// Stop signs on the given side road.
+    StopSigns(Side),
+}
+
+#[derive(Debug)]
+pub(crate) struct Intersection {
+    control: Control,
+    /// Offset of the signal cycle in ms.
+    cycle_offset: u64,
+    /// The vehicle that is crossing the junction and the time at which it entered.
+    occupant: Cell<Option<(u64, u64)>>,
+    /// Last time a vehicle on the through road asked for the right of way.
+    through_traffic: Cell<Option<u64>>,
+}
+
+impl Intersection {
+    /// Duration of the green phase of an axis in ms.
+    const GREEN_PHASE: u64 = 8_000;
+    /// Duration in ms in which all signals are red, so vehicles can clear the crossing.
+    const CLEARANCE_PHASE: u64 = 2_000;
+    const CYCLE: u64 = 2 * (Self::GREEN_PHASE + Self::CLEARANCE_PHASE);
+    /// Vehicles that never leave the junction, e.g. because they have been despawned, lose their
+    /// right of way after this time in ms.
+    const OCCUPATION_TIMEOUT: u64 = 4_000;
+    /// Through traffic is considered to be waiting if it asked for the right of way within this
+    /// time in ms.
+    const THROUGH_TRAFFIC_TIMEOUT: u64 = 250;
+
+    /// Creates the controller of a junction. Nodes that connect less than three sides are not
+    /// junctions.
+    pub fn new((x, y): TileCoords, sides: &[Side]) -> Option<Self> {
+        let control = match sides {
+            [_, _, _, _] => Control::Signals,
+            [_, _, _] => {
+                let side_road = sides
+                    .iter()
+                    .copied()
+                    .find(|side| !sides.contains(&side.opposite()))?;
+
+                Control::StopSigns(side_road)
+            }
+            _ => return None,
+        };
+
+        Some(Self {
+            control,
+            // signals along a road switch one after another.
+            cycle_offset: (u64::from(x) + u64::from(y)) * Self::CLEARANCE_PHASE,
+            occupant: Cell::new(None),
+            through_traffic: Cell::new(None),
+        })
+    }
+
+    pub fn control(&self) -> Control {
+        self.control
+    }
+
+    /// Whether the signal for vehicles that enter through the given side is green.
+    pub fn is_green(&self, from: Side, now: u64) -> bool {
+        let time = (now + self.cycle_offset) % Self::CYCLE;
+        let vertical = matches!(from, Side::Top | Side::Bottom);
+
+        if vertical {
+            time < Self::GREEN_PHASE
+        } else {
+            let start = Self::GREEN_PHASE + Self::CLEARANCE_PHASE;
+
+            (start..start + Self::GREEN_PHASE).contains(&time)
+        }
+    }
+
+    /// Asks for the right of way of a vehicle that is about to enter the junction through the
+    /// given side. Vehicles have to ask again every frame while they are waiting.
+    pub fn may_enter(&self, vehicle: u64, from: Side, now: u64) -> bool {
+        let side_road = match self.control {
+            Control::Signals => return self.is_green(from, now),
+            Control::StopSigns(side_road) => side_road,
+        };
+
+        if let Some((occupant, since)) = self.occupant.get() {
+            if occupant == vehicle {
+                return true;
+            }
+
+            if now.saturating_sub(since) < Self::OCCUPATION_TIMEOUT {
+                if from != side_road {
+                    self.through_traffic.set(Some(now));
+                }
+
+                return false;
+            }
+        }
+
+        if from == side_road {
+            let through_traffic_waiting = self
+                .through_traffic
+                .get()
+                .is_some_and(|time| now.saturating_sub(time) < Self::THROUGH_TRAFFIC_TIMEOUT);
+
+            if through_traffic_waiting {
+                return false;
+            }
+        }
+
+        self.occupant.set(Some((vehicle, now)));
+        true
+    }
+
+    /// Releases the junction after the vehicle left it.
+    pub fn leave(&self, vehicle: u64) {
+        if self
+            .occupant
+            .get()
+            .is_some_and(|(occupant, _)| occupant == vehicle)
+        {
+            self.occupant.set(None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Control, Intersection};
+    use crate::objects::scene_object_registry::Road;
+    use crate::road_navigation::lanes::road_sides;
+    use crate::road_navigation::Side;
+
+    fn intersection_of(piece: Road) -> Option<Intersection> {
+        Intersection::new((0, 0), road_sides(piece as u8).unwrap())
+    }
+
+    #[test]
+    fn junction_control() {
+        let crossing = intersection_of(Road::LeftTopBottomRight).unwrap();
+
+        assert_eq!(crossing.control(), Control::Signals);
+        assert!(intersection_of(Road::LeftRight).is_none());
+        assert!(intersection_of(Road::TopRight).is_none());
+
+        // the side road of every T-junction piece.
+        for (piece, side_road) in [
+            (Road::RightTopBottom, Side::Top),
+            (Road::LeftBottomRight, Side::Right),
+            (Road::TopLeftBottom, Side::Bottom),
+            (Road::LeftTopRight, Side::Left),
+        ] {
+            assert_eq!(
+                intersection_of(piece).unwrap().control(),
+                Control::StopSigns(side_road)
+            );
+        }
+    }
+
+    #[test]
+    fn signals_never_conflict() {
+        let crossing = Intersection::new((3, 5), &Side::ALL).unwrap();
+
+        for now in (0..Intersection::CYCLE * 2).step_by(100) {
+            let vertical = crossing.is_green(Side::Top, now);
+            let horizontal = crossing.is_green(Side::Left, now);
+
+            assert!(!(vertical && horizontal));
+            assert_eq!(vertical, crossing.is_green(Side::Bottom, now));
+            assert_eq!(horizontal, crossing.is_green(Side::Right, now));
+        }
+    }
+
+    #[test]
+    fn one_vehicle_at_a_time() {
+        let junction = intersection_of(Road::LeftTopRight).unwrap();
+
+        assert!(junction.may_enter(1, Side::Top, 0));
+        assert!(!junction.may_enter(2, Side::Bottom, 10));
+        assert!(junction.may_enter(1, Side::Top, 20));
+
+        junction.leave(1);
+
+        assert!(junction.may_enter(2, Side::Bottom, 30));
+    }
+
+    #[test]
+    fn side_road_yields_to_through_traffic() {
+        // the side road of the junction joins from the left.
+        let junction = intersection_of(Road::LeftTopRight).unwrap();
+
+        assert!(junction.may_enter(1, Side::Top, 0));
+        // through traffic from the bottom is waiting behind the first vehicle.
+        assert!(!junction.may_enter(2, Side::Bottom, 10));
+
+        junction.leave(1);
+
+        assert!(!junction.may_enter(3, Side::Left, 20));
+        assert!(junction.may_enter(2, Side::Bottom, 30));
+
+        junction.leave(2);
+
+        assert!(junction.may_enter(3, Side::Left, 1_000));
+    }
+
+    #[test]
+    fn despawned_vehicles_release_the_junction() {
+        let junction = intersection_of(Road::LeftTopRight).unwrap();
+
+        assert!(junction.may_enter(1, Side::Top, 0));
+        assert!(!junction.may_enter(2, Side::Left, 1_000));
+        assert!(junction.may_enter(2, Side::Left, Intersection::OCCUPATION_TIMEOUT + 1_000));
+    }
+}
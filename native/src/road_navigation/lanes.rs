@@ -0,0 +1,594 @@
+/*
+ * Copyright (c) SimChopper; Jovan Gerodetti and contributors.
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! Directional lanes of the road network.
+//!
+//! Every road piece connects a set of tile sides. Vehicles keep to the right hand lane of their
+//! direction of travel, so a vehicle on a tile is described by the tile and the side it entered
+//! through. It can leave the tile through every other connected side, U-turns are only legal at
+//! dead ends.
+
+use std::cmp::Ordering;
+
+use godot::builtin::Vector3;
+use num_enum::TryFromPrimitive;
+
+use crate::objects::scene_object_registry::{
+    Bridge, Highway, HighwayBridge, HighwayOnRamp, RailCrossing, Road, Tunnel,
+};
+use crate::world::city_data::TileCoords;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Side {
+    Top,
+    Right,
+    Bottom,
+    Left,
+}
+
+impl Side {
+    pub const ALL: [Self; 4] = [Self::Top, Self::Right, Self::Bottom, Self::Left];
+
+    pub fn opposite(self) -> Self {
+        match self {
+            Self::Top => Self::Bottom,
+            Self::Right => Self::Left,
+            Self::Bottom => Self::Top,
+            Self::Left => Self::Right,
+        }
+    }
+
+    /// Coordinates of the neighbouring tile on this side.
+    pub fn neighbor(self, (x, y): TileCoords) -> Option<TileCoords> {
+        match self {
+            Self::Top => y.checked_sub(1).map(|y| (x, y)),
+            Self::Right => Some((x + 1, y)),
+            Self::Bottom => Some((x, y + 1)),
+            Self::Left => x.checked_sub(1).map(|x| (x, y)),
+        }
+    }
+
+    /// The side of the `from` tile that borders the `to` tile, if both tiles are adjacent.
+    pub fn between(from: TileCoords, to: TileCoords) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|side| side.neighbor(from) == Some(to))
+    }
+
+    /// The side of the `from` tile that faces the `to` tile, if both tiles are in the same row or
+    /// column. Unlike [`Side::between`], the tiles do not have to be adjacent, like the two portals
+    /// of a tunnel.
+    pub fn towards(from: TileCoords, to: TileCoords) -> Option<Self> {
+        match (from.0.cmp(&to.0), from.1.cmp(&to.1)) {
+            (Ordering::Equal, Ordering::Greater) => Some(Self::Top),
+            (Ordering::Less, Ordering::Equal) => Some(Self::Right),
+            (Ordering::Equal, Ordering::Less) => Some(Self::Bottom),
+            (Ordering::Greater, Ordering::Equal) => Some(Self::Left),
+            _ => None,
+        }
+    }
+
+    /// World direction that points out of a tile through this side.
+    pub fn direction(self) -> Vector3 {
+        match self {
+            Self::Top => Vector3::FORWARD,
+            Self::Right => Vector3::RIGHT,
+            Self::Bottom => Vector3::BACK,
+            Self::Left => Vector3::LEFT,
+        }
+    }
+
+    /// The side a world direction is pointing to.
+    pub fn from_direction(direction: Vector3) -> Self {
+        if direction.x.abs() > direction.z.abs() {
+            if direction.x > 0.0 {
+                Self::Right
+            } else {
+                Self::Left
+            }
+        } else if direction.z > 0.0 {
+            Self::Bottom
+        } else {
+            Self::Top
+        }
+    }
+}
+
+/// Sides of a tile that are connected by a road piece.
+///
+/// The names of the road pieces follow the isometric view of SC2K, they do not match the sides of
+/// the tile grid.
+///
+/// Bridge pieces do not encode their orientation. They span the axis of their neighbouring road
+/// pieces, which is resolved by the navigation graph.
+///
+/// Tunnel portals connect the road on one side with the tunnel on the opposite side.
+///
+/// The sides of highway corners depend on the position of the tile inside the corner, see
+/// [`highway_corner_sides`].
+pub(crate) fn road_sides(building_id: u8) -> Option<&'static [Side]> {
+    if let Ok(crossing) = RailCrossing::try_from_primitive(building_id) {
+        return Some(match crossing {
+            RailCrossing::RoadLeftRightRailTopBottom => &[Side::Top, Side::Bottom],
+            RailCrossing::RoadTopBottomRailLeftRight => &[Side::Right, Side::Left],
+        });
+    }
+
+    if let Ok(highway) = Highway::try_from_primitive(building_id) {
+        return highway_sides(highway);
+    }
+
+    if let Ok(ramp) = HighwayOnRamp::try_from_primitive(building_id) {
+        return Some(match ramp {
+            HighwayOnRamp::TopLeft => &[Side::Bottom, Side::Left],
+            HighwayOnRamp::TopRight => &[Side::Right, Side::Bottom],
+            HighwayOnRamp::BottomLeft => &[Side::Top, Side::Left],
+            HighwayOnRamp::BottomRight => &[Side::Top, Side::Right],
+        });
+    }
+
+    if let Ok(bridge) = HighwayBridge::try_from_primitive(building_id) {
+        return Some(match bridge {
+            HighwayBridge::LeftRight => &[Side::Top, Side::Bottom],
+            HighwayBridge::TopBottom => &[Side::Right, Side::Left],
+        });
+    }
+
+    if let Ok(tunnel) = Tunnel::try_from_primitive(building_id) {
+        return Some(match tunnel {
+            Tunnel::Top => &[Side::Right, Side::Left],
+            Tunnel::Right => &[Side::Bottom, Side::Top],
+            Tunnel::Bottom => &[Side::Left, Side::Right],
+            Tunnel::Left => &[Side::Top, Side::Bottom],
+        });
+    }
+
+    let road = Road::try_from_primitive(building_id).ok()?;
+
+    let sides: &[Side] = match road {
+        Road::LeftRight
+        | Road::LeftHighRight
+        | Road::HighLeftRight
+        | Road::LeftRightPowerTopBottom => &[Side::Top, Side::Bottom],
+        Road::TopBottom
+        | Road::HighTopBottom
+        | Road::TopHighBottom
+        | Road::TopBottomPowerLeftRight => &[Side::Right, Side::Left],
+        Road::TopRight => &[Side::Top, Side::Right],
+        Road::BottomRight => &[Side::Right, Side::Bottom],
+        Road::BottomLeft => &[Side::Bottom, Side::Left],
+        Road::TopLeft => &[Side::Top, Side::Left],
+        Road::RightTopBottom => &[Side::Top, Side::Right, Side::Left],
+        Road::LeftBottomRight => &[Side::Top, Side::Right, Side::Bottom],
+        Road::TopLeftBottom => &[Side::Right, Side::Bottom, Side::Left],
+        Road::LeftTopRight => &[Side::Top, Side::Bottom, Side::Left],
+        Road::LeftTopBottomRight => &Side::ALL,
+    };
+
+    Some(sides)
+}
+
+fn highway_sides(highway: Highway) -> Option<&'static [Side]> {
+    let sides: &[Side] = match highway {
+        Highway::LeftRight
+        | Highway::LeftHighRight
+        | Highway::HighLeftRight
+        | Highway::LeftRightRailTopBottom
+        | Highway::LeftRightPowerTopBottom => &[Side::Top, Side::Bottom],
+        Highway::TopBottom
+        | Highway::HighTopBottom
+        | Highway::TopHighBottom
+        | Highway::TopBottomRailLeftRight
+        | Highway::TopBottomPowerLeftRight => &[Side::Right, Side::Left],
+        // the road passes below the highway, see [`is_overpass`].
+        Highway::LeftRightRoadTopBottom
+        | Highway::TopBottomRoadLeftRight
+        | Highway::LeftTopBottomRight => &Side::ALL,
+        Highway::TopRight | Highway::BottomRight | Highway::BottomLeft | Highway::TopLeft => {
+            return None
+        }
+    };
+
+    Some(sides)
+}
+
+/// Sides of a tile of a highway corner.
+///
+/// Corners fill a 2×2 block of tiles with the same piece. The inner lane turns on the tile that
+/// borders both connected sides of the block, the outer lane on the tile diagonally across from
+/// it. The block is found by looking up the pieces of the neighbouring tiles with
+/// `building_id_of`.
+pub(crate) fn highway_corner_sides(
+    tile: TileCoords,
+    building_id_of: impl Fn(TileCoords) -> Option<u8>,
+) -> Option<[Side; 2]> {
+    let building_id = building_id_of(tile)?;
+
+    let (horizontal, vertical) = match Highway::try_from_primitive(building_id).ok()? {
+        Highway::TopRight => (Side::Right, Side::Bottom),
+        Highway::BottomRight => (Side::Right, Side::Top),
+        Highway::BottomLeft => (Side::Left, Side::Top),
+        Highway::TopLeft => (Side::Left, Side::Bottom),
+        _ => return None,
+    };
+
+    let is_block = |side: Side| {
+        side.neighbor(tile)
+            .and_then(&building_id_of)
+            .is_some_and(|id| id == building_id)
+    };
+
+    // the edges of the block the tile lies on.
+    let column = if is_block(Side::Left) {
+        Side::Right
+    } else {
+        Side::Left
+    };
+    let row = if is_block(Side::Top) {
+        Side::Bottom
+    } else {
+        Side::Top
+    };
+
+    let sides = match (column == horizontal, row == vertical) {
+        (true, false) => [horizontal, horizontal.opposite()],
+        (false, true) => [vertical.opposite(), vertical],
+        (true, true) | (false, false) => [horizontal, vertical],
+    };
+
+    Some(sides)
+}
+
+pub(crate) fn is_bridge(building_id: u8) -> bool {
+    Bridge::try_from_primitive(building_id).is_ok()
+}
+
+/// Highways cross other roads on a different level, vehicles never have to give way on them.
+pub(crate) fn is_highway(building_id: u8) -> bool {
+    Highway::try_from_primitive(building_id).is_ok()
+        || HighwayBridge::try_from_primitive(building_id).is_ok()
+}
+
+/// Pieces where a highway passes over a road. Vehicles can only go straight on, they can not
+/// switch between the highway and the road.
+pub(crate) fn is_overpass(building_id: u8) -> bool {
+    matches!(
+        Highway::try_from_primitive(building_id),
+        Ok(Highway::LeftRightRoadTopBottom | Highway::TopBottomRoadLeftRight)
+    )
+}
+
+/// The side of a tunnel portal through which the tunnel leads into the ground.
+pub(crate) fn tunnel_side(building_id: u8) -> Option<Side> {
+    let tunnel = Tunnel::try_from_primitive(building_id).ok()?;
+
+    let side = match tunnel {
+        Tunnel::Top => Side::Left,
+        Tunnel::Right => Side::Top,
+        Tunnel::Bottom => Side::Right,
+        Tunnel::Left => Side::Bottom,
+    };
+
+    Some(side)
+}
+
+/// A vehicle on a road tile, together with the side it entered the tile through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct Lane {
+    pub tile: TileCoords,
+    /// `None` if the vehicle has been placed on the tile and did not enter it from a neighbour.
+    pub entered_from: Option<Side>,
+}
+
+impl Lane {
+    pub fn new(tile: TileCoords, entered_from: Option<Side>) -> Self {
+        Self { tile, entered_from }
+    }
+}
+
+/// All lanes a vehicle can legally move to from its current lane.
+///
+/// Two tiles are only connected if both pieces connect the shared side.
+pub(crate) fn successors<'n>(
+    lane: Lane,
+    sides_of: impl Fn(TileCoords) -> Option<&'n [Side]>,
+) -> Vec<Lane> {
+    linked_successors(lane, sides_of, |tile, side| side.neighbor(tile))
+}
+
+/// Like [`successors`], but the tile that is linked to a side of a tile is looked up with
+/// `linked_tile`. This allows links between tiles that are not adjacent, like the two portals
+/// of a tunnel.
+pub(crate) fn linked_successors<'n>(
+    lane: Lane,
+    sides_of: impl Fn(TileCoords) -> Option<&'n [Side]>,
+    linked_tile: impl Fn(TileCoords, Side) -> Option<TileCoords>,
+) -> Vec<Lane> {
+    let Some(sides) = sides_of(lane.tile) else {
+        return Vec::new();
+    };
+
+    let linked: Vec<(Side, TileCoords)> = sides
+        .iter()
+        .filter_map(|side| {
+            let neighbor = linked_tile(lane.tile, *side)?;
+
+            sides_of(neighbor)?
+                .contains(&side.opposite())
+                .then_some((*side, neighbor))
+        })
+        .collect();
+
+    let dead_end = linked.len() <= 1;
+
+    linked
+        .into_iter()
+        .filter(|(side, _)| dead_end || Some(*side) != lane.entered_from)
+        .map(|(side, neighbor)| Lane::new(neighbor, Some(side.opposite())))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+    use std::path::Path;
+
+    use num_enum::TryFromPrimitive;
+
+    use super::{
+        highway_corner_sides, linked_successors, road_sides, successors, tunnel_side, Lane, Side,
+    };
+    use crate::objects::scene_object_registry::{Highway, Road, Tunnel};
+    use crate::road_navigation::find_route;
+    use crate::world::city_data::TileCoords;
+    use crate::world::city_loader::load_city_file;
+
+    fn network(pieces: &[(TileCoords, u8)]) -> BTreeMap<TileCoords, &'static [Side]> {
+        pieces
+            .iter()
+            .map(|(tile, id)| (*tile, road_sides(*id).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn pieces_only_connect_matching_sides() {
+        // the piece names follow the isometric view, a road along the x-axis next to a road along
+        // the y-axis does not connect.
+        let network = network(&[
+            ((0, 0), Road::TopBottom as u8),
+            ((1, 0), Road::LeftRight as u8),
+        ]);
+
+        let lanes = successors(Lane::new((0, 0), None), |tile| network.get(&tile).copied());
+
+        assert!(lanes.is_empty());
+    }
+
+    #[test]
+    fn road_pieces_of_a_bundled_map_agree_with_their_neighbours() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../resources/Maps/tokyo.sc2.mpz");
+        let city = load_city_file(&path).expect("tokyo map is valid");
+
+        let roads: BTreeMap<TileCoords, &[Side]> = city
+            .networks
+            .iter()
+            .filter(|(_, piece)| Road::try_from_primitive(piece.id).is_ok())
+            .map(|(tile, piece)| (*tile, road_sides(piece.id).unwrap()))
+            .collect();
+
+        assert!(!roads.is_empty());
+
+        for (tile, sides) in &roads {
+            for side in *sides {
+                let Some(neighbor_sides) = side.neighbor(*tile).and_then(|tile| roads.get(&tile))
+                else {
+                    continue;
+                };
+
+                assert!(
+                    neighbor_sides.contains(&side.opposite()),
+                    "road at {tile:?} connects {side:?}, but its neighbour does not connect back",
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn no_u_turns() {
+        let network = network(&[
+            ((0, 1), Road::TopBottom as u8),
+            ((1, 1), Road::LeftTopBottomRight as u8),
+            ((2, 1), Road::TopBottom as u8),
+            ((1, 0), Road::LeftRight as u8),
+            ((1, 2), Road::LeftRight as u8),
+        ]);
+
+        let lanes = successors(Lane::new((1, 1), Some(Side::Left)), |tile| {
+            network.get(&tile).copied()
+        });
+
+        assert_eq!(
+            lanes,
+            vec![
+                Lane::new((1, 0), Some(Side::Bottom)),
+                Lane::new((2, 1), Some(Side::Left)),
+                Lane::new((1, 2), Some(Side::Top)),
+            ]
+        );
+    }
+
+    #[test]
+    fn turns_around_at_dead_ends() {
+        let network = network(&[
+            ((0, 0), Road::TopBottom as u8),
+            ((1, 0), Road::TopBottom as u8),
+        ]);
+
+        let lanes = successors(Lane::new((1, 0), Some(Side::Left)), |tile| {
+            network.get(&tile).copied()
+        });
+
+        assert_eq!(lanes, vec![Lane::new((0, 0), Some(Side::Right))]);
+    }
+
+    #[test]
+    fn routes_around_the_block_instead_of_turning() {
+        // a loop around a block, the vehicle faces right and the target is behind it.
+        let network = network(&[
+            ((0, 0), Road::BottomRight as u8),
+            ((1, 0), Road::TopBottom as u8),
+            ((2, 0), Road::BottomLeft as u8),
+            ((0, 1), Road::TopRight as u8),
+            ((1, 1), Road::TopBottom as u8),
+            ((2, 1), Road::TopLeft as u8),
+        ]);
+
+        let start = Lane::new((1, 0), Some(Side::Left));
+        let route = find_route(
+            start,
+            |lane| lane.tile == (0, 0),
+            |_| 0,
+            |lane| successors(lane, |tile| network.get(&tile).copied()),
+        )
+        .unwrap();
+
+        let tiles: Vec<_> = route.into_iter().map(|lane| lane.tile).collect();
+
+        assert_eq!(tiles, vec![(1, 0), (2, 0), (2, 1), (1, 1), (0, 1), (0, 0)]);
+    }
+
+    fn route_through_tunnel(pieces: &[(TileCoords, u8)]) -> Vec<TileCoords> {
+        let ids: BTreeMap<TileCoords, u8> = pieces.iter().copied().collect();
+        let network = network(pieces);
+        let portals: Vec<_> = pieces[1..3].iter().map(|(tile, _)| *tile).collect();
+        let (start, target) = (pieces[0].0, pieces[3].0);
+
+        let linked_tile = |tile, side: Side| {
+            if ids.get(&tile).and_then(|id| tunnel_side(*id)) == Some(side) {
+                return portals.iter().copied().find(|portal| *portal != tile);
+            }
+
+            side.neighbor(tile)
+        };
+
+        let route = find_route(
+            Lane::new(start, None),
+            |lane| lane.tile == target,
+            |_| 0,
+            |lane| linked_successors(lane, |tile| network.get(&tile).copied(), linked_tile),
+        )
+        .unwrap();
+
+        route.into_iter().map(|lane| lane.tile).collect()
+    }
+
+    #[test]
+    fn tunnels_link_their_portals() {
+        // the tunnel runs below the tiles between the two portals.
+        let tiles = route_through_tunnel(&[
+            ((0, 0), Road::TopBottom as u8),
+            // the portals lead into the ground through their right and left side.
+            ((1, 0), Tunnel::Bottom as u8),
+            ((5, 0), Tunnel::Top as u8),
+            ((6, 0), Road::TopBottom as u8),
+        ]);
+
+        assert_eq!(tiles, vec![(0, 0), (1, 0), (5, 0), (6, 0)]);
+
+        let tiles = route_through_tunnel(&[
+            ((0, 0), Road::LeftRight as u8),
+            // the portals lead into the ground through their bottom and top side.
+            ((0, 1), Tunnel::Left as u8),
+            ((0, 5), Tunnel::Right as u8),
+            ((0, 6), Road::LeftRight as u8),
+        ]);
+
+        assert_eq!(tiles, vec![(0, 0), (0, 1), (0, 5), (0, 6)]);
+    }
+
+    #[test]
+    fn highway_corners_turn_inside_their_block() {
+        // a highway from the left turns down inside the corner block at (1, 0) to (2, 1).
+        let ids = BTreeMap::from([
+            ((0, 0), Highway::TopBottom as u8),
+            ((0, 1), Highway::TopBottom as u8),
+            ((1, 0), Highway::TopLeft as u8),
+            ((2, 0), Highway::TopLeft as u8),
+            ((1, 1), Highway::TopLeft as u8),
+            ((2, 1), Highway::TopLeft as u8),
+            ((1, 2), Highway::LeftRight as u8),
+            ((2, 2), Highway::LeftRight as u8),
+        ]);
+        let network: BTreeMap<TileCoords, Vec<Side>> = ids
+            .iter()
+            .map(|(tile, id)| {
+                let sides = highway_corner_sides(*tile, |tile| ids.get(&tile).copied())
+                    .map_or_else(|| road_sides(*id).unwrap().to_vec(), Vec::from);
+
+                (*tile, sides)
+            })
+            .collect();
+        let sides_of = |tile| network.get(&tile).map(Vec::as_slice);
+
+        assert_eq!(sides_of((1, 0)), Some([Side::Left, Side::Right].as_slice()));
+        assert_eq!(
+            sides_of((2, 0)),
+            Some([Side::Left, Side::Bottom].as_slice())
+        );
+        assert_eq!(
+            sides_of((1, 1)),
+            Some([Side::Left, Side::Bottom].as_slice())
+        );
+        assert_eq!(sides_of((2, 1)), Some([Side::Top, Side::Bottom].as_slice()));
+
+        let route = find_route(
+            Lane::new((0, 0), None),
+            |lane| lane.tile == (2, 2),
+            |_| 0,
+            |lane| successors(lane, sides_of),
+        )
+        .unwrap();
+
+        let tiles: Vec<_> = route.into_iter().map(|lane| lane.tile).collect();
+
+        assert_eq!(tiles, vec![(0, 0), (1, 0), (2, 0), (2, 1), (2, 2)]);
+
+        // the lanes do not cross inside the block.
+        assert_eq!(
+            successors(Lane::new((1, 0), Some(Side::Left)), sides_of),
+            vec![Lane::new((2, 0), Some(Side::Left))]
+        );
+    }
+
+    #[test]
+    fn side_between_tiles() {
+        assert_eq!(Side::between((2, 2), (2, 1)), Some(Side::Top));
+        assert_eq!(Side::between((2, 2), (3, 2)), Some(Side::Right));
+        assert_eq!(Side::between((2, 2), (2, 3)), Some(Side::Bottom));
+        assert_eq!(Side::between((2, 2), (1, 2)), Some(Side::Left));
+        assert_eq!(Side::between((2, 2), (2, 9)), None);
+        assert_eq!(Side::between((2, 2), (3, 3)), None);
+        assert_eq!(Side::between((2, 2), (2, 2)), None);
+    }
+
+    #[test]
+    fn side_towards_tiles() {
+        assert_eq!(Side::towards((2, 2), (2, 0)), Some(Side::Top));
+        assert_eq!(Side::towards((2, 2), (3, 2)), Some(Side::Right));
+        assert_eq!(Side::towards((2, 2), (2, 9)), Some(Side::Bottom));
+        assert_eq!(Side::towards((2, 2), (1, 2)), Some(Side::Left));
+        assert_eq!(Side::towards((2, 2), (3, 3)), None);
+        assert_eq!(Side::towards((2, 2), (2, 2)), None);
+    }
+
+    #[test]
+    fn side_from_direction() {
+        for side in Side::ALL {
+            assert_eq!(Side::from_direction(side.direction()), side);
+        }
+    }
+}
@@ -5,16 +5,14 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/.
  */
 
-use godot::builtin::{Callable, Color, NodePath};
 use godot::classes::Decal;
-use godot::meta::ToGodot;
 use godot::obj::Gd;
-use godot_rust_script::{godot_script_impl, CastToScript, Context, GodotScript, OnEditor, RsRef};
+use godot_rust_script::{godot_script_impl, GodotScript, OnEditor};
 
 use crate::resources::WaterDecalTracker;
-use crate::util;
-use crate::{script_callable, util::logger};
 
+/// A patch of water on a surface. The patch dries through the [`WaterDecalTracker`], which frees
+/// the decal once it is dry.
 #[derive(GodotScript, Debug)]
 #[script(base = Decal)]
 struct WaterDecal {
@@ -27,41 +25,12 @@ struct WaterDecal {
 
 #[godot_script_impl]
 impl WaterDecal {
-    const LIFETIME: f64 = 6.0;
-
-    pub fn _ready(&mut self) {
+    /// Decals also leave the tree when the node they landed on is removed.
+    pub fn _exit_tree(&mut self) {
         if !self.is_active {
             return;
         }
 
-        util::timer(&mut self.base.get_tree(), Self::LIFETIME)
-            .connect("timeout", &script_callable!(self, Self::on_timeout));
-    }
-
-    pub fn on_timeout(&mut self, mut ctx: Context<Self>) {
-        let Some(mut tween) = self.base.create_tween() else {
-            logger::error!("Failed to create tween!");
-            return;
-        };
-
-        ctx.reentrant_scope(self, |base: Gd<Decal>| {
-            tween.tween_property(
-                &base,
-                &NodePath::from("modulate"),
-                &Color::TRANSPARENT_WHITE.to_variant(),
-                1.0,
-            );
-
-            let mut script: RsRef<Self> = base.clone().into_script();
-
-            tween.tween_callback(&Callable::from_fn("water_decal_tween_out", move |_| {
-                script.clean_up();
-            }));
-        });
-    }
-
-    pub fn clean_up(&mut self) {
         self.decal_tracker.bind_mut().free(&self.base);
-        self.base.queue_free();
     }
 }
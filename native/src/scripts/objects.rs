@@ -10,4 +10,5 @@ mod building;
 mod camera;
 mod canon_upgrade;
 mod debugger_3_d;
+mod teargas_jet;
 mod water_jet;
@@ -6,3 +6,5 @@
  */
 
 mod car;
+mod fire_engine;
+mod train;
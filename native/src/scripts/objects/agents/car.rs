@@ -5,12 +5,13 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/.
  */
 
+use std::collections::VecDeque;
 use std::ops::Neg;
 
 use godot::builtin::math::ApproxEq;
-use godot::builtin::{Transform3D, Vector2i, Vector3};
+use godot::builtin::{array, Array, Transform3D, Vector2i, Vector3};
 use godot::classes::{
-    MeshInstance3D, PhysicsDirectBodyState3D, ProjectSettings, RayCast3D, RigidBody3D,
+    MeshInstance3D, PhysicsDirectBodyState3D, ProjectSettings, RayCast3D, RigidBody3D, Time,
 };
 use godot::meta::ToGodot;
 use godot::obj::{Gd, Singleton as _};
@@ -18,7 +19,7 @@ use godot_rust_script::{godot_script_impl, GodotScript, OnEditor, RsRef};
 
 use crate::debug_3d;
 use crate::project_settings::CustomProjectSettings;
-use crate::road_navigation::{NavNodeRef, RoadNavigation, RoadNavigationConfig};
+use crate::road_navigation::{NavNodeRef, RoadNavigation, RoadNavigationConfig, Side};
 use crate::scripts::objects::debugger_3_d::Debugger3D;
 use crate::util::{self, logger};
 use crate::world::city_data::TileCoords;
@@ -65,6 +66,8 @@ struct MovingNavigation {
     current: TileCoords,
     target: TileCoords,
     next: TileCoords,
+    /// Remaining nodes of the route after the next node.
+    route: VecDeque<TileCoords>,
 }
 
 #[derive(GodotScript, Debug)]
@@ -76,6 +79,8 @@ struct Car {
     on_ground: bool,
     ground_normal: Vector3,
     stuck: f32,
+    /// Seconds until the driver recovers from teargas.
+    gassed: f32,
     last_transform: Transform3D,
     navigation: Navigation,
 
@@ -102,6 +107,9 @@ struct Car {
 
 #[godot_script_impl]
 impl Car {
+    /// Seconds a driver stops after being exposed to teargas.
+    const GASSED_TIME: f32 = 3.0;
+
     pub fn _init(&mut self) {
         self.velocity = 30.0;
         self.ground_normal = Vector3::DOWN;
@@ -128,6 +136,44 @@ impl Car {
         self.choose_target();
     }
 
+    /// Tile coordinates of the navigation node the car is heading to. Empty while the car is not
+    /// navigating.
+    pub fn target_node(&self) -> Array<u32> {
+        let (x, y) = match &self.navigation {
+            Navigation::Uninitialized | Navigation::Located(_) => return Array::new(),
+            Navigation::Targeted(navigation) => navigation.target_node,
+            Navigation::Moving(navigation) => navigation.target,
+        };
+
+        array![x, y]
+    }
+
+    /// Activates a car of a restored session and continues the navigation to its previous target.
+    pub fn resume(&mut self, target_node: Array<u32>) {
+        self.activate();
+
+        let (Some(x), Some(y)) = (target_node.get(0), target_node.get(1)) else {
+            return;
+        };
+
+        let Navigation::Targeted(navigation) = &mut self.navigation else {
+            return;
+        };
+
+        if self
+            .road_network
+            .bind()
+            .road_navigation()
+            .try_node((x, y))
+            .is_none()
+        {
+            logger::warn!("Restored car target is not part of the road network!");
+            return;
+        }
+
+        navigation.target_node = (x, y);
+    }
+
     /// Godot's physics callback called at the projects physics step.
     #[allow(clippy::used_underscore_items)]
     pub fn _physics_process(&mut self, delta: f32) {
@@ -138,35 +184,80 @@ impl Car {
         let navigation = match &self.navigation {
             Navigation::Uninitialized | Navigation::Located(_) => return,
             Navigation::Targeted(targeted_navigation) => {
-                let road_network = self.road_network.bind();
-                let road_navigation = road_network.road_navigation();
-
-                let current_node = road_navigation.node(targeted_navigation.current_node);
-                let target_node = road_navigation.node(targeted_navigation.target_node);
+                let current = targeted_navigation.current_node;
+                let route = self.plan_route(
+                    current,
+                    Some(Side::from_direction(agent_rot)),
+                    targeted_navigation.target_node,
+                );
+
+                if !self.follow_route(current, route) {
+                    return;
+                }
 
-                self.navigation = self
-                    .get_next_node(&current_node, &target_node, agent_rot)
-                    .into();
                 self.navigation.as_moving()
             }
             Navigation::Moving(moving_navigation) => {
-                let road_network = self.road_network.bind();
-                let road_navigation = road_network.road_navigation();
+                let has_arrived = {
+                    let road_network = self.road_network.bind();
 
-                let current_node = road_navigation.node(moving_navigation.next);
-                let target_node = road_navigation.node(moving_navigation.target);
+                    road_network
+                        .road_navigation()
+                        .try_node(moving_navigation.next)
+                        .map(|next_node| next_node.has_arrived(agent_pos, agent_rot))
+                };
 
-                if current_node.has_arrived(agent_pos, agent_rot) {
-                    self.navigation = self
-                        .get_next_node(&current_node, &target_node, agent_rot)
-                        .into();
-                    self.navigation.as_moving()
-                } else {
+                if has_arrived == Some(false) {
                     moving_navigation
+                } else {
+                    let (current, route) = if has_arrived.is_some() {
+                        self.road_network
+                            .bind()
+                            .road_navigation()
+                            .leave(moving_navigation.current, self.vehicle_id());
+
+                        (
+                            moving_navigation.next,
+                            self.advance_route(moving_navigation),
+                        )
+                    } else {
+                        // the next node disappeared from the road network.
+                        (
+                            moving_navigation.current,
+                            self.plan_route(
+                                moving_navigation.current,
+                                Some(Side::from_direction(agent_rot)),
+                                moving_navigation.target,
+                            ),
+                        )
+                    };
+
+                    if !self.follow_route(current, route) {
+                        return;
+                    }
+
+                    self.navigation.as_moving()
                 }
             }
         };
 
+        if self.gassed > 0.0 {
+            // drivers can not see anything until the gas cleared, which is not being stuck either.
+            self.gassed = (self.gassed - delta).max(0.0);
+            self.stuck = 0.0;
+            self.last_transform = self.base.get_global_transform();
+            self.set_velocity(Vector3::ZERO);
+            return;
+        }
+
+        if !self.has_right_of_way(navigation, agent_pos) {
+            // waiting in front of an intersection does not count as being stuck.
+            self.stuck = 0.0;
+            self.last_transform = self.base.get_global_transform();
+            self.set_velocity(Vector3::ZERO);
+            return;
+        }
+
         self.stuck = if self.last_transform.origin.approx_eq(&agent_pos) {
             self.stuck + 1.0 * delta
         } else {
@@ -238,6 +329,11 @@ impl Car {
         debug_3d!(self.debugger => stuck);
     }
 
+    /// The car is inside a cloud of teargas and stops for a while.
+    pub fn impact_teargas(&mut self, _delta: f64) {
+        self.gassed = Self::GASSED_TIME;
+    }
+
     /// Godot's physics body force callback.
     #[expect(clippy::needless_pass_by_value)]
     pub fn _integrate_forces(&mut self, state: Gd<PhysicsDirectBodyState3D>) {
@@ -324,9 +420,7 @@ impl Car {
                 target_node: target,
             })
             | Navigation::Moving(MovingNavigation {
-                current,
-                target,
-                next: _,
+                current, target, ..
             }) => (
                 current,
                 Self::get_random_street_location(road_navigation, Some(target)),
@@ -383,33 +477,113 @@ impl Car {
         navigation.current == navigation.next && navigation.current == navigation.target
     }
 
-    /// Determines the next node on the path to the target node.
-    fn get_next_node(
+    /// Plans the route from the current node to the target node. The car will not turn around
+    /// if it is heading to one of the sides of the current node.
+    fn plan_route(
         &self,
-        current_node: &NavNodeRef<'_>,
-        target_node: &NavNodeRef<'_>,
-        agent_rot: Vector3,
-    ) -> MovingNavigation {
+        current: TileCoords,
+        heading: Option<Side>,
+        target: TileCoords,
+    ) -> Option<MovingNavigation> {
         let road_network = self.road_network.bind();
+        let mut route: VecDeque<_> = road_network
+            .road_navigation()
+            .plan_route(current, heading, target)?
+            .into();
+
+        // the route starts with the current node.
+        route.pop_front();
+
+        Some(MovingNavigation {
+            current,
+            target,
+            next: route.pop_front().unwrap_or(current),
+            route,
+        })
+    }
 
-        let next_node =
-            road_network
-                .road_navigation()
-                .get_next_node(current_node, target_node, agent_rot);
+    /// Moves on to the next node of the route. The route is only planned again when its next node
+    /// disappeared from the road network.
+    fn advance_route(&self, navigation: &MovingNavigation) -> Option<MovingNavigation> {
+        let current = navigation.next;
+        let mut route = navigation.route.clone();
+        let next = route.pop_front().unwrap_or(current);
+
+        let next_exists = self
+            .road_network
+            .bind()
+            .road_navigation()
+            .try_node(next)
+            .is_some();
+
+        if !next_exists {
+            logger::debug!("car route is blocked, planning a new route");
+            let heading = Side::towards(navigation.current, current);
+
+            return self.plan_route(current, heading, navigation.target);
+        }
+
+        Some(MovingNavigation {
+            current,
+            target: navigation.target,
+            next,
+            route,
+        })
+    }
 
-        MovingNavigation {
-            current: current_node.tile_coords(),
-            target: target_node.tile_coords(),
-            next: next_node.tile_coords(),
+    /// Follows the new route, or picks a new target if the current target can not be reached.
+    /// Returns whether the car has a route to follow.
+    fn follow_route(&mut self, current: TileCoords, route: Option<MovingNavigation>) -> bool {
+        if let Some(route) = route {
+            self.navigation = route.into();
+            return true;
         }
+
+        logger::debug!("car target is unreachable, choosing a new target");
+        self.navigation = Navigation::Located(current);
+        self.choose_target();
+        false
+    }
+
+    /// Whether the car may drive on to the next node. Cars have to wait in front of intersections
+    /// until they have the right of way.
+    fn has_right_of_way(&self, navigation: &MovingNavigation, agent_pos: Vector3) -> bool {
+        let Some(from) = Side::towards(navigation.next, navigation.current) else {
+            return true;
+        };
+
+        let road_network = self.road_network.bind();
+        let road_navigation = road_network.road_navigation();
+
+        if !road_navigation.is_intersection(navigation.next)
+            || !road_navigation
+                .node(navigation.next)
+                .is_at_stop_line(agent_pos)
+        {
+            return true;
+        }
+
+        road_navigation.may_enter(
+            navigation.next,
+            from,
+            self.vehicle_id(),
+            Time::singleton().get_ticks_msec(),
+        )
+    }
+
+    fn vehicle_id(&self) -> u64 {
+        self.base.instance_id().to_u64()
     }
 
-    /// Get the world position of the next node on the path to the target.
+    /// Get the world position of the next node on the path to the target. The car keeps to the
+    /// lane of its direction of travel between the current and the next node.
     fn get_next_pos(&self, navigation: &MovingNavigation, agent_rot: Vector3) -> Vector3 {
         let road_network = self.road_network.bind();
         let next_node = road_network.road_navigation().node(navigation.next);
+        let direction =
+            Side::towards(navigation.current, navigation.next).map_or(agent_rot, Side::direction);
 
-        next_node.get_global_transform(agent_rot).origin
+        next_node.get_global_transform(direction).origin
     }
 
     fn set_velocity(&mut self, value: Vector3) {
@@ -0,0 +1,222 @@
+/*
+ * Copyright (c) SimChopper; Jovan Gerodetti and contributors.
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::collections::VecDeque;
+
+use godot::builtin::{Array, Vector3};
+use godot::classes::{Node, Node3D};
+use godot::meta::ToGodot;
+use godot::obj::Gd;
+use godot_rust_script::{godot_script_impl, GodotScript, OnEditor};
+
+use crate::road_navigation::{RoadNavigationConfig, Side};
+use crate::util::logger;
+use crate::world::city_data::TileCoords;
+use crate::world::fire_spread::FireSimulation;
+
+#[derive(Debug, Default)]
+enum Assignment {
+    #[default]
+    Idle,
+    /// Driving to the burning building.
+    Responding,
+    /// Hosing down the burning building.
+    Deployed,
+    /// Driving back to the station after the fire is out.
+    Returning,
+}
+
+/// A fire engine that drives from its station to a burning building and fights the fire.
+///
+/// Like trains, fire engines are not physics bodies. They follow their route from node to node and
+/// do not wait at intersections.
+#[derive(GodotScript, Debug)]
+#[script(base = Node3D)]
+struct FireEngine {
+    /// Speed of the fire engine in m/s.
+    #[export]
+    pub speed: f32,
+
+    /// Amount of water the engine puts on the fire, relative to the water cannon of a helicopter.
+    #[export]
+    pub hose_rate: f64,
+
+    #[export]
+    pub road_network: OnEditor<Gd<RoadNavigationConfig>>,
+
+    assignment: Assignment,
+    /// The road node next to the station.
+    station: TileCoords,
+    /// The road node next to the burning building.
+    scene: TileCoords,
+    building: TileCoords,
+    building_node: Option<Gd<Node>>,
+    fire_simulation: Option<Gd<FireSimulation>>,
+    /// The node the engine is currently on.
+    current: TileCoords,
+    /// Remaining nodes of the route.
+    route: VecDeque<TileCoords>,
+
+    base: Gd<Node3D>,
+}
+
+#[godot_script_impl]
+impl FireEngine {
+    pub fn _init(&mut self) {
+        self.speed = 25.0;
+        self.hose_rate = 0.5;
+    }
+
+    /// Sends the engine from the road node next to its station to the road node next to the
+    /// burning building.
+    pub fn dispatch(
+        &mut self,
+        station: Array<u32>,
+        scene: Array<u32>,
+        building: Array<u32>,
+        building_node: Gd<Node>,
+        fire_simulation: Gd<FireSimulation>,
+    ) {
+        let station = tile_coords(&station);
+        let scene = tile_coords(&scene);
+
+        let Some(position) = self.node_position(station, None) else {
+            logger::warn!(
+                "Fire engine can not leave from {:?}, there is no road!",
+                station
+            );
+            self.base.queue_free();
+            return;
+        };
+
+        self.base.set_global_position(position);
+        self.station = station;
+        self.scene = scene;
+        self.building = tile_coords(&building);
+        self.building_node = Some(building_node);
+        self.fire_simulation = Some(fire_simulation);
+        self.current = station;
+
+        if !self.plan_route(scene) {
+            logger::debug!("fire engine can not reach the fire at {:?}", scene);
+            self.base.queue_free();
+            return;
+        }
+
+        self.assignment = Assignment::Responding;
+    }
+
+    pub fn _process(&mut self, delta: f64) {
+        match self.assignment {
+            Assignment::Idle => (),
+            Assignment::Responding => {
+                if self.drive(delta) {
+                    logger::debug!("fire engine arrived at {:?}", self.scene);
+                    self.assignment = Assignment::Deployed;
+                }
+            }
+            Assignment::Deployed => {
+                if self.fight_fire(delta) {
+                    return;
+                }
+
+                if self.plan_route(self.station) {
+                    self.assignment = Assignment::Returning;
+                } else {
+                    self.base.queue_free();
+                }
+            }
+            Assignment::Returning => {
+                if self.drive(delta) {
+                    self.base.queue_free();
+                }
+            }
+        }
+    }
+
+    /// Hoses down the building while it is burning. Returns `false` once the fire is out.
+    fn fight_fire(&mut self, delta: f64) -> bool {
+        let is_burning = self
+            .fire_simulation
+            .as_ref()
+            .is_some_and(|simulation| simulation.bind().is_burning(self.building));
+
+        let Some(building) = self
+            .building_node
+            .as_mut()
+            .filter(|building| is_burning && building.is_instance_valid())
+        else {
+            return false;
+        };
+
+        building.call("impact_hose", &[(delta * self.hose_rate).to_variant()]);
+        true
+    }
+
+    fn plan_route(&mut self, target: TileCoords) -> bool {
+        let road_network = self.road_network.bind();
+        let Some(route) = road_network
+            .road_navigation()
+            .plan_route(self.current, None, target)
+        else {
+            return false;
+        };
+
+        // the route starts with the current node.
+        self.route = route.into_iter().skip(1).collect();
+        true
+    }
+
+    /// Drives along the route. Returns `true` once the end of the route has been reached.
+    fn drive(&mut self, delta: f64) -> bool {
+        let Some(next) = self.route.front().copied() else {
+            return true;
+        };
+
+        let direction = Side::towards(self.current, next).map(Side::direction);
+
+        let Some(target) = self.node_position(next, direction) else {
+            // the road disappeared, the engine can not go on.
+            self.route.clear();
+            return true;
+        };
+
+        #[expect(clippy::cast_possible_truncation)]
+        let step = self.speed * delta as f32;
+        let position = self.base.get_global_position();
+
+        if position.distance_to(target) <= step {
+            self.current = next;
+            self.route.pop_front();
+            self.base.set_global_position(target);
+            return self.route.is_empty();
+        }
+
+        self.base
+            .look_at_from_position(position.move_toward(target, step), target);
+        false
+    }
+
+    /// Position of the lane of a road node in the given direction of travel.
+    fn node_position(
+        &self,
+        tile_coords: TileCoords,
+        direction: Option<Vector3>,
+    ) -> Option<Vector3> {
+        let road_network = self.road_network.bind();
+        let node = road_network.road_navigation().try_node(tile_coords)?;
+
+        Some(
+            node.get_global_transform(direction.unwrap_or(Vector3::ZERO))
+                .origin,
+        )
+    }
+}
+
+fn tile_coords(array: &Array<u32>) -> TileCoords {
+    (array.get(0).unwrap_or(0), array.get(1).unwrap_or(0))
+}
@@ -0,0 +1,215 @@
+/*
+ * Copyright (c) SimChopper; Jovan Gerodetti and contributors.
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::collections::VecDeque;
+
+use godot::builtin::Vector3;
+use godot::classes::Node3D;
+use godot::obj::Gd;
+use godot_rust_script::{godot_script_impl, GodotScript, OnEditor};
+
+use crate::rail_navigation::RailNavigationConfig;
+use crate::road_navigation::Side;
+use crate::util::logger;
+use crate::world::city_data::TileCoords;
+
+/// A train that runs a consist of wagons along the rail network.
+///
+/// The train is not a physics body, the locomotive follows the tracks from node to node and every
+/// wagon follows the trail of the locomotive.
+#[derive(GodotScript, Debug)]
+#[script(base = Node3D)]
+struct Train {
+    /// Speed of the train in m/s.
+    #[export]
+    pub speed: f32,
+
+    /// Number of wagons that are coupled to the locomotive.
+    #[export]
+    pub wagon_count: u8,
+
+    /// Distance between the centers of two cars of the consist.
+    #[export]
+    pub car_spacing: f32,
+
+    #[export]
+    pub locomotive: OnEditor<Gd<Node3D>>,
+
+    /// Template that is duplicated for every wagon of the consist.
+    #[export]
+    pub wagon: OnEditor<Gd<Node3D>>,
+
+    #[export]
+    pub rail_network: OnEditor<Gd<RailNavigationConfig>>,
+
+    wagons: Vec<Gd<Node3D>>,
+    /// The node the locomotive is currently on.
+    current: Option<TileCoords>,
+    heading: Option<Side>,
+    /// Remaining nodes of the route.
+    route: VecDeque<TileCoords>,
+    /// Positions of the nodes the locomotive passed, the most recent first.
+    trail: VecDeque<Vector3>,
+
+    base: Gd<Node3D>,
+}
+
+#[godot_script_impl]
+impl Train {
+    pub fn _init(&mut self) {
+        self.speed = 20.0;
+        self.wagon_count = 3;
+        self.car_spacing = 12.0;
+    }
+
+    /// Places the train on the rail node at the given tile coordinates and starts driving.
+    pub fn start(&mut self, x: u32, y: u32) {
+        let Some(position) = self.rail_network.bind().rail_navigation().position((x, y)) else {
+            logger::warn!("Train can not start at {}, {}, there is no rail!", x, y);
+            return;
+        };
+
+        self.locomotive.set_global_position(position);
+        self.wagon.set_visible(false);
+
+        for mut wagon in self.wagons.drain(..) {
+            wagon.queue_free();
+        }
+
+        for _ in 0..self.wagon_count {
+            let mut wagon: Gd<Node3D> = self.wagon.duplicate_node();
+
+            self.base.add_child(&wagon);
+            wagon.set_global_position(position);
+            wagon.set_visible(true);
+            self.wagons.push(wagon);
+        }
+
+        self.current = Some((x, y));
+        self.heading = None;
+        self.route.clear();
+        self.trail.clear();
+    }
+
+    pub fn _process(&mut self, delta: f64) {
+        let Some(current) = self.current else {
+            return;
+        };
+
+        if self.route.is_empty() {
+            self.choose_target(current);
+        }
+
+        let Some(next) = self.route.front().copied() else {
+            return;
+        };
+
+        let Some(target) = self.rail_network.bind().rail_navigation().position(next) else {
+            // the track disappeared, plan a new route.
+            self.route.clear();
+            return;
+        };
+
+        #[expect(clippy::cast_possible_truncation)]
+        let step = self.speed * delta as f32;
+        let head = self.locomotive.get_global_position();
+
+        let head = if head.distance_to(target) <= step {
+            self.heading = Side::between(current, next);
+            self.current = Some(next);
+            self.route.pop_front();
+            self.trail.push_front(target);
+            target
+        } else {
+            head.move_toward(target, step)
+        };
+
+        Self::place(&mut self.locomotive, head, target);
+        self.trim_trail(head);
+
+        for (index, wagon) in (1u8..).zip(self.wagons.iter_mut()) {
+            let (position, ahead) =
+                trail_point(head, &self.trail, f32::from(index) * self.car_spacing);
+
+            Self::place(wagon, position, ahead);
+        }
+    }
+
+    /// Picks a random node of the rail network and plans the route to it.
+    fn choose_target(&mut self, current: TileCoords) {
+        let rail_network = self.rail_network.bind();
+        let rail_navigation = rail_network.rail_navigation();
+
+        let Some(target) = rail_navigation.get_random_node() else {
+            return;
+        };
+
+        if target == current {
+            return;
+        }
+
+        let Some(route) = rail_navigation.plan_route(current, self.heading, target) else {
+            logger::debug!("train target is unreachable");
+            return;
+        };
+
+        // the route starts with the current node.
+        self.route = route.into_iter().skip(1).collect();
+    }
+
+    /// Only keeps as much of the trail as the consist covers.
+    fn trim_trail(&mut self, head: Vector3) {
+        let consist_length = f32::from(self.wagon_count) * self.car_spacing;
+        let mut length = 0.0;
+        let mut previous = head;
+        let mut keep = 0;
+
+        for point in &self.trail {
+            keep += 1;
+
+            if length >= consist_length {
+                break;
+            }
+
+            length += previous.distance_to(*point);
+            previous = *point;
+        }
+
+        self.trail.truncate(keep);
+    }
+
+    fn place(node: &mut Gd<Node3D>, position: Vector3, ahead: Vector3) {
+        if position.is_equal_approx(ahead) {
+            node.set_global_position(position);
+            return;
+        }
+
+        node.look_at_from_position(position, ahead);
+    }
+}
+
+/// Point of the trail at the given distance behind the head, together with the point that lies
+/// ahead of it.
+fn trail_point(head: Vector3, trail: &VecDeque<Vector3>, distance: f32) -> (Vector3, Vector3) {
+    let mut ahead = head;
+    let mut previous = head;
+    let mut remaining = distance;
+
+    for point in trail {
+        let segment = ahead.distance_to(*point);
+
+        if segment > 0.0 && segment >= remaining {
+            return (ahead.lerp(*point, remaining / segment), ahead);
+        }
+
+        remaining -= segment;
+        previous = ahead;
+        ahead = *point;
+    }
+
+    (ahead, previous)
+}
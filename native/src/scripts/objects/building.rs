@@ -6,6 +6,8 @@
  */
 
 mod fire;
+mod mission;
+mod power;
 
 use std::{any::Any, fmt::Debug};
 
@@ -14,12 +16,18 @@ use godot::classes::Texture2D;
 use godot::classes::{MeshInstance3D, Node};
 use godot::obj::{Gd, Inherits};
 use godot_rust_script::godot_script_impl;
-use godot_rust_script::{GodotScript, OnEditor, ScriptExportGroup, ScriptExportSubgroup};
+use godot_rust_script::{GodotScript, OnEditor, RsRef, ScriptExportGroup, ScriptExportSubgroup};
 
+use crate::resources::MissionBoard;
+use crate::scripts::world::solar_setup::SolarSetup;
 use crate::util::Uf32;
+use crate::world::fire_spread::FireSimulation;
+use crate::world::power_grid::PowerGrid;
 use crate::{util::logger, world::city_data::TileCoords};
 
 use fire::FireFeature;
+use mission::MissionFeature;
+use power::PowerFeature;
 
 trait BuildingFeature<N: Inherits<Node>>: Debug {
     fn process(&mut self, _delta: f64, _node: &mut Gd<N>) {}
@@ -64,7 +72,11 @@ impl<N: Inherits<Node>> BuildingFeature<N> for Features<dyn BuildingFeature<N>>
 
 #[derive(Clone, Copy)]
 enum BuildingNotification {
+    /// Water from the player's helicopter.
     WaterImpact(f64),
+    /// Water from a fire engine. It fights the fire but does not count for the player.
+    HoseImpact(f64),
+    TeargasImpact(f64),
 }
 
 #[derive(ScriptExportGroup, Debug, Default)]
@@ -95,6 +107,14 @@ struct Building {
 
     pub tile_coords_array: Array<u32>,
 
+    pub fire_simulation: Option<Gd<FireSimulation>>,
+
+    pub mission_board: Option<Gd<MissionBoard>>,
+
+    pub power_grid: Option<Gd<PowerGrid>>,
+
+    pub solar_setup: Option<RsRef<SolarSetup>>,
+
     tile_coords: TileCoords,
 
     features: Features<dyn BuildingFeature<Node>>,
@@ -112,12 +132,36 @@ impl Building {
 
         if let Some(config) = &self.events.fire {
             if let Some(ref mesh) = self.mesh {
-                self.features
-                    .push(Box::new(FireFeature::new(self.tile_coords, mesh, config)));
+                self.features.push(Box::new(FireFeature::new(
+                    self.tile_coords,
+                    mesh,
+                    config,
+                    self.fire_simulation.clone(),
+                    self.mission_board.clone(),
+                    self.power_grid.clone(),
+                )));
             } else {
                 logger::warn!("Unable to instantiate FireFeature because no mesh has been set.");
             }
         }
+
+        if let Some(mission_board) = self.mission_board.clone() {
+            self.features.push(Box::new(MissionFeature::new(
+                self.tile_coords,
+                mission_board,
+            )));
+        }
+
+        if let (Some(power_grid), Some(solar_setup)) =
+            (self.power_grid.clone(), self.solar_setup.clone())
+        {
+            self.features.push(Box::new(PowerFeature::new(
+                self.tile_coords,
+                power_grid,
+                solar_setup,
+                self.mesh.clone(),
+            )));
+        }
     }
 
     pub fn _process(&mut self, delta: f64) {
@@ -134,6 +178,18 @@ impl Building {
         self.dispatch_notification(notification);
     }
 
+    pub fn impact_hose(&mut self, delta: f64) {
+        let notification = BuildingNotification::HoseImpact(delta);
+
+        self.dispatch_notification(notification);
+    }
+
+    pub fn impact_teargas(&mut self, delta: f64) {
+        let notification = BuildingNotification::TeargasImpact(delta);
+
+        self.dispatch_notification(notification);
+    }
+
     fn dispatch_notification(&mut self, notification: BuildingNotification) {
         self.features.dispatch_notification(notification);
     }
@@ -13,9 +13,13 @@ use godot_rust_script::{CastToScript, RsRef};
 use num::ToPrimitive;
 use rand::Rng;
 
+use crate::resources::MissionBoard;
 use crate::scripts::{FireSpawner, IFireSpawner};
 use crate::util::{logger, Uf32};
 use crate::world::city_data::TileCoords;
+use crate::world::fire_spread::FireSimulation;
+use crate::world::missions::MissionEvent;
+use crate::world::power_grid::PowerGrid;
 
 use super::{BuildingFeature, BuildingNotification};
 
@@ -31,17 +35,33 @@ pub(super) struct FireFeature {
     emission_points: Gd<Texture2D>,
     emission_point_normals: Gd<Texture2D>,
     emission_point_count: Uf32,
+    fire_simulation: Option<Gd<FireSimulation>>,
+    mission_board: Option<Gd<MissionBoard>>,
+    power_grid: Option<Gd<PowerGrid>>,
+    reported_extinguished: bool,
+    /// The player has put water on the current fire. Fires that are only fought by fire engines
+    /// do not count for the missions.
+    player_water: bool,
+    /// Seconds the current fire has been burning.
+    burn_time: f64,
+    /// Buildings can not be rebuilt yet, once burned down they stay burned down.
+    burned_down: bool,
 }
 
 impl FireFeature {
     const FIRE_SPAWNER_SCENE: &'static str = "res://resources/Objects/Spawner/fire_spawner.tscn";
     const RECOVERY_RATE: f32 = 0.01;
     const WATER_IMPACT_RATE: f32 = 0.2;
+    /// Seconds after which a fire that has not been extinguished destroys the building.
+    const BURN_DOWN_TIME: f64 = 180.0;
 
     pub fn new(
         tile_coords: TileCoords,
         mesh: &Gd<MeshInstance3D>,
         config: &super::FireEventConfig,
+        fire_simulation: Option<Gd<FireSimulation>>,
+        mission_board: Option<Gd<MissionBoard>>,
+        power_grid: Option<Gd<PowerGrid>>,
     ) -> Self {
         let packed = load(Self::FIRE_SPAWNER_SCENE);
 
@@ -56,6 +76,13 @@ impl FireFeature {
             emission_point_normals: config.emission_point_normals.clone(),
             emission_point_count: config.emission_point_count,
             tile_coords,
+            fire_simulation,
+            mission_board,
+            power_grid,
+            reported_extinguished: false,
+            player_water: false,
+            burn_time: 0.0,
+            burned_down: false,
         }
     }
 
@@ -85,6 +112,87 @@ impl FireFeature {
             + Self::RECOVERY_RATE * delta.to_f32().expect("delta can be truncated"))
         .min(1.0);
     }
+
+    /// Shares the current fire strength with the fire simulation, so the fire can spread.
+    fn report_fire_strength(&mut self) {
+        let Some(simulation) = self.fire_simulation.as_mut() else {
+            return;
+        };
+
+        simulation
+            .bind_mut()
+            .set_fire_strength(self.tile_coords, self.fire_strength);
+    }
+
+    /// Checks if the fire of a neighbouring building has spread to this building. Returns the
+    /// strength of the new fire.
+    fn take_spread_ignition(&mut self) -> Option<f32> {
+        let simulation = self.fire_simulation.as_mut()?;
+
+        simulation.bind_mut().take_ignition(self.tile_coords)
+    }
+
+    /// Calls the fire department to the burning building.
+    fn report_alarm(&mut self, node: Gd<Node>) {
+        let Some(simulation) = self.fire_simulation.as_mut() else {
+            return;
+        };
+
+        simulation.bind_mut().report_alarm(self.tile_coords, node);
+    }
+
+    /// Checks if the active mission wants this building to burn.
+    fn take_mission_ignition(&mut self) -> bool {
+        let Some(mission_board) = self.mission_board.as_mut() else {
+            return false;
+        };
+
+        mission_board.bind_mut().take_ignition(self.tile_coords)
+    }
+
+    fn report_extinguished(&mut self) {
+        self.reported_extinguished = true;
+
+        if !self.player_water {
+            return;
+        }
+
+        let Some(mission_board) = self.mission_board.as_mut() else {
+            return;
+        };
+
+        mission_board
+            .bind_mut()
+            .report(MissionEvent::FireExtinguished(self.tile_coords));
+    }
+
+    fn extinguish(&mut self, delta: f64) {
+        self.fire_strength = (self.fire_strength
+            - Self::WATER_IMPACT_RATE * delta.to_f32().expect("delta can be truncated"))
+        .max(0.0);
+    }
+
+    /// Keeps track of how long the fire has been burning and reports the building to the power
+    /// grid once it burned down.
+    fn burn(&mut self, delta: f64) {
+        if self.fire_scene.is_none() || self.is_dead() {
+            self.burn_time = 0.0;
+            return;
+        }
+
+        self.burn_time += delta;
+
+        if self.burned_down || self.burn_time < Self::BURN_DOWN_TIME {
+            return;
+        }
+
+        self.burned_down = true;
+        logger::info!("Building burned down: {:?}", self.tile_coords);
+
+        if let Some(power_grid) = self.power_grid.as_mut() {
+            power_grid.bind_mut().burn_down(self.tile_coords);
+        }
+    }
 }
 
 impl<N: Inherits<Node>> BuildingFeature<N> for FireFeature {
@@ -93,6 +201,11 @@ impl<N: Inherits<Node>> BuildingFeature<N> for FireFeature {
 
         if let Some(mut scene) = self.fire_scene.clone() {
             self.update_fire_strength(&mut scene);
+            self.report_fire_strength();
+
+            if scene.is_dead() && !self.reported_extinguished {
+                self.report_extinguished();
+            }
 
             if !self.is_dead() {
                 self.last_fire = current_ticks;
@@ -111,21 +224,28 @@ impl<N: Inherits<Node>> BuildingFeature<N> for FireFeature {
             return;
         }
 
-        let tick_delta = current_ticks - self.last_fire;
-        let tick_damp = (tick_delta
-            .to_f64()
-            .expect("tick delta is expected to fit in f64")
-            / 10_000.0)
-            .min(1.0);
-        let rng = rand::rng().sample::<f64, _>(rand::distr::OpenClosed01);
+        if let Some(strength) = self.take_spread_ignition() {
+            logger::debug!("Fire spread to building: {:?}", self.tile_coords);
+            self.fire_strength = strength;
+        } else if self.take_mission_ignition() {
+            logger::debug!("Mission set building on fire: {:?}", self.tile_coords);
+        } else {
+            let tick_delta = current_ticks - self.last_fire;
+            let tick_damp = (tick_delta
+                .to_f64()
+                .expect("tick delta is expected to fit in f64")
+                / 10_000.0)
+                .min(1.0);
+            let rng = rand::rng().sample::<f64, _>(rand::distr::OpenClosed01);
 
-        let chance = rng * tick_damp;
+            let chance = rng * tick_damp;
 
-        if chance < 0.9 {
-            return;
-        }
+            if chance < 0.9 {
+                return;
+            }
 
-        logger::debug!("Building will burn! (tick_delta: {tick_delta}, tick_boost: {tick_damp}, rng: {rng}, chance: {chance})");
+            logger::debug!("Building will burn! (tick_delta: {tick_delta}, tick_boost: {tick_damp}, rng: {rng}, chance: {chance})");
+        }
 
         let Some(scene_instance) = self.packed_fire_scene.try_instantiate_as::<Node3D>() else {
             logger::error!("Failed to instantiate fire_spawner scene as decendant of Node3D");
@@ -150,21 +270,26 @@ impl<N: Inherits<Node>> BuildingFeature<N> for FireFeature {
             .done();
 
         self.fire_scene = Some(scene_instance.to_script());
+        self.reported_extinguished = false;
+        self.player_water = false;
+        self.report_alarm(node.clone().upcast());
 
         logger::info!("Building started burning: {:?}", self.tile_coords);
     }
 
     fn physics_process(&mut self, delta: f64, _node: &mut Gd<N>) {
         self.recover_fire_strength(delta);
+        self.burn(delta);
     }
 
     fn dispatch_notification(&mut self, notification: BuildingNotification) {
         match notification {
             BuildingNotification::WaterImpact(delta) => {
-                self.fire_strength = (self.fire_strength
-                    - Self::WATER_IMPACT_RATE * delta.to_f32().expect("delta can be truncated"))
-                .max(0.0);
+                self.player_water = true;
+                self.extinguish(delta);
             }
+            BuildingNotification::HoseImpact(delta) => self.extinguish(delta),
+            BuildingNotification::TeargasImpact(_) => (),
         }
     }
 }
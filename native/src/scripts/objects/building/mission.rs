@@ -0,0 +1,49 @@
+/*
+ * Copyright (c) SimChopper; Jovan Gerodetti and contributors.
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use godot::classes::Node;
+use godot::obj::{Gd, Inherits};
+
+use crate::resources::MissionBoard;
+use crate::world::city_data::TileCoords;
+use crate::world::missions::MissionEvent;
+
+use super::{BuildingFeature, BuildingNotification};
+
+/// Forwards building notifications to the mission board.
+#[derive(Debug)]
+pub(super) struct MissionFeature {
+    tile_coords: TileCoords,
+    mission_board: Gd<MissionBoard>,
+}
+
+impl MissionFeature {
+    pub fn new(tile_coords: TileCoords, mission_board: Gd<MissionBoard>) -> Self {
+        Self {
+            tile_coords,
+            mission_board,
+        }
+    }
+}
+
+impl<N: Inherits<Node>> BuildingFeature<N> for MissionFeature {
+    fn dispatch_notification(&mut self, notification: BuildingNotification) {
+        match notification {
+            BuildingNotification::WaterImpact(delta) => {
+                self.mission_board
+                    .bind_mut()
+                    .report(MissionEvent::WaterImpact(self.tile_coords, delta));
+            }
+            BuildingNotification::TeargasImpact(delta) => {
+                self.mission_board
+                    .bind_mut()
+                    .report(MissionEvent::TeargasImpact(self.tile_coords, delta));
+            }
+            BuildingNotification::HoseImpact(_) => (),
+        }
+    }
+}
@@ -0,0 +1,136 @@
+/*
+ * Copyright (c) SimChopper; Jovan Gerodetti and contributors.
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use godot::classes::base_material_3d::Feature;
+use godot::classes::{BaseMaterial3D, Light3D, Material, MeshInstance3D, Node};
+use godot::obj::{Gd, Inherits};
+use godot_rust_script::RsRef;
+
+use crate::scripts::world::solar_setup::{ISolarSetup, SolarSetup};
+use crate::world::city_data::TileCoords;
+use crate::world::power_grid::PowerGrid;
+
+use super::BuildingFeature;
+
+/// Switches off the lights and emissive materials of a building at night, while it is cut off
+/// from the power grid.
+#[derive(Debug)]
+pub(super) struct PowerFeature {
+    tile_coords: TileCoords,
+    power_grid: Gd<PowerGrid>,
+    solar_setup: RsRef<SolarSetup>,
+    mesh: Option<Gd<MeshInstance3D>>,
+    /// The surface override materials of the mesh while the building is lit.
+    lit_materials: Vec<Option<Gd<Material>>>,
+    is_dark: bool,
+    update_delay: f64,
+}
+
+impl PowerFeature {
+    /// Seconds between two checks of the power supply.
+    const UPDATE_INTERVAL: f64 = 1.0;
+
+    /// Below this environment brightness in lux, buildings need their own lights.
+    const NIGHT_LUX: f32 = 2560.0;
+
+    pub fn new(
+        tile_coords: TileCoords,
+        power_grid: Gd<PowerGrid>,
+        solar_setup: RsRef<SolarSetup>,
+        mesh: Option<Gd<MeshInstance3D>>,
+    ) -> Self {
+        Self {
+            tile_coords,
+            power_grid,
+            solar_setup,
+            mesh,
+            lit_materials: Vec::new(),
+            is_dark: false,
+            update_delay: 0.0,
+        }
+    }
+
+    fn should_be_dark(&self) -> bool {
+        !self.power_grid.bind().is_powered(self.tile_coords)
+            && self.solar_setup.environment_brightness() < Self::NIGHT_LUX
+    }
+
+    fn switch_lights(&mut self, node: &Node, dark: bool) {
+        let lights = node
+            .find_children_ex("*")
+            .type_("Light3D")
+            .owned(false)
+            .done();
+
+        for light in lights.iter_shared() {
+            let Ok(mut light) = light.try_cast::<Light3D>() else {
+                continue;
+            };
+
+            light.set_visible(!dark);
+        }
+
+        if let Some(mesh) = self.mesh.as_mut() {
+            if dark {
+                self.lit_materials = Self::darken_materials(mesh);
+            } else {
+                for (surface, material) in self.lit_materials.drain(..).enumerate() {
+                    let surface = i32::try_from(surface).expect("surface count fits into i32");
+
+                    mesh.set_surface_override_material(surface, material.as_ref());
+                }
+            }
+        }
+
+        self.is_dark = dark;
+    }
+
+    /// Replaces all emissive materials of the mesh with copies that do not emit any light.
+    /// Returns the previous override materials.
+    fn darken_materials(mesh: &mut Gd<MeshInstance3D>) -> Vec<Option<Gd<Material>>> {
+        (0..mesh.get_surface_override_material_count())
+            .map(|surface| {
+                let lit_material = mesh.get_surface_override_material(surface);
+
+                let dark_material = mesh
+                    .get_active_material(surface)
+                    .and_then(|material| material.try_cast::<BaseMaterial3D>().ok())
+                    .filter(|material| material.get_feature(Feature::EMISSION))
+                    .map(|material| {
+                        let mut material: Gd<BaseMaterial3D> = material.duplicate_resource();
+
+                        material.set_feature(Feature::EMISSION, false);
+                        material.upcast::<Material>()
+                    });
+
+                if let Some(dark_material) = dark_material {
+                    mesh.set_surface_override_material(surface, &dark_material);
+                }
+
+                lit_material
+            })
+            .collect()
+    }
+}
+
+impl<N: Inherits<Node>> BuildingFeature<N> for PowerFeature {
+    fn process(&mut self, delta: f64, node: &mut Gd<N>) {
+        self.update_delay += delta;
+
+        if self.update_delay < Self::UPDATE_INTERVAL {
+            return;
+        }
+
+        self.update_delay = 0.0;
+
+        let dark = self.should_be_dark();
+
+        if dark != self.is_dark {
+            self.switch_lights(node.upcast_ref(), dark);
+        }
+    }
+}
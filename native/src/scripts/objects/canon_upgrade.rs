@@ -9,6 +9,8 @@ use godot::classes::{GpuParticles3D, Node3D};
 use godot::obj::Gd;
 use godot_rust_script::{godot_script_impl, GodotScript, GodotScriptEnum, OnEditor};
 
+use crate::resources::WaterTank;
+
 #[derive(Debug, Default, GodotScriptEnum, Clone, Copy)]
 #[script_enum(export)]
 pub enum CanonMode {
@@ -28,15 +30,46 @@ struct CanonUpgrade {
     #[export]
     pub water_jet: OnEditor<Gd<GpuParticles3D>>,
 
+    #[export]
+    pub teargas_jet: OnEditor<Gd<GpuParticles3D>>,
+
+    /// The water canon can only fire while there is water in the tank.
+    #[export]
+    pub water_tank: OnEditor<Gd<WaterTank>>,
+
+    /// The mode the canon fires in, switched with the secondary action.
+    selected: CanonMode,
+
     base: Gd<Node3D>,
 }
 
 #[godot_script_impl]
 impl CanonUpgrade {
+    pub fn _init(&mut self) {
+        self.selected = CanonMode::Water;
+    }
+
     pub fn _ready(&mut self) {
         self.set_mode(self.mode);
     }
 
+    pub fn _physics_process(&mut self, delta: f64) {
+        #[expect(clippy::cast_possible_truncation)]
+        let delta = delta as f32;
+        let location = self.base.get_global_position();
+        let mut water_tank = self.water_tank.bind_mut();
+
+        water_tank.refill(location, delta);
+
+        let out_of_water = matches!(self.mode, CanonMode::Water) && !water_tank.drain(delta);
+
+        drop(water_tank);
+
+        if out_of_water {
+            self.set_mode(CanonMode::Inactive);
+        }
+    }
+
     pub fn set_mode(&mut self, value: CanonMode) {
         self.mode = value;
 
@@ -45,24 +78,46 @@ impl CanonUpgrade {
         }
 
         self.water_jet.set_emitting(false);
+        self.teargas_jet.set_emitting(false);
 
         match value {
             CanonMode::Water => self.water_jet.set_emitting(true),
-            CanonMode::Teargas | CanonMode::Inactive => (),
+            CanonMode::Teargas => self.teargas_jet.set_emitting(true),
+            CanonMode::Inactive => (),
         }
     }
 
+    /// Fires the canon in the selected mode.
+    fn fire(&mut self) {
+        if matches!(self.selected, CanonMode::Water) && self.water_tank.bind().is_empty() {
+            self.set_mode(CanonMode::Inactive);
+            return;
+        }
+
+        self.set_mode(self.selected);
+    }
+
     pub fn action(&mut self, pressed: bool) {
         match (pressed, self.mode) {
-            (true, CanonMode::Inactive) => {
-                self.set_mode(CanonMode::Water);
-            }
+            (true, CanonMode::Inactive) => self.fire(),
+            (false, CanonMode::Water | CanonMode::Teargas) => self.set_mode(CanonMode::Inactive),
+            _ => (),
+        }
+    }
+
+    /// Switches between water and teargas. A firing canon continues in the new mode.
+    pub fn switch_mode(&mut self, pressed: bool) {
+        if !pressed {
+            return;
+        }
 
-            (false, CanonMode::Water) => {
-                self.set_mode(CanonMode::Inactive);
-            }
+        self.selected = match self.selected {
+            CanonMode::Teargas => CanonMode::Water,
+            CanonMode::Water | CanonMode::Inactive => CanonMode::Teargas,
+        };
 
-            _ => (),
+        if !matches!(self.mode, CanonMode::Inactive) {
+            self.fire();
         }
     }
 }
@@ -0,0 +1,132 @@
+/*
+ * Copyright (c) SimChopper; Jovan Gerodetti and contributors.
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::collections::BTreeSet;
+
+use anyhow::{bail, Result};
+use godot::builtin::{Array, Basis, NodePath, Transform3D, Vector3};
+use godot::classes::{
+    GpuParticles3D, Node3D, PhysicsShapeQueryParameters3D, ShapeCast3D, SphereShape3D,
+};
+use godot::meta::ToGodot;
+use godot::obj::{Gd, NewGd};
+use godot_rust_script::{godot_script_impl, GodotScript};
+
+use crate::util::logger;
+
+/// Teargas that is fired by the canon. Unlike water, the gas spreads around the point of impact
+/// and affects every agent and building inside its cloud.
+#[derive(GodotScript, Debug)]
+#[script(base = GpuParticles3D)]
+struct TeargasJet {
+    /// List of [`ShapeCast3D`] nodes to approximate the impact of the gas.
+    #[export(node_path = ["ShapeCast3D"])]
+    pub impact_cast_paths: Array<NodePath>,
+
+    /// Radius of the gas cloud around the point of impact in meters.
+    #[export(range(min = 1.0, max = 50.0, step = 0.5))]
+    pub cloud_radius: f32,
+
+    impact_casts: Vec<Gd<ShapeCast3D>>,
+
+    base: Gd<GpuParticles3D>,
+}
+
+#[godot_script_impl]
+impl TeargasJet {
+    /// Maximum number of bodies that are affected by the gas at the same time.
+    const MAX_TARGETS: i32 = 32;
+
+    pub fn _init(&mut self) {
+        self.cloud_radius = 12.0;
+    }
+
+    pub fn _ready(&mut self) {
+        self.impact_casts = self
+            .impact_cast_paths
+            .iter_shared()
+            .map(|path| self.base.get_node_as(&path))
+            .collect();
+    }
+
+    pub fn _physics_process(&mut self, delta: f64) {
+        if !self.base.is_emitting() {
+            return;
+        }
+
+        let impact = self.impact_casts.iter_mut().find_map(|shape_cast| {
+            shape_cast.force_shapecast_update();
+
+            (shape_cast.get_collision_count() > 0).then(|| shape_cast.get_collision_point(0))
+        });
+
+        let Some(impact) = impact else {
+            return;
+        };
+
+        let targets = match self.targets_in_cloud(impact) {
+            Ok(targets) => targets,
+            Err(err) => {
+                logger::error!(
+                    "{:?}",
+                    err.context("Failed to find targets of the teargas!")
+                );
+                return;
+            }
+        };
+
+        for mut target in targets {
+            if !target.has_method("impact_teargas") {
+                continue;
+            }
+
+            // the gas is less dense at the edge of the cloud.
+            let distance = target.get_global_position().distance_to(impact);
+            let density = (1.0 - distance / self.cloud_radius).clamp(0.0, 1.0);
+
+            target.call(
+                "impact_teargas",
+                &[(delta * f64::from(density)).to_variant()],
+            );
+        }
+    }
+
+    /// All bodies inside the gas cloud around the point of impact.
+    fn targets_in_cloud(&self, impact: Vector3) -> Result<Vec<Gd<Node3D>>> {
+        let Some(physics_world) = self.base.get_world_3d() else {
+            bail!("Failed to acquire physics world!");
+        };
+
+        let Some(mut space) = physics_world.get_direct_space_state() else {
+            bail!("Failed to acquire space!");
+        };
+
+        let mut cloud = SphereShape3D::new_gd();
+        cloud.set_radius(self.cloud_radius);
+
+        let mut query = PhysicsShapeQueryParameters3D::new_gd();
+        query.set_shape(&cloud);
+        query.set_transform(Transform3D::new(Basis::IDENTITY, impact));
+
+        let results = space
+            .intersect_shape_ex(&query)
+            .max_results(Self::MAX_TARGETS)
+            .done();
+
+        let mut seen = BTreeSet::new();
+
+        // bodies with several shapes are reported once per shape.
+        let targets = results
+            .iter_shared()
+            .filter_map(|result| result.get("collider"))
+            .filter_map(|collider| collider.try_to::<Gd<Node3D>>().ok())
+            .filter(|target| seen.insert(target.instance_id()))
+            .collect();
+
+        Ok(targets)
+    }
+}
@@ -22,6 +22,7 @@ use crate::ext::node_3d::Node3DExt;
 use crate::resources::WaterDecalTracker;
 use crate::scripts::objects::debugger_3_d::Debugger3D;
 use crate::util::logger;
+use crate::world::drying::Surface;
 use crate::{debug_3d, util};
 
 #[derive(GodotScript, Debug)]
@@ -65,6 +66,8 @@ struct Intersection {
 #[godot_script_impl]
 impl WaterJet {
     const MAX_DISTANCE: f32 = 60.0;
+    const ROAD_NETWORK_GROUP: &str = "road-network";
+    const RAIL_NETWORK_GROUP: &str = "rail-network";
 
     pub fn _ready(&mut self) {
         self.impact_casts = self
@@ -96,9 +99,7 @@ impl WaterJet {
         self.decal_tracker
             .bind()
             .get_decals_at_point(aabb)
-            .iter()
             .take(max as usize)
-            .map(|decal| decal.get_global_position())
             .collect()
     }
 
@@ -157,6 +158,8 @@ impl WaterJet {
         }
 
         let decal = self.decal().to_owned();
+        #[expect(clippy::cast_possible_truncation)]
+        let soak_time = delta as f32;
 
         #[cfg(debug_assertions)]
         let mut shape_cast_name = "N/A".to_owned();
@@ -215,12 +218,20 @@ impl WaterJet {
                     target_decal_count,
                 );
 
-                let mut decal_inst = self
-                    .can_spawn_decal(&point, extent, &decals_at_point)
-                    .then(|| self.spawn_decal(&decal, &point, decal_scale, &mut target_node));
+                // water that hits a wet patch again soaks the patch instead of adding a decal.
+                let soaked = self
+                    .decal_tracker
+                    .bind_mut()
+                    .soak(point.position, extent, soak_time);
+
+                let mut decal_inst = (!soaked
+                    && self.can_spawn_decal(&point, extent, &decals_at_point))
+                .then(|| self.spawn_decal(&decal, &point, decal_scale, &mut target_node));
 
                 if let Some(inst) = &decal_inst {
-                    self.decal_tracker.bind_mut().insert(inst);
+                    self.decal_tracker
+                        .bind_mut()
+                        .insert(inst, Self::surface(&target_node));
                 }
 
                 let impact_delay = Self::distance_scale(impact_distance, 0.0, self.max_delay);
@@ -253,6 +264,29 @@ impl WaterJet {
         debug_3d!(self.debugger => shape_cast_name, (ref impacting), decal_spawned);
     }
 
+    /// The kind of surface the node is part of.
+    fn surface(target: &Gd<Node3D>) -> Surface {
+        let mut node = Some(target.clone().upcast::<Node>());
+
+        while let Some(current) = node {
+            let is_network = current.is_in_group(Self::ROAD_NETWORK_GROUP)
+                || current.is_in_group(Self::RAIL_NETWORK_GROUP);
+
+            if is_network {
+                return Surface::Asphalt;
+            }
+
+            // only buildings can be hit by water.
+            if current.has_method("impact_water") {
+                return Surface::Roof;
+            }
+
+            node = current.get_parent();
+        }
+
+        Surface::Grass
+    }
+
     fn distance_scale(distance: f32, min: f32, max: f32) -> f32 {
         (max / Self::MAX_DISTANCE * distance).clamp(min, max)
     }
@@ -5,9 +5,10 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/.
  */
 
+use godot::builtin::{Array, Transform3D};
 use godot::meta::ToGodot;
 use godot::{obj::NewAlloc, tools::load};
-use godot_rust_script::godot::classes::Timer;
+use godot_rust_script::godot::classes::{Node, Timer};
 use godot_rust_script::{
     godot::prelude::{godot_error, Gd, Node3D, NodePath, PackedScene},
     godot_script_impl, GodotScript,
@@ -17,7 +18,7 @@ use crate::script_callable;
 
 #[derive(Debug, GodotScript)]
 #[script(base = Node3D)]
-struct CarSpawner {
+pub struct CarSpawner {
     default_car: Option<Gd<PackedScene>>,
 
     #[export]
@@ -40,6 +41,24 @@ impl CarSpawner {
             return;
         }
 
+        let Some(mut inst) = self.instantiate_car() else {
+            return;
+        };
+
+        inst.call("activate", &[]);
+    }
+
+    /// Spawns a car of a restored session at its previous location.
+    pub fn spawn_car_at(&mut self, transform: Transform3D, target_node: Array<u32>) {
+        let Some(mut inst) = self.instantiate_car() else {
+            return;
+        };
+
+        inst.set("global_transform", &transform.to_variant());
+        inst.call("resume", &[target_node.to_variant()]);
+    }
+
+    fn instantiate_car(&mut self) -> Option<Gd<Node>> {
         let inst = self
             .default_car
             .as_ref()
@@ -48,7 +67,7 @@ impl CarSpawner {
 
         let Some(mut inst) = inst else {
             godot_error!("failed to instantiate car scene!");
-            return;
+            return None;
         };
 
         inst.set("road_network_path", &self.road_network_path.to_variant());
@@ -60,11 +79,11 @@ impl CarSpawner {
 
         let Some(current_scene) = self.base.get_tree().get_current_scene() else {
             godot_error!("there is no active scene!");
-            return;
+            return None;
         };
 
         inst.set_owner(&current_scene);
-        inst.call("activate", &[]);
+        Some(inst)
     }
 
     pub fn start_auto_spawn(&mut self) {
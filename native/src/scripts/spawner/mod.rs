@@ -8,4 +8,5 @@
 mod car_spawner;
 mod fire_spawner;
 
+pub use car_spawner::*;
 pub use fire_spawner::*;
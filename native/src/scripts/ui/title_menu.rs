@@ -4,15 +4,16 @@ use godot::classes::input::MouseMode;
 use godot::classes::resource_loader::ThreadLoadStatus;
 use godot::classes::{
     window, Animation, AnimationPlayer, BaseButton, Button, Control, DisplayServer, Engine,
-    InputEvent, Node3D, PackedScene, ResourceLoader,
+    InputEvent, Node3D, OptionButton, PackedScene, ResourceLoader,
 };
 use godot::global;
 use godot::meta::conv::ObjectToOwned;
+use godot::meta::ToGodot;
 use godot::obj::{EngineEnum, Gd, Singleton as _};
 use godot_rust_script::{godot_script_impl, Context, GodotScript, OnEditor, ScriptExportGroup};
 use num::ToPrimitive;
 
-use crate::resources::InputDevice;
+use crate::resources::{InputDevice, MapCatalog};
 use crate::script_callable;
 use crate::util::logger;
 
@@ -34,6 +35,13 @@ struct TitleMenu {
     #[export]
     pub quit_game: OnEditor<Gd<Button>>,
 
+    #[export]
+    pub map_picker: OnEditor<Gd<OptionButton>>,
+
+    /// The catalog of maps that can be picked.
+    #[export]
+    pub map_catalog: OnEditor<Gd<MapCatalog>>,
+
     #[export(file = ["*.tscn"])]
     pub main_scene: GString,
 
@@ -46,6 +54,9 @@ struct TitleMenu {
     #[export]
     pub input_device: OnEditor<Gd<InputDevice>>,
 
+    /// The picked map, it is handed to the world of the main scene.
+    selected_map: GString,
+
     ready: bool,
 
     base: Gd<Node3D>,
@@ -53,6 +64,9 @@ struct TitleMenu {
 
 #[godot_script_impl]
 impl TitleMenu {
+    /// Path of the world node inside the main scene.
+    const WORLD_NODE: &str = "SubViewportContainer/SubViewport/World";
+
     pub fn _ready(&mut self, mut context: Context<'_, Self>) {
         self.apply_ui_scale();
 
@@ -77,6 +91,15 @@ impl TitleMenu {
             .to_untyped()
             .connect(&script_callable!(self, Self::on_start_game));
 
+        self.map_picker
+            .signals()
+            .item_selected()
+            .to_untyped()
+            .connect(&script_callable!(self, Self::on_map_selected));
+
+        self.selected_map = self.map_catalog.bind().default_map();
+        self.populate_map_picker();
+
         self.ready = true;
         self.input_device
             .bind_mut()
@@ -98,6 +121,7 @@ impl TitleMenu {
         }
 
         let scene_path = self.main_scene.clone();
+        let selected_map = self.selected_map.clone();
         let mut tree = self.base.get_tree();
         let animation_player = self.scene_transitions.clone();
 
@@ -123,7 +147,21 @@ impl TitleMenu {
                             .unwrap()
                             .cast();
 
-                        tree.change_scene_to_packed(Some(&scene));
+                        let Some(scene_root) = scene.instantiate() else {
+                            logger::error!("Failed to instantiate scene: {}", scene_path);
+                            break;
+                        };
+
+                        match scene_root.get_node_or_null(Self::WORLD_NODE) {
+                            Some(mut world) => world.set("map_path", &selected_map.to_variant()),
+                            None => logger::error!(
+                                "Scene {} has no world at {}!",
+                                scene_path,
+                                Self::WORLD_NODE
+                            ),
+                        }
+
+                        tree.change_scene_to_node(&scene_root);
                         break;
                     }
 
@@ -143,6 +181,35 @@ impl TitleMenu {
         });
     }
 
+    pub fn on_map_selected(&mut self, index: i32) {
+        let Ok(path) = self.map_picker.get_item_metadata(index).try_to() else {
+            logger::error!("map picker entry {} has no map path!", index);
+            return;
+        };
+
+        self.selected_map = path;
+    }
+
+    fn populate_map_picker(&self) {
+        let maps = self.map_catalog.bind().load_maps();
+        let selected_map = self.selected_map.clone();
+        let mut map_picker = self.map_picker.clone();
+
+        godot::task::spawn(async move {
+            for map in maps.await {
+                let map = map.bind();
+                let index = map_picker.get_item_count();
+
+                map_picker.add_item(&map.name());
+                map_picker.set_item_metadata(index, &map.path().to_variant());
+
+                if map.path() == selected_map {
+                    map_picker.select(index);
+                }
+            }
+        });
+    }
+
     pub fn on_quit(&mut self) {
         let mut tree = self.base.get_tree();
 
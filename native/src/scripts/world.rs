@@ -5,6 +5,11 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/.
  */
 
-mod buildings;
+pub mod buildings;
+mod fire_department;
 mod gi_probes;
+mod missions;
+mod networks;
+mod session;
 pub mod solar_setup;
+mod wet_surfaces;
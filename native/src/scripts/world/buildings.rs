@@ -7,9 +7,8 @@
 
 use std::{collections::BTreeMap, ops::Not};
 
-use anyhow::Context as _;
 use derive_debug::Dbg;
-use godot::builtin::{Array, VarDictionary};
+use godot::builtin::Array;
 use godot::classes::{Marker3D, Node, Node3D, Time};
 use godot::meta::ToGodot;
 use godot::obj::{Gd, NewAlloc, Singleton as _};
@@ -20,21 +19,35 @@ use godot_rust_script::{
 };
 
 use crate::objects::scene_object_registry;
-use crate::resources::WorldConstants;
+use crate::resources::{MissionBoard, WorldConstants};
+use crate::scripts::world::solar_setup::SolarSetup;
 use crate::util::async_support::{self, GodotFuture};
 use crate::util::logger;
 use crate::world::city_coords_feature::CityCoordsFeature;
-use crate::world::city_data::{self, TileCoords, TryFromDictionary};
+use crate::world::city_data::{self, TileCoords};
+use crate::world::city_loader::CityHandle;
+use crate::world::fire_spread::FireSimulation;
+use crate::world::power_grid::PowerGrid;
 
 #[derive(GodotScript, Dbg)]
 #[script(base = Node)]
-struct Buildings {
+pub struct Buildings {
     #[dbg(skip)]
     pending_build_tasks: Vec<TaskHandle>,
 
+    fire_simulation: Option<Gd<FireSimulation>>,
+
     #[export]
     pub world_constants: OnEditor<Gd<WorldConstants>>,
 
+    /// Buildings report mission events to this board.
+    #[export]
+    pub mission_board: OnEditor<Gd<MissionBoard>>,
+
+    /// Unpowered buildings switch off their lights depending on the brightness of the scene.
+    #[export]
+    pub solar_setup: OnEditor<RsRef<SolarSetup>>,
+
     #[signal("coords", "size", "altitude")]
     pub spawn_point_encountered: ScriptSignal<(Array<u32>, u8, u32)>,
 
@@ -44,11 +57,28 @@ struct Buildings {
     base: Gd<Node>,
 }
 
+/// City wide simulations that are shared with every building.
+struct CitySystems {
+    fire_simulation: Gd<FireSimulation>,
+    mission_board: Gd<MissionBoard>,
+    power_grid: Gd<PowerGrid>,
+    solar_setup: RsRef<SolarSetup>,
+}
+
 #[godot_script_impl]
 impl Buildings {
     const TIME_BUDGET: u64 = 50;
 
-    pub fn _process(&mut self, _delta: f64) {
+    /// The fire simulation of the city, available once the city has been built.
+    pub fn fire_simulation(&self) -> Option<Gd<FireSimulation>> {
+        self.fire_simulation.clone()
+    }
+
+    pub fn _process(&mut self, delta: f64) {
+        if let Some(fire_simulation) = self.fire_simulation.as_mut() {
+            fire_simulation.bind_mut().advance(delta);
+        }
+
         self.pending_build_tasks
             .retain(godot::task::TaskHandle::is_pending);
 
@@ -66,9 +96,24 @@ impl Buildings {
         &self.world_constants
     }
 
-    pub fn build_async(&mut self, city: VarDictionary, mut ctx: Context<Self>) -> Gd<GodotFuture> {
+    #[expect(clippy::needless_pass_by_value)]
+    pub fn build_async(&mut self, city: Gd<CityHandle>, mut ctx: Context<Self>) -> Gd<GodotFuture> {
         let world_constants = self.world_constants().clone();
+        let city = city.bind().city().clone();
         let (resolve, godot_future) = async_support::godot_future();
+        let fire_seed = rand::random();
+        let fire_simulation = FireSimulation::new_gd(city.clone(), fire_seed);
+        let power_grid = PowerGrid::new_gd(&city);
+
+        logger::info!("fire simulation seed: {}", fire_seed);
+        self.fire_simulation = Some(fire_simulation.clone());
+
+        let systems = CitySystems {
+            fire_simulation,
+            mission_board: (*self.mission_board).clone(),
+            power_grid,
+            solar_setup: (*self.solar_setup).clone(),
+        };
 
         let handle = ctx.reentrant_scope(self, |mut base: Gd<Node>| {
             let mut script_self_ref: RsRef<Self> = base.to_script();
@@ -78,19 +123,9 @@ impl Buildings {
                 let next_tick = tree.signals().process_frame();
                 let time = Time::singleton();
 
-                let city = match crate::world::city_data::City::try_from_dict(&city)
-                    .context("Failed to deserialize city data")
-                {
-                    Ok(v) => v,
-                    Err(err) => {
-                        logger::error!("{:?}", err);
-                        return;
-                    }
-                };
-
                 let sea_level = city.simulator_settings.sea_level;
-                let buildings = city.buildings;
-                let tiles = city.tilelist;
+                let buildings = &city.buildings;
+                let tiles = &city.tilelist;
                 let city_coords_feature = CityCoordsFeature::new(world_constants, sea_level);
 
                 logger::info!("starting to load buildings...");
@@ -99,7 +134,7 @@ impl Buildings {
                 let mut start = time.get_ticks_msec();
                 let mut spawn_point: Option<[TileCoords; 4]> = None;
 
-                for building in buildings.into_values() {
+                for building in buildings.values() {
                     if (time.get_ticks_msec() - start) > Self::TIME_BUDGET {
                         script_self_ref.emit_progress(count);
                         count = 0;
@@ -117,7 +152,7 @@ impl Buildings {
 
                     // Check if tarmac tile is the spawn point.
                     if building.id == scene_object_registry::Buildings::Tarmac
-                        && is_spawn_point(&building, &tiles)
+                        && is_spawn_point(building, tiles)
                     {
                         let building_origin = (building.tile_coords.0, building.tile_coords.1 + 1);
 
@@ -144,8 +179,9 @@ impl Buildings {
                         Self::insert_building(
                             &mut base,
                             &spawn_building,
-                            &tiles,
+                            tiles,
                             &city_coords_feature,
+                            &systems,
                         );
 
                         CastToScript::<Buildings>::to_script(&base).emit_spawn_point_encountered(
@@ -159,7 +195,13 @@ impl Buildings {
                         continue;
                     }
 
-                    Self::insert_building(&mut base, &building, &tiles, &city_coords_feature);
+                    Self::insert_building(
+                        &mut base,
+                        building,
+                        tiles,
+                        &city_coords_feature,
+                        &systems,
+                    );
                 }
 
                 script_self_ref.emit_progress(count);
@@ -178,6 +220,7 @@ impl Buildings {
         building: &city_data::Building,
         tiles: &BTreeMap<(u32, u32), city_data::Tile>,
         city_coords_feature: &CityCoordsFeature,
+        systems: &CitySystems,
     ) {
         let building_size = building.size;
         let name = building.name.as_str();
@@ -210,6 +253,10 @@ impl Buildings {
             array.push(tile_coords.1);
 
             instance.set("tile_coords_array", &array.to_variant());
+            instance.set("fire_simulation", &systems.fire_simulation.to_variant());
+            instance.set("mission_board", &systems.mission_board.to_variant());
+            instance.set("power_grid", &systems.power_grid.to_variant());
+            instance.set("solar_setup", &systems.solar_setup.to_variant());
         }
 
         let mut location = city_coords_feature.get_building_coords(
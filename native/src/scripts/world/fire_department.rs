@@ -0,0 +1,198 @@
+/*
+ * Copyright (c) SimChopper; Jovan Gerodetti and contributors.
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use derive_debug::Dbg;
+use godot::builtin::array;
+use godot::classes::{Node, Node3D, PackedScene};
+use godot::meta::ToGodot;
+use godot::obj::Gd;
+use godot_rust_script::{godot_script_impl, GodotScript, OnEditor, RsRef};
+
+use crate::objects::scene_object_registry::Buildings as BuildingIds;
+use crate::road_navigation::RoadNavigationConfig;
+use crate::scripts::world::buildings::{Buildings, IBuildings};
+use crate::util::logger;
+use crate::world::city_data::{City, TileCoords};
+use crate::world::city_loader::CityHandle;
+use crate::world::fire_department;
+use crate::world::fire_spread::FireSimulation;
+
+/// Number of tiles between a building and the road from where it can still be reached.
+const MAX_HOSE_DISTANCE: u8 = 3;
+
+/// A fire engine that is on the road.
+#[derive(Debug)]
+struct DeployedEngine {
+    station: TileCoords,
+    building: TileCoords,
+    node: Gd<Node3D>,
+}
+
+/// Sends a fire engine from the nearest fire station to every building that starts burning. Every
+/// fire gets one engine and a station only has a limited number of engines.
+#[derive(GodotScript, Dbg)]
+#[script(base = Node3D)]
+struct FireDepartment {
+    /// Provides the fire simulation that raises the alarms.
+    #[export]
+    pub buildings: OnEditor<RsRef<Buildings>>,
+
+    #[export]
+    pub road_navigation: OnEditor<Gd<RoadNavigationConfig>>,
+
+    #[export]
+    pub fire_engine: OnEditor<Gd<PackedScene>>,
+
+    #[dbg(skip)]
+    city: Option<Arc<City>>,
+
+    fire_simulation: Option<Gd<FireSimulation>>,
+
+    /// Anchors of all fire stations of the city.
+    stations: Vec<TileCoords>,
+
+    engines: Vec<DeployedEngine>,
+
+    base: Gd<Node3D>,
+}
+
+#[godot_script_impl]
+impl FireDepartment {
+    #[expect(clippy::needless_pass_by_value)]
+    pub fn start(&mut self, city: Gd<CityHandle>) {
+        let city = city.bind().city().clone();
+
+        self.stations = city
+            .buildings
+            .values()
+            .filter(|building| building.id == BuildingIds::StationFire)
+            .map(|building| building.tile_coords)
+            .collect();
+
+        logger::info!("city has {} fire stations", self.stations.len());
+
+        self.fire_simulation = self.buildings.fire_simulation();
+        self.city = Some(city);
+    }
+
+    pub fn _process(&mut self, _delta: f64) {
+        let Some(mut fire_simulation) = self.fire_simulation.clone() else {
+            return;
+        };
+
+        let alarms = fire_simulation.bind_mut().take_alarms();
+
+        // engines free themselves once they are back at their station.
+        self.engines
+            .retain(|engine| engine.node.is_instance_valid());
+
+        for (building, node) in alarms {
+            self.dispatch(building, node, &fire_simulation);
+        }
+    }
+
+    /// Sends an engine from the nearest fire station to the burning building.
+    fn dispatch(
+        &mut self,
+        building: TileCoords,
+        node: Gd<Node>,
+        fire_simulation: &Gd<FireSimulation>,
+    ) {
+        if self
+            .engines
+            .iter()
+            .any(|engine| engine.building == building)
+        {
+            return;
+        }
+
+        let deployed = self
+            .engines
+            .iter()
+            .fold(BTreeMap::new(), |mut deployed, engine| {
+                *deployed.entry(engine.station).or_insert(0) += 1;
+                deployed
+            });
+
+        let Some(station) = fire_department::nearest_station(&self.stations, &deployed, building)
+        else {
+            logger::debug!("no fire engine available for the fire at {:?}", building);
+            return;
+        };
+
+        let (Some(departure), Some(scene)) = (
+            self.road_node_beside(station, building),
+            self.road_node_beside(building, station),
+        ) else {
+            logger::debug!(
+                "fire at {:?} can not be reached from the station at {:?}",
+                building,
+                station
+            );
+            return;
+        };
+
+        let Some(mut engine) = self.fire_engine.try_instantiate_as::<Node3D>() else {
+            logger::error!("failed to instantiate fire engine");
+            return;
+        };
+
+        self.base
+            .add_child_ex(&engine)
+            .force_readable_name(true)
+            .done();
+
+        logger::info!(
+            "dispatching fire engine from {:?} to {:?}",
+            station,
+            building
+        );
+
+        engine.call(
+            "dispatch",
+            &[
+                array![departure.0, departure.1].to_variant(),
+                array![scene.0, scene.1].to_variant(),
+                array![building.0, building.1].to_variant(),
+                node.to_variant(),
+                fire_simulation.to_variant(),
+            ],
+        );
+
+        self.engines.push(DeployedEngine {
+            station,
+            building,
+            node: engine,
+        });
+    }
+
+    /// The road node next to the building that is closest to `towards`. Buildings that are not
+    /// next to a road can still be reached from a road a few tiles away.
+    fn road_node_beside(&self, building: TileCoords, towards: TileCoords) -> Option<TileCoords> {
+        let city = self.city.as_ref()?;
+        let road_network = self.road_navigation.bind();
+        let road_navigation = road_network.road_navigation();
+
+        fire_department::access_tiles(&city.tilelist, building, towards)
+            .into_iter()
+            .find(|tile| road_navigation.try_node(*tile).is_some())
+            .or_else(|| {
+                road_navigation
+                    .get_nodes_around(building, MAX_HOSE_DISTANCE)
+                    .map(|node| node.tile_coords())
+                    .min_by_key(|tile| {
+                        (
+                            fire_department::distance(*tile, building),
+                            fire_department::distance(*tile, towards),
+                        )
+                    })
+            })
+    }
+}
@@ -0,0 +1,196 @@
+/*
+ * Copyright (c) SimChopper; Jovan Gerodetti and contributors.
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::sync::Arc;
+
+use derive_debug::Dbg;
+use godot::builtin::{Array, GString, Vector2};
+use godot::classes::{Node, Node3D};
+use godot::obj::Gd;
+use godot_rust_script::{godot_script_impl, GodotScript, OnEditor, RsRef, ScriptSignal};
+
+use crate::resources::{MissionBoard, MissionChain, MissionUpdate, Wallet, WorldConstants};
+use crate::scripts::world::solar_setup::{ISolarSetup, SolarSetup};
+use crate::util::logger;
+use crate::world::city_coords_feature::CityCoordsFeature;
+use crate::world::city_data::{City, TileCoords};
+use crate::world::city_loader::CityHandle;
+use crate::world::missions::{self, Mission, MissionEvent, Objective};
+
+#[derive(GodotScript, Dbg)]
+#[script(base = Node)]
+struct Missions {
+    /// The mission board that is shared with all buildings.
+    #[export]
+    pub mission_board: OnEditor<Gd<MissionBoard>>,
+
+    /// Provides the in-game time for mission time limits.
+    #[export]
+    pub solar_setup: OnEditor<RsRef<SolarSetup>>,
+
+    #[export]
+    pub world_constants: OnEditor<Gd<WorldConstants>>,
+
+    /// Rewards of completed missions and extinguished fires are paid into this wallet.
+    #[export]
+    pub wallet: OnEditor<Gd<Wallet>>,
+
+    /// Hand written mission chains. Maps without a chain get a randomly planned one.
+    #[export]
+    pub mission_chains: Array<Gd<MissionChain>>,
+
+    #[signal("title", "description")]
+    pub mission_started: ScriptSignal<(GString, GString)>,
+
+    #[signal("title", "reward")]
+    pub mission_completed: ScriptSignal<(GString, u32)>,
+
+    #[signal("title")]
+    pub mission_failed: ScriptSignal<GString>,
+
+    #[dbg(skip)]
+    city: Option<Arc<City>>,
+
+    city_coords_feature: Option<CityCoordsFeature>,
+
+    base: Gd<Node>,
+}
+
+#[godot_script_impl]
+impl Missions {
+    #[expect(clippy::needless_pass_by_value)]
+    pub fn start(&mut self, city: Gd<CityHandle>, map_path: GString) {
+        let city = city.bind().city().clone();
+        let chain = self.mission_chain(&city, &map_path);
+
+        self.mission_board.bind_mut().set_missions(chain);
+        self.city_coords_feature = Some(CityCoordsFeature::new(
+            (*self.world_constants).clone(),
+            city.simulator_settings.sea_level,
+        ));
+        self.city = Some(city);
+    }
+
+    pub fn _physics_process(&mut self, _delta: f64) {
+        if self.city.is_none() {
+            return;
+        }
+
+        if let Some(building) = self.reached_building() {
+            self.mission_board
+                .bind_mut()
+                .report(MissionEvent::BuildingReached(building));
+        }
+
+        let extinguished_fires = self.mission_board.bind_mut().take_extinguished_fires();
+
+        if extinguished_fires > 0 {
+            let reward = self.wallet.bind().fire_reward() * extinguished_fires;
+
+            self.wallet.bind_mut().earn(reward);
+        }
+
+        let now = self.solar_setup.get_ingame_time_m();
+        let updates = self.mission_board.bind_mut().update(now);
+
+        for update in updates {
+            match update {
+                MissionUpdate::Started(mission) => {
+                    logger::info!("mission started: {}", mission.title());
+                    self.mission_started.emit((
+                        mission.title().as_str().into(),
+                        mission.description().as_str().into(),
+                    ));
+                }
+                MissionUpdate::Completed(mission) => {
+                    logger::info!("mission completed: {}", mission.title());
+                    self.wallet.bind_mut().earn(mission.reward);
+                    self.mission_completed
+                        .emit((mission.title().as_str().into(), mission.reward));
+                }
+                MissionUpdate::Failed(mission) => {
+                    logger::info!("mission failed: {}", mission.title());
+                    self.mission_failed.emit(mission.title().as_str().into());
+                }
+            }
+        }
+    }
+
+    /// The hand written mission chain of the map, or a randomly planned chain.
+    fn mission_chain(&self, city: &City, map_path: &GString) -> Vec<Mission> {
+        let Some(chain) = self
+            .mission_chains
+            .iter_shared()
+            .find(|chain| chain.bind().map() == map_path)
+        else {
+            let seed = rand::random();
+            let chain = missions::plan_missions(city, seed);
+
+            logger::info!("planned {} missions with seed {}", chain.len(), seed);
+            return chain;
+        };
+
+        let chain: Vec<_> = chain
+            .bind()
+            .missions()
+            .enumerate()
+            .filter_map(|(index, mission)| {
+                let Some(definition) = mission.bind().definition() else {
+                    logger::error!(
+                        "mission {} of {} targets a tile outside of the city",
+                        index,
+                        map_path
+                    );
+                    return None;
+                };
+
+                definition
+                    .build(city)
+                    .inspect_err(|err| {
+                        logger::error!("mission {} of {} is invalid: {}", index, map_path, err);
+                    })
+                    .ok()
+            })
+            .collect();
+
+        logger::info!("loaded {} missions for {}", chain.len(), map_path);
+        chain
+    }
+
+    /// Checks if the player is hovering over the target building of the active mission.
+    fn reached_building(&self) -> Option<TileCoords> {
+        let city = self.city.as_ref()?;
+        let city_coords_feature = self.city_coords_feature.as_ref()?;
+
+        let target = match &self.mission_board.bind().active_mission()?.objective {
+            Objective::ReachBuilding { building, .. } => *building,
+            Objective::ExtinguishFires { .. } | Objective::ClearRiot { .. } => return None,
+        };
+
+        let building = city.buildings.get(&target)?;
+        let tile = city.tilelist.get(&target)?;
+        let player = self
+            .base
+            .get_tree()
+            .get_first_node_in_group("player")?
+            .try_cast::<Node3D>()
+            .ok()?;
+
+        let location = city_coords_feature.get_building_coords(
+            target.0,
+            target.1,
+            tile.altitude,
+            building.size,
+        );
+        let player_location = player.get_global_position();
+        let distance = Vector2::new(location.x, location.z)
+            .distance_to(Vector2::new(player_location.x, player_location.z));
+        let radius = f32::from(self.world_constants.bind().tile_size()) * f32::from(building.size);
+
+        (distance <= radius).then_some(target)
+    }
+}
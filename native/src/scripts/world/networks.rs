@@ -0,0 +1,398 @@
+/*
+ * Copyright (c) SimChopper; Jovan Gerodetti and contributors.
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use derive_debug::Dbg;
+use godot::builtin::Vector3;
+use godot::classes::{Node, Node3D, PackedScene, Time};
+use godot::meta::ToGodot;
+use godot::obj::{Gd, Singleton as _};
+use godot::task;
+use godot::task::TaskHandle;
+use godot_rust_script::{
+    godot_script_impl, CastToScript, Context, GodotScript, OnEditor, RsRef, ScriptSignal,
+};
+use rand::seq::IndexedRandom;
+
+use crate::objects::scene_object_registry;
+use crate::rail_navigation::RailNavigationConfig;
+use crate::resources::WorldConstants;
+use crate::road_navigation::RoadNavigationConfig;
+use crate::scripts::{CarSpawner, ICarSpawner};
+use crate::util::async_support::{self, GodotFuture};
+use crate::util::logger;
+use crate::world::city_coords_feature::CityCoordsFeature;
+use crate::world::city_data::{self, TileCoords, TileList, ToDictionary};
+use crate::world::city_loader::CityHandle;
+use crate::world::network_pieces::{self, Network};
+
+/// Places the power line, road and rail network pieces of a city and links them into the
+/// navigation graphs.
+#[derive(GodotScript, Dbg)]
+#[script(base = Node)]
+pub struct Networks {
+    #[dbg(skip)]
+    pending_build_tasks: Vec<TaskHandle>,
+
+    #[export]
+    pub world_constants: OnEditor<Gd<WorldConstants>>,
+
+    #[export]
+    pub road_navigation: OnEditor<Gd<RoadNavigationConfig>>,
+
+    #[export]
+    pub rail_navigation: OnEditor<Gd<RailNavigationConfig>>,
+
+    #[export]
+    pub powerline_network: OnEditor<Gd<Node>>,
+
+    #[export]
+    pub road_network: OnEditor<Gd<Node3D>>,
+
+    #[export]
+    pub rail_network: OnEditor<Gd<Node3D>>,
+
+    /// Scene of the car spawners that are placed on straight roads.
+    #[export]
+    pub car_spawner: OnEditor<Gd<PackedScene>>,
+
+    #[export]
+    pub car_spawner_count: u8,
+
+    /// Scene of the trains that are placed on straight tracks.
+    #[export]
+    pub train: OnEditor<Gd<PackedScene>>,
+
+    #[export]
+    pub train_count: u8,
+
+    #[signal("progress")]
+    pub loading_progress: ScriptSignal<u32>,
+
+    base: Gd<Node>,
+}
+
+/// The nodes network pieces are inserted into.
+struct NetworkTargets {
+    powerlines: Gd<Node>,
+    road: Gd<Node3D>,
+    rail: Gd<Node3D>,
+    road_navigation: Gd<RoadNavigationConfig>,
+    rail_navigation: Gd<RailNavigationConfig>,
+    owner: Option<Gd<Node>>,
+}
+
+#[godot_script_impl]
+impl Networks {
+    const TIME_BUDGET: u64 = 100;
+
+    pub fn _init(&mut self) {
+        self.car_spawner_count = 3;
+        self.train_count = 2;
+    }
+
+    pub fn _process(&mut self, _delta: f64) {
+        self.pending_build_tasks
+            .retain(godot::task::TaskHandle::is_pending);
+    }
+
+    #[expect(clippy::needless_pass_by_value)]
+    pub fn build_async(&mut self, city: Gd<CityHandle>, mut ctx: Context<Self>) -> Gd<GodotFuture> {
+        let world_constants = (*self.world_constants).clone();
+        let city = city.bind().city().clone();
+        let (resolve, godot_future) = async_support::godot_future();
+        let car_spawner = (*self.car_spawner).clone();
+        let car_spawner_count = self.car_spawner_count;
+        let train = (*self.train).clone();
+        let train_count = self.train_count;
+
+        let mut targets = NetworkTargets {
+            powerlines: (*self.powerline_network).clone(),
+            road: (*self.road_network).clone(),
+            rail: (*self.rail_network).clone(),
+            road_navigation: (*self.road_navigation).clone(),
+            rail_navigation: (*self.rail_navigation).clone(),
+            owner: None,
+        };
+
+        let handle = ctx.reentrant_scope(self, |base: Gd<Node>| {
+            let mut script_self_ref: RsRef<Self> = base.to_script();
+            let tree = base.get_tree();
+
+            task::spawn(async move {
+                let next_tick = tree.signals().process_frame();
+                let time = Time::singleton();
+
+                let tile_height = world_constants.bind().tile_height();
+                let tiles = &city.tilelist;
+                let city_coords_feature =
+                    CityCoordsFeature::new(world_constants, city.simulator_settings.sea_level);
+
+                targets.owner = tree.get_current_scene();
+
+                logger::info!("starting to load networks...");
+
+                let mut count = 0;
+                let mut start = time.get_ticks_msec();
+                let mut road_sections = Vec::new();
+                let mut rail_sections = Vec::new();
+
+                for piece in city.networks.values() {
+                    if (time.get_ticks_msec() - start) > Self::TIME_BUDGET {
+                        script_self_ref.emit_progress(count);
+                        count = 0;
+                        start = time.get_ticks_msec();
+
+                        let _: () = next_tick.to_future().await;
+                    }
+
+                    count += 1;
+
+                    let Some(network) = Self::insert_piece(
+                        &mut targets,
+                        piece,
+                        tiles,
+                        &city_coords_feature,
+                        tile_height,
+                    ) else {
+                        continue;
+                    };
+
+                    if network_pieces::is_straight(piece.id) {
+                        match network {
+                            Network::Road => road_sections.push(piece.tile_coords),
+                            Network::Rail => rail_sections.push(piece.tile_coords),
+                            Network::Powerline | Network::RoadAndRail => (),
+                        }
+                    }
+                }
+
+                script_self_ref.emit_progress(count);
+
+                let mut parent = base.get_parent().expect("networks node is always in the tree");
+
+                Self::spawn_car_spawners(
+                    &mut parent,
+                    &car_spawner,
+                    car_spawner_count,
+                    &road_sections,
+                    &targets.road,
+                    tiles,
+                    &city_coords_feature,
+                );
+
+                Self::spawn_trains(&mut parent, &train, train_count, &rail_sections);
+
+                logger::info!(
+                    "generated networks: {} nodes in road network, {} nodes in rail network, {} nodes in powerline network",
+                    targets.road.get_child_count(),
+                    targets.rail.get_child_count(),
+                    targets.powerlines.get_child_count()
+                );
+
+                // yield at least once at the end, to let the engine catch up
+                let _: () = next_tick.to_future().await;
+
+                resolve(());
+            })
+        });
+
+        self.pending_build_tasks.push(handle);
+        godot_future
+    }
+
+    /// Insert a network piece into the world and into the navigation graph of its network.
+    fn insert_piece(
+        targets: &mut NetworkTargets,
+        piece: &city_data::Building,
+        tiles: &TileList,
+        city_coords_feature: &CityCoordsFeature,
+        tile_height: u8,
+    ) -> Option<Network> {
+        let Some(network) = Network::of(piece.id) else {
+            logger::warn!("network section doesn't belong to any network: {:?}", piece);
+            return None;
+        };
+
+        let Some(object) = scene_object_registry::load_network(piece.id) else {
+            logger::error!("unknown network section \"{}\"", piece.name);
+            return None;
+        };
+
+        let Some(mut instance) = object.try_instantiate_as::<Node3D>() else {
+            logger::error!("failed to instantiate network section {}", piece.name);
+            return None;
+        };
+
+        let (x, y) = piece.tile_coords;
+
+        let Some(tile) = tiles.get(&piece.tile_coords) else {
+            logger::error!("missing tile at {:?}", piece.tile_coords);
+            return None;
+        };
+
+        let mut location = city_coords_feature.get_building_coords(x, y, tile.altitude, 1);
+
+        location.y += f32::from(tile_height) * f32::from(network_pieces::elevation(piece.id, tile));
+
+        let neighbors = network_pieces::neighbors(tiles, piece.tile_coords);
+
+        if instance.has_method("set_orientation") {
+            let neighbors = neighbors.map(|tile| {
+                tile.map(ToDictionary::to_dict)
+                    .unwrap_or_default()
+                    .to_variant()
+            });
+
+            instance.call("set_orientation", &neighbors);
+        }
+
+        if instance.has_method("set_turned") {
+            let turned = network_pieces::is_turned_highway_segment(piece.id, neighbors);
+
+            instance.call("set_turned", &[turned.to_variant()]);
+        }
+
+        instance.set_position(location);
+
+        match network {
+            Network::Powerline => {
+                targets
+                    .powerlines
+                    .add_child_ex(&instance)
+                    .force_readable_name(true)
+                    .done();
+            }
+
+            Network::Road => {
+                targets
+                    .road
+                    .add_child_ex(&instance)
+                    .force_readable_name(true)
+                    .done();
+
+                targets
+                    .road_navigation
+                    .bind_mut()
+                    .road_navigation_mut()
+                    .insert_node(piece.clone(), instance.clone());
+            }
+
+            Network::Rail => {
+                targets
+                    .rail
+                    .add_child_ex(&instance)
+                    .force_readable_name(true)
+                    .done();
+
+                targets
+                    .rail_navigation
+                    .bind_mut()
+                    .rail_navigation_mut()
+                    .insert_node(piece.clone(), instance.clone());
+            }
+
+            Network::RoadAndRail => {
+                targets
+                    .road
+                    .add_child_ex(&instance)
+                    .force_readable_name(true)
+                    .done();
+
+                targets
+                    .road_navigation
+                    .bind_mut()
+                    .road_navigation_mut()
+                    .insert_node(piece.clone(), instance.clone());
+
+                targets
+                    .rail_navigation
+                    .bind_mut()
+                    .rail_navigation_mut()
+                    .insert_node(piece.clone(), instance.clone());
+            }
+        }
+
+        if let Some(owner) = targets.owner.as_ref() {
+            instance.set_owner(owner);
+        }
+
+        Some(network)
+    }
+
+    fn spawn_car_spawners(
+        parent: &mut Gd<Node>,
+        scene: &Gd<PackedScene>,
+        count: u8,
+        road_sections: &[TileCoords],
+        road_network: &Gd<Node3D>,
+        tiles: &TileList,
+        city_coords_feature: &CityCoordsFeature,
+    ) {
+        let mut rng = rand::rng();
+
+        for _ in 0..count {
+            let Some((x, y)) = road_sections.choose(&mut rng).copied() else {
+                logger::warn!("there are no roads to place car spawners on");
+                return;
+            };
+
+            let Some(tile) = tiles.get(&(x, y)) else {
+                logger::error!("missing tile at {:?}", (x, y));
+                continue;
+            };
+
+            let Some(mut spawner) = scene.try_instantiate_as::<Node3D>() else {
+                logger::error!("failed to instantiate car spawner");
+                return;
+            };
+
+            let altitude = tile.altitude + u32::from(network_pieces::terrain_elevation(tile));
+            let location = city_coords_feature.get_building_coords(x, y, altitude, 1);
+
+            spawner.set("road_network_path", &road_network.get_path().to_variant());
+            spawner.translate(location);
+            spawner.translate(Vector3::UP * 0.05);
+
+            parent
+                .add_child_ex(&spawner)
+                .force_readable_name(true)
+                .done();
+            spawner.set_owner(&*parent);
+
+            let mut spawner: RsRef<CarSpawner> = spawner.into_script();
+
+            spawner.start_auto_spawn();
+        }
+    }
+
+    fn spawn_trains(
+        parent: &mut Gd<Node>,
+        scene: &Gd<PackedScene>,
+        count: u8,
+        rail_sections: &[TileCoords],
+    ) {
+        let mut rng = rand::rng();
+
+        for (x, y) in rail_sections
+            .choose_multiple(&mut rng, count.into())
+            .copied()
+        {
+            let Some(mut train) = scene.try_instantiate_as::<Node3D>() else {
+                logger::error!("failed to instantiate train");
+                return;
+            };
+
+            parent.add_child_ex(&train).force_readable_name(true).done();
+            train.set_owner(&*parent);
+            train.call("start", &[x.to_variant(), y.to_variant()]);
+        }
+    }
+
+    pub fn emit_progress(&self, new_piece_count: u32) {
+        self.loading_progress.emit(new_piece_count);
+    }
+}
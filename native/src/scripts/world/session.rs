@@ -0,0 +1,291 @@
+/*
+ * Copyright (c) SimChopper; Jovan Gerodetti and contributors.
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use derive_debug::Dbg;
+use godot::builtin::{array, Array, GString, PackedByteArray, PackedStringArray, Vector3};
+use godot::classes::file_access::ModeFlags;
+use godot::classes::{Decal, FileAccess, Node3D, Resource, RigidBody3D};
+use godot::meta::ToGodot;
+use godot::obj::Gd;
+use godot_rust_script::{godot_script_impl, CastToScript, GodotScript, OnEditor, RsRef};
+
+use crate::resources::{HelicopterUpgrade, MissionBoard, Wallet, WaterDecalTracker, WaterTank};
+use crate::scripts::world::buildings::{Buildings, IBuildings};
+use crate::scripts::world::solar_setup::{ISolarSetup, SolarSetup};
+use crate::scripts::{CarSpawner, ICarSpawner};
+use crate::util::{logger, Uf32};
+use crate::world::drying::Surface;
+use crate::world::save_game::{SaveGame, SavedCar, SavedDecal};
+
+/// Saves the running session and restores it on top of a freshly built city.
+#[derive(GodotScript, Dbg)]
+#[script(base = Node3D)]
+struct Session {
+    /// File the session is saved to.
+    #[export]
+    pub save_path: GString,
+
+    #[export]
+    pub buildings: OnEditor<RsRef<Buildings>>,
+
+    #[export]
+    pub solar_setup: OnEditor<RsRef<SolarSetup>>,
+
+    #[export]
+    pub mission_board: OnEditor<Gd<MissionBoard>>,
+
+    #[export]
+    pub decal_tracker: OnEditor<Gd<WaterDecalTracker>>,
+
+    #[export]
+    pub wallet: OnEditor<Gd<Wallet>>,
+
+    #[export]
+    pub water_tank: OnEditor<Gd<WaterTank>>,
+
+    base: Gd<Node3D>,
+}
+
+#[godot_script_impl]
+impl Session {
+    const PLAYER_GROUP: &str = "player";
+    const CAR_SPAWNER_GROUP: &str = "car_spawner";
+    const WATER_DECAL_GROUP: &str = "wet_surface_decal";
+
+    pub fn has_save(&self) -> bool {
+        FileAccess::file_exists(&self.save_path)
+    }
+
+    /// Saves the current state of the session that is played on the map.
+    #[expect(clippy::needless_pass_by_value)]
+    pub fn save(&mut self, map_path: GString) -> bool {
+        let Some(helicopter) = self.helicopter() else {
+            logger::error!("Unable to save session, there is no player!");
+            return false;
+        };
+
+        let upgrades: Array<Gd<HelicopterUpgrade>> = helicopter.get("upgrades_owned").to();
+        let fire_simulation = self.buildings.fire_simulation();
+        let fires = fire_simulation
+            .as_ref()
+            .map(|simulation| simulation.bind().fires())
+            .unwrap_or_default();
+
+        let save = SaveGame {
+            map: map_path.to_string(),
+            helicopter: helicopter.get_global_transform(),
+            upgrades: upgrades
+                .iter_shared()
+                .map(|upgrade| upgrade.upcast_ref::<Resource>().get_path())
+                .map(|path| path.to_string())
+                .collect(),
+            balance: self.wallet.bind().balance(),
+            water: self.water_tank.bind().level(),
+            time: self.solar_setup.get_time().into_u32(),
+            fires,
+            cars: self.cars(),
+            decals: self.decals(),
+            missions: self.mission_board.bind().missions().cloned().collect(),
+        };
+
+        let Some(mut file) = FileAccess::open(&self.save_path, ModeFlags::WRITE) else {
+            logger::error!(
+                "Failed to open {} for writing: {:?}",
+                self.save_path,
+                FileAccess::get_open_error()
+            );
+            return false;
+        };
+
+        let saved = file.store_buffer(&PackedByteArray::from(save.encode().as_slice()));
+
+        file.close();
+
+        if saved {
+            logger::info!(
+                "saved session with {} fires, {} cars and {} missions",
+                save.fires.len(),
+                save.cars.len(),
+                save.missions.len()
+            );
+        } else {
+            logger::error!("Failed to write session to {}", self.save_path);
+        }
+
+        saved
+    }
+
+    /// Restores the saved session. The city of the map has to be fully built before the session
+    /// can be restored.
+    #[expect(clippy::needless_pass_by_value)]
+    pub fn restore(&mut self, map_path: GString) -> bool {
+        let data = FileAccess::get_file_as_bytes(&self.save_path);
+
+        let save = match SaveGame::decode(data.as_slice())
+            .and_then(|save| save.for_map(&map_path.to_string()))
+        {
+            Ok(save) => save,
+            Err(err) => {
+                logger::error!("Failed to restore session from {}: {}", self.save_path, err);
+                return false;
+            }
+        };
+
+        if let Some(mut helicopter) = self.helicopter() {
+            let upgrades: PackedStringArray = save.upgrades.iter().map(GString::from).collect();
+
+            helicopter.set_global_transform(save.helicopter);
+            helicopter.set_linear_velocity(Vector3::ZERO);
+            helicopter.set_angular_velocity(Vector3::ZERO);
+            helicopter.call("restore_upgrades", &[upgrades.to_variant()]);
+            helicopter.call("snap_camera", &[]);
+        }
+
+        self.wallet.bind_mut().set_balance(save.balance);
+        self.water_tank.bind_mut().set_level(save.water);
+
+        self.solar_setup
+            .set_time(Uf32::new(save.time.min(Uf32::MAX.into_u32())));
+
+        if let Some(mut simulation) = self.buildings.fire_simulation() {
+            let mut simulation = simulation.bind_mut();
+
+            for (building, strength) in &save.fires {
+                simulation.ignite(*building, *strength);
+            }
+        }
+
+        self.restore_cars(&save.cars);
+        self.restore_decals(&save.decals);
+        self.mission_board.bind_mut().set_missions(save.missions);
+
+        logger::info!("restored session from {}", self.save_path);
+        true
+    }
+
+    fn helicopter(&self) -> Option<Gd<RigidBody3D>> {
+        self.base
+            .get_tree()
+            .get_first_node_in_group(Self::PLAYER_GROUP)?
+            .try_cast()
+            .ok()
+    }
+
+    fn car_spawners(&self) -> Vec<Gd<Node3D>> {
+        self.base
+            .get_tree()
+            .get_nodes_in_group(Self::CAR_SPAWNER_GROUP)
+            .iter_shared()
+            .filter_map(|spawner| spawner.try_cast().ok())
+            .collect()
+    }
+
+    /// All cars that have been spawned by one of the car spawners.
+    fn spawned_cars(&self) -> Vec<(Gd<Node3D>, Gd<Node3D>)> {
+        self.car_spawners()
+            .into_iter()
+            .flat_map(|spawner| {
+                spawner
+                    .get_children()
+                    .iter_shared()
+                    .filter(|child| child.has_method("target_node"))
+                    .filter_map(|child| child.try_cast::<Node3D>().ok())
+                    .map(|car| (spawner.clone(), car))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    fn cars(&self) -> Vec<SavedCar> {
+        self.spawned_cars()
+            .into_iter()
+            .map(|(spawner, mut car)| {
+                let target: Array<u32> = car.call("target_node", &[]).to();
+
+                SavedCar {
+                    spawner: spawner.get_name().to_string(),
+                    transform: car.get_global_transform(),
+                    target: target.get(0).zip(target.get(1)),
+                }
+            })
+            .collect()
+    }
+
+    fn restore_cars(&self, cars: &[SavedCar]) {
+        // the cars of the fresh city are replaced by the cars of the session.
+        for (_, mut car) in self.spawned_cars() {
+            car.queue_free();
+        }
+
+        let spawners = self.car_spawners();
+
+        for car in cars {
+            let Some(spawner) = spawners
+                .iter()
+                .find(|spawner| spawner.get_name().to_string() == car.spawner)
+            else {
+                logger::warn!("Car spawner {} does not exist, skipping car.", car.spawner);
+                continue;
+            };
+
+            let target = car.target.map(|(x, y)| array![x, y]).unwrap_or_default();
+
+            let mut spawner: RsRef<CarSpawner> = spawner.clone().into_script();
+
+            spawner.spawn_car_at(car.transform, target);
+        }
+    }
+
+    fn decals(&self) -> Vec<SavedDecal> {
+        self.decal_tracker
+            .bind()
+            .decals()
+            .filter(|decal| decal.is_instance_valid())
+            .map(|decal| SavedDecal {
+                transform: decal.get_global_transform(),
+                size: decal.get_size(),
+            })
+            .collect()
+    }
+
+    fn restore_decals(&mut self, decals: &[SavedDecal]) {
+        if decals.is_empty() {
+            return;
+        }
+
+        // spawned decals are copies of the inactive template decal of the water cannon.
+        let template = self
+            .base
+            .get_tree()
+            .get_nodes_in_group(Self::WATER_DECAL_GROUP)
+            .iter_shared()
+            .filter_map(|node| node.try_cast::<Decal>().ok())
+            .find(|decal| !decal.get("is_active").try_to::<bool>().unwrap_or(false));
+
+        let Some(template) = template else {
+            logger::warn!("There is no water decal template, skipping water decals.");
+            return;
+        };
+
+        for decal in decals {
+            let mut decal_inst: Gd<Decal> = template.duplicate_node();
+
+            decal_inst.set_size(decal.size);
+            decal_inst.set("is_active", &true.to_variant());
+
+            self.base.add_child(&decal_inst);
+
+            decal_inst.set_global_transform(decal.transform);
+            decal_inst.set_visible(true);
+
+            // the surface is not saved, restored decals dry like the ground.
+            self.decal_tracker
+                .bind_mut()
+                .insert(&decal_inst, Surface::default());
+        }
+    }
+}
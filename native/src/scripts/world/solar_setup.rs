@@ -51,6 +51,9 @@ pub struct SolarSetup {
 
     update_delay: f32,
 
+    /// Offset of the game time, replaces the default start time of a new game.
+    time_offset: Option<Uf32>,
+
     base: Gd<Node3D>,
 }
 
@@ -165,7 +168,9 @@ impl SolarSetup {
     pub fn get_time(&self) -> Uf32 {
         let day_length = self.day_length_ms();
         // start game at 30% of the day.
-        let base_offset = (day_length / Uf32::new(10)) * Uf32::new(3);
+        let base_offset = self
+            .time_offset
+            .unwrap_or((day_length / Uf32::new(10)) * Uf32::new(3));
 
         (base_offset
             + Uf32::new(
@@ -177,6 +182,19 @@ impl SolarSetup {
             % (day_length * Uf32::new(2))
     }
 
+    /// Continues the game at the given time in ms, e.g. when a saved session is restored.
+    pub fn set_time(&mut self, time: Uf32) {
+        let full_day = (self.day_length_ms() * Uf32::new(2)).into_u64();
+        let ticks = Time::singleton().get_ticks_msec() % full_day;
+        let offset = (time.into_u64() % full_day + full_day - ticks) % full_day;
+
+        self.time_offset = Some(Uf32::new(
+            offset
+                .try_into()
+                .expect("offset is smaller than the length of a day"),
+        ));
+    }
+
     // get the current in-game time in seconds since sunrise.
     pub fn get_ingame_time_s(&self) -> f64 {
         self.get_ingame_time_m() * 60.0
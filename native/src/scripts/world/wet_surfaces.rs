@@ -0,0 +1,77 @@
+/*
+ * Copyright (c) SimChopper; Jovan Gerodetti and contributors.
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use godot::classes::Node;
+use godot::obj::Gd;
+use godot_rust_script::{godot_script_impl, GodotScript, OnEditor, RsRef};
+
+use crate::resources::{WaterDecalTracker, WorldConstants};
+use crate::scripts::world::buildings::{Buildings, IBuildings};
+use crate::scripts::world::solar_setup::{ISolarSetup, SolarSetup};
+use crate::world::city_coords_feature::CityCoordsFeature;
+
+/// Dries the water decals depending on the sun and the fires of the city.
+#[derive(GodotScript, Debug)]
+#[script(base = Node)]
+struct WetSurfaces {
+    #[export]
+    pub decal_tracker: OnEditor<Gd<WaterDecalTracker>>,
+
+    #[export]
+    pub solar_setup: OnEditor<RsRef<SolarSetup>>,
+
+    /// Provides the fire simulation, burning buildings dry the ground around them.
+    #[export]
+    pub buildings: OnEditor<RsRef<Buildings>>,
+
+    #[export]
+    pub world_constants: OnEditor<Gd<WorldConstants>>,
+
+    update_delay: f64,
+
+    base: Gd<Node>,
+}
+
+#[godot_script_impl]
+impl WetSurfaces {
+    /// Seconds between two drying steps.
+    const UPDATE_INTERVAL: f64 = 0.25;
+
+    pub fn _process(&mut self, delta: f64) {
+        self.update_delay += delta;
+
+        if self.update_delay < Self::UPDATE_INTERVAL {
+            return;
+        }
+
+        #[expect(clippy::cast_possible_truncation)]
+        let elapsed = self.update_delay as f32;
+
+        self.update_delay = 0.0;
+
+        let city_coords_feature = CityCoordsFeature::new((*self.world_constants).clone(), 0);
+        let fires: Vec<_> = self
+            .buildings
+            .fire_simulation()
+            .map(|simulation| simulation.bind().burning_buildings())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(building, strength)| {
+                let (x, y) = building.tile_coords;
+                let location = city_coords_feature.get_building_coords(x, y, 0, building.size);
+
+                (location, strength)
+            })
+            .collect();
+
+        let sun_brightness = self.solar_setup.sun_brightness();
+
+        self.decal_tracker
+            .bind_mut()
+            .dry(elapsed, sun_brightness, &fires);
+    }
+}
@@ -5,17 +5,22 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/.
  */
 
+mod collision;
 mod lerp;
 mod point;
+mod slope_solver;
 mod terrain_rotation;
+mod tile_edits;
 mod tile_surface;
+mod validation;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::ops::{Deref, Not};
+use std::sync::Arc;
 use std::time::Instant;
 
 use godot::classes::mesh::PrimitiveType;
-use godot::classes::{ArrayMesh, Material, SurfaceTool};
+use godot::classes::{ArrayMesh, CollisionShape3D, Material, SurfaceTool};
 use godot::meta::GodotType;
 use godot::{prelude::*, task};
 use itertools::Itertools;
@@ -24,8 +29,14 @@ use num::ToPrimitive;
 use num_enum::TryFromPrimitive;
 use rayon::prelude::*;
 
+use collision::ChunkCollision;
 use point::{DimensionX, DimensionZ};
-use tile_surface::{Face, SurfaceAssociated, TileFaces, TileSurface, TileSurfaceType, Vertex};
+use slope_solver::SlopeSolverReport;
+use tile_edits::{EditQueue, TileEdit};
+use tile_surface::{
+    lod_levels, Face, SurfaceAssociated, TileFaces, TileSurface, TileSurfaceType, Vertex,
+    WATER_RESOLUTION,
+};
 
 pub(crate) use terrain_rotation::TerrainRotation;
 
@@ -33,9 +44,9 @@ use crate::objects::scene_object_registry::{Bridge, Powerlines, Road};
 use crate::util::async_support::{godot_future, GodotFuture};
 use crate::util::logger;
 use crate::world::city_data::{
-    TerrainSlope, TerrainType, Tile, TileCoords, TileList, TileListExt, TileValidationResult,
-    TryFromDictionary,
+    City, TerrainSlope, TerrainType, Tile, TileCoords, TileList, TileListExt, TileValidationResult,
 };
+use crate::world::city_loader::CityHandle;
 
 struct Shared<T: GodotType>(T);
 
@@ -67,14 +78,35 @@ struct ChunkConfig {
 #[class(no_init)]
 pub struct TerrainChunk {
     config: ChunkConfig,
-    mesh: Shared<Gd<ArrayMesh>>,
+    /// One mesh per level of detail, starting with the most detailed one.
+    lods: Vec<Shared<Gd<ArrayMesh>>>,
+    collision: ChunkCollision,
 }
 
 #[godot_api]
 impl TerrainChunk {
+    /// The most detailed mesh of the chunk.
     #[func]
     pub fn mesh(&self) -> Gd<ArrayMesh> {
-        self.mesh.inner().to_owned()
+        self.lods[0].inner().to_owned()
+    }
+
+    /// Meshes of all levels of detail, starting with the most detailed one. The outer tiles of
+    /// each level match the most detailed mesh, so neighboring chunks of different levels share
+    /// their edges.
+    #[func]
+    pub fn lod_meshes(&self) -> Array<Gd<ArrayMesh>> {
+        self.lods
+            .iter()
+            .map(|mesh| mesh.inner().to_owned())
+            .collect()
+    }
+
+    /// Creates a heightfield shape of the chunk and a wall shape for each cliff. The shapes use
+    /// the same coordinate space as the meshes.
+    #[func]
+    pub fn collision_shapes(&self) -> Array<Gd<CollisionShape3D>> {
+        self.collision.create_shapes().into_iter().collect()
     }
 
     #[func]
@@ -87,7 +119,8 @@ impl TerrainChunk {
 
 struct ChunkSurfaces {
     config: ChunkConfig,
-    surfaces: SurfaceMap,
+    /// Surfaces for each level of detail.
+    lods: Vec<SurfaceMap>,
 }
 
 struct CoordinatorThreadContext {
@@ -145,9 +178,14 @@ impl WorkerThreadContext<'_> {
     }
 }
 
+/// Resolves the future of a rebuild with the new chunks.
+type ChunkResolver = Box<dyn Fn(Vec<Gd<TerrainChunk>>)>;
+
 enum TerrainBuilderProgress {
     Progress,
-    Complete(Vec<TerrainChunk>),
+    /// The generated chunks, the tiles they have been generated from and the outcome of the slope
+    /// solver.
+    Complete(Vec<TerrainChunk>, TileList, SlopeSolverReport),
 }
 
 #[derive(GodotClass)]
@@ -159,7 +197,15 @@ pub struct TerrainBuilder {
     sea_level: u16,
     chunk_size: u32,
     rotation: Gd<TerrainRotation>,
-    tilelist: VarDictionary,
+    city: Option<Arc<City>>,
+    /// Tiles of the last build, including all edits that have been applied since.
+    tilelist: Option<Arc<TileList>>,
+    /// Tile edits that are applied with the next rebuild.
+    tile_edits: EditQueue,
+    /// Callers that wait for the next rebuild, while another rebuild is still running.
+    queued_rebuilds: Vec<ChunkResolver>,
+    /// Outcome of the slope solver of the last build or rebuild.
+    solver_report: SlopeSolverReport,
     materials: VarDictionary,
     debug_render_invalid: bool,
     render_water: bool,
@@ -172,17 +218,27 @@ impl TerrainBuilder {
 
     const WATER_SURFACE: &str = "water";
 
+    /// Number of meshes with decreasing resolution that are generated for each chunk. The last
+    /// level reduces even the most detailed surfaces to a single quad per tile.
+    ///
+    /// Tiles are never merged, so a single quad per tile is the lowest detail a chunk can reach.
+    /// Ground surfaces only drop from two quads per side to one.
+    const LOD_LEVELS: u8 = lod_levels(WATER_RESOLUTION);
+
     #[func]
     fn ground_surface() -> StringName {
         Self::GROUND_SURFACE.into()
     }
 
     #[func]
+    #[expect(clippy::needless_pass_by_value)]
     fn new(
-        tilelist: VarDictionary,
+        city: Gd<CityHandle>,
         rotation: Gd<TerrainRotation>,
         materials: VarDictionary,
     ) -> Gd<TerrainBuilder> {
+        let city = city.bind().city().clone();
+
         Gd::from_init_fn(|base| TerrainBuilder {
             tile_size: 16,
             city_size: 0,
@@ -190,7 +246,11 @@ impl TerrainBuilder {
             sea_level: 0,
             chunk_size: 8,
             rotation,
-            tilelist,
+            city: Some(city),
+            tilelist: None,
+            tile_edits: EditQueue::default(),
+            queued_rebuilds: Vec::new(),
+            solver_report: SlopeSolverReport::default(),
             materials,
             debug_render_invalid: false,
             render_water: true,
@@ -255,7 +315,8 @@ impl TerrainBuilder {
 
         std::thread::spawn(move || {
             let timer = Instant::now();
-            let tilelist = Self::pre_process_tilelist(&context, tilelist);
+            let (tilelist, report) =
+                Self::pre_process_tilelist(context.rotation, context.chunk_size, tilelist);
 
             let chunks = (0..chunk_count)
                 .flat_map(|y| (0..chunk_count).map(move |x| (x, y)))
@@ -286,7 +347,7 @@ impl TerrainBuilder {
 
             godot_print!("terrain build time: {}ms", timer.elapsed().as_millis());
 
-            if let Err(err) = tx.send(TerrainBuilderProgress::Complete(result)) {
+            if let Err(err) = tx.send(TerrainBuilderProgress::Complete(result, tilelist, report)) {
                 logger::error!("Failed to Send TerrainBuilder final message: {}", err);
             }
         });
@@ -294,6 +355,85 @@ impl TerrainBuilder {
         rx
     }
 
+    /// Applies the tile edits and regenerates every chunk that contains a changed tile or one of
+    /// its eight neighbors, since the cliffs and the collision corners of a tile depend on its
+    /// neighbors.
+    fn spawn_rebuild_thread(
+        context: CoordinatorThreadContext,
+        tilelist: Arc<TileList>,
+        edits: BTreeMap<TileCoords, TileEdit>,
+    ) -> Receiver<TerrainBuilderProgress> {
+        let (tx, rx) = kanal::unbounded::<TerrainBuilderProgress>();
+
+        std::thread::spawn(move || {
+            let timer = Instant::now();
+            let chunk_size = context.chunk_size;
+            let mut tilelist = (*tilelist).clone();
+            let (changed, report) = tile_edits::apply(&mut tilelist, &edits, context.rotation);
+
+            for tile in &report.unresolved {
+                logger::warn!(
+                    "Edited tile {:?} has an invalid slope ({:?})",
+                    tile.coordinates,
+                    tile.reason
+                );
+            }
+
+            let chunks: BTreeSet<_> = changed
+                .iter()
+                .flat_map(|coords| tile_edits::surrounding(*coords))
+                .filter(|(x, y)| *x < context.city_size && *y < context.city_size)
+                .map(|(x, y)| (x - x % chunk_size, y - y % chunk_size))
+                .collect();
+
+            let worker_context = context.to_worker(&tilelist);
+
+            let result: Vec<_> = chunks
+                .into_iter()
+                .collect::<Vec<_>>()
+                .into_par_iter()
+                .map(|tile_coords| {
+                    let chunk = ChunkConfig {
+                        tile_coords,
+                        size: chunk_size,
+                    };
+
+                    generate_chunk_mesh(
+                        &worker_context,
+                        generate_chunk_vertices(&worker_context, chunk),
+                    )
+                })
+                .collect();
+
+            logger::debug!(
+                "terrain rebuild time for {} chunks: {}ms",
+                result.len(),
+                timer.elapsed().as_millis()
+            );
+
+            if let Err(err) = tx.send(TerrainBuilderProgress::Complete(result, tilelist, report)) {
+                logger::error!("Failed to Send TerrainBuilder final message: {}", err);
+            }
+        });
+
+        rx
+    }
+
+    /// Keeps the tiles of a finished build for later rebuilds.
+    fn complete_build(
+        builder: &mut Gd<Self>,
+        chunks: Vec<TerrainChunk>,
+        tilelist: TileList,
+        report: SlopeSolverReport,
+    ) -> Vec<Gd<TerrainChunk>> {
+        let mut builder = builder.bind_mut();
+
+        builder.tilelist = Some(Arc::new(tilelist));
+        builder.solver_report = report;
+
+        chunks.into_iter().map(Gd::from_object).collect()
+    }
+
     #[func]
     pub fn build_terain_async(&self) -> Gd<GodotFuture> {
         let chunk_size = self.chunk_size;
@@ -303,10 +443,14 @@ impl TerrainBuilder {
 
         let chunk_count = self.city_size / chunk_size;
         let rotation = self.rotation().bind().deref().to_owned();
-        let tilelist = TileList::try_from_dict(&self.tilelist)
-            .expect("TileList passed from GDScript must be valid");
+        let tilelist = self
+            .city
+            .as_ref()
+            .expect("TerrainBuilder must be created with a city")
+            .tilelist
+            .clone();
         let context = self.thread_context(rotation);
-        let builder: Gd<Self> = self.base().clone().cast();
+        let mut builder: Gd<Self> = self.base().clone().cast();
 
         let (resolve, future) = godot_future::<Vec<Gd<TerrainChunk>>>();
 
@@ -319,10 +463,8 @@ impl TerrainBuilder {
                     Ok(TerrainBuilderProgress::Progress) => {
                         builder.signals().progress().emit(chunk_size);
                     }
-                    Ok(TerrainBuilderProgress::Complete(result)) => {
-                        let chunks = result.into_iter().map(Gd::from_object).collect();
-
-                        resolve(chunks);
+                    Ok(TerrainBuilderProgress::Complete(result, tilelist, report)) => {
+                        resolve(Self::complete_build(&mut builder, result, tilelist, report));
                         break;
                     }
 
@@ -339,96 +481,129 @@ impl TerrainBuilder {
         future
     }
 
-    fn pre_process_tilelist(
-        context: &CoordinatorThreadContext,
-        mut tilelist: TileList,
-    ) -> TileList {
-        let rotation = context.rotation;
-
-        let chunks: Vec<_> = tilelist
-            .values()
-            .chunks(context.chunk_size.pow(2) as usize)
-            .into_iter()
-            .map(itertools::Itertools::collect_vec)
-            .collect();
-
-        let mut pending_tiles: Vec<_> = chunks
-            .par_iter()
-            .map(|chunk| {
-                chunk
-                    .iter()
-                    .map(|tile| (*tile, tilelist.validate_tile_slope(tile, rotation)))
-                    .filter_map(|(tile, validation_result)| {
-                        if !validation_result.is_invalid() {
-                            return None;
-                        }
-
-                        if let Some(special_case) =
-                            is_special_case(tile, &tilelist, &validation_result)
-                        {
-                            return Some((tile, validation_result, Some(special_case)));
-                        }
-
-                        (tile.has_building() || tile.has_surface_water())
-                            .not()
-                            .then_some((tile, validation_result, None))
-                    })
-                    .par_bridge()
-            })
-            .flatten()
-            .map(|(tile, invalid, special_case)| (tile.to_owned(), invalid, special_case))
-            .collect::<Vec<_>>()
-            .into_iter()
-            .sorted_by(|(_, a, _), (_, b, _)| a.empty_invalid_tiles.cmp(&b.empty_invalid_tiles))
-            .collect();
-
-        drop(chunks);
-
-        // remove all the invalid tiles first
-        for (original_tile, _, special_case) in &mut pending_tiles {
-            let tile = tilelist
-                .get_mut(&original_tile.coordinates)
-                .expect("we know the tile exists");
-
-            tile.terrain.slope = TerrainSlope::Undetermined;
-
-            match special_case {
-                // bridge transition pieces have to be lowered by one level of altitude.
-                // This also applies to power line cliffs
-                Some(TileSpecialCase::BridgeTransition | TileSpecialCase::PowerlineCliff) => {
-                    original_tile.altitude -= 1;
-                    tile.altitude -= 1;
-                }
+    /// Number of tiles whose slope still leaves a hole in the terrain after the last build or
+    /// rebuild.
+    #[func]
+    fn unresolved_slopes(&self) -> u32 {
+        self.solver_report
+            .unresolved
+            .len()
+            .try_into()
+            .unwrap_or(u32::MAX)
+    }
 
-                Some(TileSpecialCase::PintchedAllSlope) => {
-                    original_tile.altitude += 1;
-                    original_tile.terrain.slope = TerrainSlope::None;
-                    tile.altitude += 1;
-                }
+    /// Changes the altitude of a tile. The change becomes visible with the next rebuild.
+    #[func]
+    fn set_tile_altitude(&mut self, x: u32, y: u32, altitude: u32) {
+        self.tile_edits.edit((x, y)).altitude = Some(altitude);
+    }
 
-                Some(TileSpecialCase::Powerline | TileSpecialCase::Bridge) | None => (),
-            }
+    /// Changes the terrain type of a tile. The change becomes visible with the next rebuild.
+    #[func]
+    fn set_tile_terrain_type(&mut self, x: u32, y: u32, terrain_type: u8) {
+        self.tile_edits.edit((x, y)).terrain_type = Some(terrain_type.into());
+    }
+
+    /// Applies all pending tile edits and only regenerates the chunks they affect. Resolves to
+    /// the new chunks, which replace the chunks with the same tile coordinates.
+    ///
+    /// Only one rebuild runs at a time. Calls that are made while a rebuild is running share the
+    /// next rebuild, which starts once the running one has completed.
+    #[func]
+    pub fn rebuild_async(&mut self) -> Gd<GodotFuture> {
+        let (resolve, future) = godot_future::<Vec<Gd<TerrainChunk>>>();
+
+        if self.tilelist.is_none() {
+            logger::error!("Unable to rebuild the terrain before it has been built!");
+            resolve(Vec::new());
+            return future;
         }
 
-        let invalid_empty_tiles = pending_tiles.len();
-        let mut fixed_tiles = 0;
+        self.queued_rebuilds.push(Box::new(resolve));
+        self.start_rebuild();
+
+        future
+    }
+
+    /// Starts a rebuild for all queued callers, unless a rebuild is still running. The running
+    /// rebuild starts the next one once it has completed.
+    fn start_rebuild(&mut self) {
+        let Some(tilelist) = self.tilelist.clone() else {
+            return;
+        };
+
+        let Some(edits) = self.tile_edits.start() else {
+            return;
+        };
 
-        for (mut tile, _, _) in pending_tiles {
-            let options = tilelist.valid_slopes(&tile, rotation);
+        let resolvers = std::mem::take(&mut self.queued_rebuilds);
+        let rotation = self.rotation().bind().deref().to_owned();
+        let context = self.thread_context(rotation);
+        let mut builder: Gd<Self> = self.base().clone().cast();
+
+        task::spawn(async move {
+            let receiver = Self::spawn_rebuild_thread(context, tilelist, edits);
 
-            let Some(slope_type) = options.into_iter().next() else {
-                tilelist.insert(tile.coordinates, tile);
-                continue;
+            let chunks = loop {
+                match receiver.as_async().recv().await {
+                    Ok(TerrainBuilderProgress::Progress) => (),
+                    Ok(TerrainBuilderProgress::Complete(result, tilelist, report)) => {
+                        break Self::complete_build(&mut builder, result, tilelist, report);
+                    }
+
+                    Err(ReceiveError::SendClosed | ReceiveError::Closed) => {
+                        logger::error!(
+                            "rebuild thread has disconnected before completing terrain!"
+                        );
+                        break Vec::new();
+                    }
+                }
             };
 
-            tile.terrain.slope = rotation.to_reverted().normalize_slope(*slope_type);
-            tilelist.insert(tile.coordinates, tile);
-            fixed_tiles += 1;
-        }
+            for resolve in &resolvers {
+                resolve(chunks.clone());
+            }
+
+            let mut builder = builder.bind_mut();
 
-        logger::debug!("Fixed {fixed_tiles} of {invalid_empty_tiles} empty tiles");
+            builder.tile_edits.finish();
 
-        tilelist
+            if !builder.queued_rebuilds.is_empty() {
+                builder.start_rebuild();
+            }
+        });
+    }
+
+    /// Fixes the slopes of invalid tiles and logs the tiles that are still invalid. Also returns
+    /// the outcome of the slope solver.
+    fn pre_process_tilelist(
+        rotation: TerrainRotation,
+        chunk_size: u32,
+        tilelist: TileList,
+    ) -> (TileList, SlopeSolverReport) {
+        let (tilelist, report) = fix_invalid_slopes(rotation, chunk_size, tilelist);
+
+        logger::debug!(
+            "Fixed {} of {} empty tiles, {} of them changed their altitude",
+            report.resolved,
+            report.resolved + report.unresolved.len(),
+            report.adjusted.len()
+        );
+
+        let unresolved = report
+            .unresolved
+            .iter()
+            .map(|tile| (tile.reason, tile.coordinates))
+            .into_group_map();
+
+        for (reason, coordinates) in unresolved {
+            logger::warn!(
+                "{} tiles have an invalid slope ({reason:?}): {coordinates:?}",
+                coordinates.len()
+            );
+        }
+
+        (tilelist, report)
     }
 }
 
@@ -456,14 +631,15 @@ struct TerrainBuilderFactory;
 impl TerrainBuilderFactory {
     #[func]
     fn create(
-        tilelist: VarDictionary,
+        city: Gd<CityHandle>,
         rotation: Gd<TerrainRotation>,
         materials: VarDictionary,
     ) -> Gd<TerrainBuilder> {
-        TerrainBuilder::new(tilelist, rotation, materials)
+        TerrainBuilder::new(city, rotation, materials)
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 enum TileSpecialCase {
     BridgeTransition,
     Powerline,
@@ -472,11 +648,55 @@ enum TileSpecialCase {
     PintchedAllSlope,
 }
 
+/// Height of the lowest corner of a tile.
+fn tile_altitude(context: &WorkerThreadContext, tile_data: &Tile) -> f32 {
+    tile_data
+        .altitude()
+        .to_f32()
+        .expect("tile altitude is u32 but should fit in f32")
+        * f32::from(context.tile_height)
+}
+
+/// The sloped ground surface of a tile, before it is modified for water or cliffs.
+fn ground_surface(context: &WorkerThreadContext, tile_data: &Tile) -> TileSurface {
+    let tile_size = f32::from(context.tile_size);
+    let (tile_x, tile_y) = tile_data.coordinates();
+    let tile_x = tile_x
+        .to_f32()
+        .expect("tile coords are u32 but should fit in f32")
+        * tile_size;
+    let tile_y = tile_y
+        .to_f32()
+        .expect("tile coords are u32 but should fit in f32")
+        * tile_size;
+    let tile_z = tile_altitude(context, tile_data);
+
+    let mut tile_surface = TileSurface::new(TileSurfaceType::Ground);
+
+    tile_surface.set_corners([
+        //			0											1
+        Vector3::new(tile_x, tile_z, tile_y),
+        Vector3::new(tile_x + tile_size, tile_z, tile_y),
+        //			2											3
+        Vector3::new(tile_x, tile_z, tile_y + tile_size),
+        Vector3::new(tile_x + tile_size, tile_z, tile_y + tile_size),
+    ]);
+
+    tile_surface.apply_slope(
+        tile_data.terrain.slope,
+        context.rotation,
+        context.tile_height.into(),
+    );
+
+    tile_surface
+}
+
 #[expect(clippy::too_many_lines)]
 fn generate_tile_surfaces(
     context: &WorkerThreadContext,
     tile_data: &Tile,
     tilelist: &TileList,
+    lod: u8,
 ) -> TileFaces {
     let tile_size = f32::from(context.tile_size);
     let tile_height = context.tile_height;
@@ -495,34 +715,15 @@ fn generate_tile_surfaces(
         .to_f32()
         .expect("tile coords are u32 but should fit in f32")
         * tile_size;
-    let tile_z = tile_data
-        .altitude()
-        .to_f32()
-        .expect("tile altitude is u32 but should fit in f32")
-        * f32::from(tile_height);
+    let tile_z = tile_altitude(context, tile_data);
 
-    let mut tile_surface = TileSurface::new(TileSurfaceType::Ground);
-
-    tile_surface.set_corners([
-        //			0											1
-        Vector3::new(tile_x, tile_z, tile_y),
-        Vector3::new(tile_x + tile_size, tile_z, tile_y),
-        //			2											3
-        Vector3::new(tile_x, tile_z, tile_y + tile_size),
-        Vector3::new(tile_x + tile_size, tile_z, tile_y + tile_size),
-    ]);
+    let mut tile_surface = ground_surface(context, tile_data);
 
     tile_surface.set_fixed(tile_data.has_building());
     tile_surface.set_invalid(is_invalid_type);
 
     let mut extra_surfaces: Vec<TileSurface> = Vec::new();
 
-    tile_surface.apply_slope(
-        tile_data.terrain.slope,
-        rotation,
-        context.tile_height.into(),
-    );
-
     match tile_data.terrain.ty {
         TerrainType::DryLand => {
             // (-1, 0)
@@ -590,7 +791,7 @@ fn generate_tile_surfaces(
 
             let mut water_tile = tile_surface.clone();
             water_tile.set_kind(TileSurfaceType::Water);
-            water_tile.set_resolution(3);
+            water_tile.set_resolution(WATER_RESOLUTION);
 
             water_tile.corners[0].y = water_altitude;
             water_tile.corners[1].y = water_altitude;
@@ -611,7 +812,7 @@ fn generate_tile_surfaces(
             let tile_height = f32::from(tile_height);
 
             water_tile.set_kind(TileSurfaceType::Water);
-            water_tile.set_resolution(3);
+            water_tile.set_resolution(WATER_RESOLUTION);
 
             extra_surfaces.push(water_tile);
 
@@ -682,7 +883,7 @@ fn generate_tile_surfaces(
             let tile_height = f32::from(tile_height);
             let mut water_tile = tile_surface.clone();
             water_tile.set_kind(TileSurfaceType::Water);
-            water_tile.set_resolution(3);
+            water_tile.set_resolution(WATER_RESOLUTION);
 
             water_tile.corners[rotation.nw()].y = tile_z;
             water_tile.corners[rotation.ne()].y = tile_z;
@@ -702,6 +903,11 @@ fn generate_tile_surfaces(
         return vec![];
     }
 
+    tile_surface.reduce_resolution(lod);
+    extra_surfaces
+        .iter_mut()
+        .for_each(|surface| surface.reduce_resolution(lod));
+
     let mut tile_faces: Vec<_> = tile_surface.into();
     let water_faces = extra_surfaces.into_iter().flat_map(Vec::<Face>::from);
 
@@ -710,60 +916,117 @@ fn generate_tile_surfaces(
     tile_faces
 }
 
-fn generate_chunk_vertices(context: &WorkerThreadContext, chunk: ChunkConfig) -> ChunkSurfaces {
-    let mut surfaces = SurfaceMap::new();
+/// Level of detail of a tile in the mesh of a chunk. The outer tiles always use the full
+/// resolution to stitch the seams between chunks of different levels.
+fn tile_lod(chunk: &ChunkConfig, (x, y): TileCoords, lod: u8) -> u8 {
+    let (lower_x, lower_y) = chunk.tile_coords;
+    let is_border = x == lower_x
+        || x + 1 == lower_x + chunk.size
+        || y == lower_y
+        || y + 1 == lower_y + chunk.size;
+
+    if is_border {
+        0
+    } else {
+        lod
+    }
+}
 
+fn generate_chunk_vertices(context: &WorkerThreadContext, chunk: ChunkConfig) -> ChunkSurfaces {
     let lower_y = chunk.tile_coords.1;
     let lower_x = chunk.tile_coords.0;
     let upper_y = lower_y + chunk.size;
     let upper_x = lower_x + chunk.size;
 
-    for y in lower_y..upper_y {
-        for x in lower_x..upper_x {
-            let key = (x, y);
-            let tile = context
-                .tilelist
-                .get(&key)
-                .expect("there is a hole in the tilelist!");
+    let lods = (0..TerrainBuilder::LOD_LEVELS)
+        .map(|lod| {
+            let mut surfaces = SurfaceMap::new();
+
+            for y in lower_y..upper_y {
+                for x in lower_x..upper_x {
+                    let key = (x, y);
+                    let tile = context
+                        .tilelist
+                        .get(&key)
+                        .expect("there is a hole in the tilelist!");
+
+                    generate_tile_surfaces(
+                        context,
+                        tile,
+                        context.tilelist,
+                        tile_lod(&chunk, key, lod),
+                    )
+                    .into_iter()
+                    .flatten()
+                    .for_each(|vertex| {
+                        TerrainBuilder::add_to_surface(&mut surfaces, vertex);
+                    });
+                }
+            }
 
-            generate_tile_surfaces(context, tile, context.tilelist)
-                .into_iter()
-                .flatten()
-                .for_each(|vertex| {
-                    TerrainBuilder::add_to_surface(&mut surfaces, vertex);
-                });
-        }
-    }
+            surfaces
+        })
+        .collect();
 
     ChunkSurfaces {
-        surfaces,
+        lods,
         config: chunk,
     }
 }
 
-/// Generate an [`ArrayMesh`] from a list of surface vertecies.
+/// Generate an [`ArrayMesh`] for each level of detail of the chunk.
 fn generate_chunk_mesh(context: &WorkerThreadContext, chunk: ChunkSurfaces) -> TerrainChunk {
+    let mut vertex_count = 0;
+    let lods = chunk
+        .lods
+        .into_iter()
+        .map(|surfaces| {
+            let (mesh, lod_vertex_count) = generate_lod_mesh(context, &chunk.config, surfaces);
+
+            vertex_count += lod_vertex_count;
+            Shared(mesh)
+        })
+        .collect();
+
+    logger::info!(
+        "generated {} vertices for terain in {} levels of detail",
+        vertex_count,
+        TerrainBuilder::LOD_LEVELS
+    );
+
+    TerrainChunk {
+        lods,
+        collision: collision::generate(context, &chunk.config),
+        config: chunk.config,
+    }
+}
+
+/// Generate an [`ArrayMesh`] from a list of surface vertecies. Also returns the number of
+/// vertices.
+fn generate_lod_mesh(
+    context: &WorkerThreadContext,
+    config: &ChunkConfig,
+    surfaces: SurfaceMap,
+) -> (Gd<ArrayMesh>, usize) {
     let mut generator = SurfaceTool::new_gd();
     let mut mesh = ArrayMesh::new_gd();
     let mut vertex_count = 0;
 
-    for (surface_type, surface) in chunk.surfaces {
+    for (surface_type, surface) in surfaces {
         generator.clear();
         generator.begin(PrimitiveType::TRIANGLES);
 
         // calculate global offset. Vertex contains the wold coordinates and we have to subtract the
         // offset to get the model coordinates.
         let world_offset = Vector3 {
-            x: chunk
-                .config
+            x: config
                 .tile_coords
                 .0
                 .to_f32()
                 .expect("tile coords are u32 but should fit in f32")
                 * f32::from(context.tile_size),
             y: 0.0,
-            z: chunk
-                .config
+            z: config
                 .tile_coords
                 .1
                 .to_f32()
@@ -820,12 +1083,84 @@ fn generate_chunk_mesh(context: &WorkerThreadContext, chunk: ChunkSurfaces) -> T
         }
     }
 
-    logger::info!("generated {} vertices for terain", vertex_count);
+    (mesh, vertex_count)
+}
 
-    TerrainChunk {
-        mesh: Shared(mesh),
-        config: chunk.config,
+/// Fixes the slopes of invalid tiles. Tiles with buildings or surface water are only fixed if
+/// they are a special case. Also returns the outcome of the slope solver.
+fn fix_invalid_slopes(
+    rotation: TerrainRotation,
+    chunk_size: u32,
+    mut tilelist: TileList,
+) -> (TileList, SlopeSolverReport) {
+    let chunks: Vec<_> = tilelist
+        .values()
+        .chunks(chunk_size.pow(2) as usize)
+        .into_iter()
+        .map(itertools::Itertools::collect_vec)
+        .collect();
+
+    let mut pending_tiles: Vec<_> = chunks
+        .par_iter()
+        .map(|chunk| {
+            chunk
+                .iter()
+                .map(|tile| (*tile, tilelist.validate_tile_slope(tile, rotation)))
+                .filter_map(|(tile, validation_result)| {
+                    if !validation_result.is_invalid() {
+                        return None;
+                    }
+
+                    if let Some(special_case) = is_special_case(tile, &tilelist, &validation_result)
+                    {
+                        return Some((tile, validation_result, Some(special_case)));
+                    }
+
+                    (tile.has_building() || tile.has_surface_water())
+                        .not()
+                        .then_some((tile, validation_result, None))
+                })
+                .par_bridge()
+        })
+        .flatten()
+        .map(|(tile, invalid, special_case)| (tile.to_owned(), invalid, special_case))
+        .collect::<Vec<_>>()
+        .into_iter()
+        .sorted_by(|(_, a, _), (_, b, _)| a.empty_invalid_tiles.cmp(&b.empty_invalid_tiles))
+        .collect();
+
+    drop(chunks);
+
+    // remove all the invalid tiles first
+    for (original_tile, _, special_case) in &mut pending_tiles {
+        let tile = tilelist
+            .get_mut(&original_tile.coordinates)
+            .expect("we know the tile exists");
+
+        tile.terrain.slope = TerrainSlope::Undetermined;
+
+        match special_case {
+            // bridge transition pieces have to be lowered by one level of altitude.
+            // This also applies to power line cliffs
+            Some(TileSpecialCase::BridgeTransition | TileSpecialCase::PowerlineCliff) => {
+                original_tile.altitude -= 1;
+                tile.altitude -= 1;
+            }
+
+            Some(TileSpecialCase::PintchedAllSlope) => {
+                original_tile.altitude += 1;
+                original_tile.terrain.slope = TerrainSlope::None;
+                tile.altitude += 1;
+            }
+
+            Some(TileSpecialCase::Powerline | TileSpecialCase::Bridge) | None => (),
+        }
     }
+
+    let pending_tiles = pending_tiles.into_iter().map(|(tile, _, _)| tile).collect();
+    let report = slope_solver::solve(&mut tilelist, pending_tiles, &BTreeSet::new(), rotation);
+
+    (tilelist, report)
 }
 
 /// Tile is a special case and requires some additional transformation.
@@ -925,7 +1260,7 @@ fn handle_water_tile_cliff(
 
     let mut water_side = TileSurface::new(TileSurfaceType::Water);
 
-    water_side.set_resolution(3);
+    water_side.set_resolution(WATER_RESOLUTION);
     water_side.set_corners(cliff_corners);
 
     surfaces.push(water_side);
@@ -941,3 +1276,24 @@ fn is_water_cliff(tile: &Tile, neighbor: &Tile) -> bool {
                 TerrainType::SurfaceWater | TerrainType::MoreSurfaceWater
             ) || !matches!(neighbor.terrain.slope, TerrainSlope::VertialCliff))
 }
+
+#[cfg(test)]
+mod test {
+    use super::{tile_lod, ChunkConfig};
+
+    #[test]
+    fn border_tiles_keep_the_full_resolution() {
+        let chunk = ChunkConfig {
+            tile_coords: (8, 16),
+            size: 4,
+        };
+
+        assert_eq!(tile_lod(&chunk, (8, 17), 1), 0);
+        assert_eq!(tile_lod(&chunk, (11, 17), 1), 0);
+        assert_eq!(tile_lod(&chunk, (9, 16), 1), 0);
+        assert_eq!(tile_lod(&chunk, (9, 19), 1), 0);
+        assert_eq!(tile_lod(&chunk, (9, 17), 1), 1);
+        assert_eq!(tile_lod(&chunk, (10, 18), 1), 1);
+        assert_eq!(tile_lod(&chunk, (10, 18), 0), 0);
+    }
+}
@@ -0,0 +1,332 @@
+/*
+ * Copyright (c) SimChopper; Jovan Gerodetti and contributors.
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::collections::BTreeMap;
+
+use godot::builtin::{Basis, PackedFloat32Array, Transform3D, Vector3};
+use godot::classes::{BoxShape3D, CollisionShape3D, HeightMapShape3D};
+use godot::obj::{Gd, NewAlloc, NewGd};
+use num::ToPrimitive;
+
+use super::lerp::bilerp_xyz;
+use super::{ground_surface, tile_altitude, ChunkConfig, WorkerThreadContext};
+use crate::world::city_data::{TerrainSlope, TerrainType, Tile, TileCoords};
+
+/// Number of height samples along each edge of a tile.
+const SAMPLES_PER_TILE: u32 = 2;
+
+type TileCorners = [Vector3; 4];
+
+/// Box that closes the gap of a cliff between two tiles.
+#[derive(Debug, PartialEq)]
+struct Wall {
+    center: Vector3,
+    size: Vector3,
+}
+
+/// Collision geometry of a terrain chunk in the coordinate space of the chunk mesh.
+#[derive(Debug)]
+pub(super) struct ChunkCollision {
+    /// Height samples, row by row along the x-axis.
+    heights: Vec<f32>,
+    /// Number of samples along each axis.
+    samples: u32,
+    /// Distance between two samples in meters.
+    spacing: f32,
+    walls: Vec<Wall>,
+}
+
+impl ChunkCollision {
+    /// Creates the collision shapes of the chunk. Has to be called on the main thread.
+    pub(super) fn create_shapes(&self) -> Vec<Gd<CollisionShape3D>> {
+        let samples = i32::try_from(self.samples).expect("chunk sample count should fit into i32");
+        let extent = (self.samples - 1).to_f32().unwrap_or(f32::MAX) * self.spacing;
+
+        // height map shapes are centered and have a cell size of one unit, the shape is scaled
+        // uniformly to the sample spacing.
+        let mut height_map = HeightMapShape3D::new_gd();
+        height_map.set_map_width(samples);
+        height_map.set_map_depth(samples);
+        height_map.set_map_data(
+            &self
+                .heights
+                .iter()
+                .map(|height| height / self.spacing)
+                .collect::<PackedFloat32Array>(),
+        );
+
+        let mut ground = CollisionShape3D::new_alloc();
+        ground.set_shape(&height_map);
+        ground.set_transform(Transform3D::new(
+            Basis::from_scale(Vector3::splat(self.spacing)),
+            Vector3::new(extent / 2.0, 0.0, extent / 2.0),
+        ));
+
+        let walls = self.walls.iter().map(|wall| {
+            let mut shape = BoxShape3D::new_gd();
+            shape.set_size(wall.size);
+
+            let mut node = CollisionShape3D::new_alloc();
+            node.set_shape(&shape);
+            node.set_position(wall.center);
+            node
+        });
+
+        std::iter::once(ground).chain(walls).collect()
+    }
+}
+
+/// Corners of the topmost surface of a tile, which is the water surface for tiles that are
+/// covered by water.
+fn collision_corners(context: &WorkerThreadContext, tile: &Tile) -> TileCorners {
+    let mut corners = ground_surface(context, tile).corners;
+
+    let water_level = match tile.terrain.ty {
+        TerrainType::Underwater | TerrainType::Shoreline
+            if context.render_water && u32::from(context.sea_level) >= tile.altitude() =>
+        {
+            Some(f32::from(
+                u16::from(context.tile_height) * context.sea_level,
+            ))
+        }
+        TerrainType::SurfaceWater if tile.terrain.slope == TerrainSlope::VertialCliff => None,
+        TerrainType::SurfaceWater | TerrainType::MoreSurfaceWater => {
+            Some(tile_altitude(context, tile))
+        }
+        TerrainType::DryLand | TerrainType::Underwater | TerrainType::Shoreline => None,
+    };
+
+    if let Some(water_level) = water_level {
+        corners.iter_mut().for_each(|corner| corner.y = water_level);
+    }
+
+    corners
+}
+
+/// Generates the heightfield and cliff walls of a chunk.
+pub(super) fn generate(context: &WorkerThreadContext, chunk: &ChunkConfig) -> ChunkCollision {
+    let (lower_x, lower_y) = chunk.tile_coords;
+
+    // the tiles around the chunk are required for the shared edges.
+    let corners: BTreeMap<_, _> = (lower_x.saturating_sub(1)..=lower_x + chunk.size)
+        .flat_map(|x| (lower_y.saturating_sub(1)..=lower_y + chunk.size).map(move |y| (x, y)))
+        .filter_map(|coords| context.tilelist.get(&coords))
+        .map(|tile| (tile.coordinates, collision_corners(context, tile)))
+        .collect();
+
+    build(&corners, chunk, f32::from(context.tile_size))
+}
+
+/// Tiles that touch a sample along one axis, with the weight of the sample inside each tile.
+fn touching_tiles(origin: u32, sample: u32) -> impl Iterator<Item = (u32, f32)> {
+    let tile = origin + sample / SAMPLES_PER_TILE;
+    let step = sample % SAMPLES_PER_TILE;
+    let weight = step.to_f32().unwrap_or(0.0) / SAMPLES_PER_TILE.to_f32().unwrap_or(1.0);
+
+    let touching = match (step, tile.checked_sub(1)) {
+        (0, Some(previous)) => [Some((previous, 1.0)), Some((tile, 0.0))],
+        _ => [Some((tile, weight)), None],
+    };
+
+    touching.into_iter().flatten()
+}
+
+fn build(
+    corners: &BTreeMap<TileCoords, TileCorners>,
+    chunk: &ChunkConfig,
+    tile_size: f32,
+) -> ChunkCollision {
+    let (lower_x, lower_y) = chunk.tile_coords;
+    let samples = chunk.size * SAMPLES_PER_TILE + 1;
+    let spacing = tile_size / SAMPLES_PER_TILE.to_f32().unwrap_or(1.0);
+
+    // samples on the edge between two tiles use the lower tile, the cliff walls cover the rest.
+    let heights = (0..samples)
+        .flat_map(|sz| (0..samples).map(move |sx| (sx, sz)))
+        .map(|(sx, sz)| {
+            touching_tiles(lower_x, sx)
+                .flat_map(|(x, weight_x)| {
+                    touching_tiles(lower_y, sz)
+                        .map(move |(y, weight_y)| ((x, y), weight_x, weight_y))
+                })
+                .filter_map(|(coords, weight_x, weight_y)| {
+                    corners
+                        .get(&coords)
+                        .map(|corners| bilerp_xyz(corners, weight_x, weight_y).y)
+                })
+                .reduce(f32::min)
+                .unwrap_or(0.0)
+        })
+        .collect();
+
+    let offset = Vector3::new(
+        lower_x.to_f32().unwrap_or(f32::MAX) * tile_size,
+        0.0,
+        lower_y.to_f32().unwrap_or(f32::MAX) * tile_size,
+    );
+
+    let walls = corners
+        .iter()
+        .filter(|((x, y), _)| {
+            (lower_x..lower_x + chunk.size).contains(x)
+                && (lower_y..lower_y + chunk.size).contains(y)
+        })
+        .flat_map(|(coords, tile)| cliff_walls(corners, *coords, tile, spacing))
+        .map(|wall| Wall {
+            center: wall.center - offset,
+            size: wall.size,
+        })
+        .collect();
+
+    ChunkCollision {
+        heights,
+        samples,
+        spacing,
+        walls,
+    }
+}
+
+/// Walls along every edge of the tile that rises above its neighbor. The walls are placed inside
+/// the tile, so each cliff is only covered once.
+fn cliff_walls(
+    corners: &BTreeMap<TileCoords, TileCorners>,
+    (x, y): TileCoords,
+    tile: &TileCorners,
+    depth: f32,
+) -> Vec<Wall> {
+    // corner indices of the tile edge and the matching edge of the neighbor.
+    let edges = [
+        (x.checked_sub(1).map(|x| (x, y)), [0, 2], [1, 3]),
+        (Some((x + 1, y)), [1, 3], [0, 2]),
+        (y.checked_sub(1).map(|y| (x, y)), [0, 1], [2, 3]),
+        (Some((x, y + 1)), [2, 3], [0, 1]),
+    ];
+
+    edges
+        .into_iter()
+        .filter_map(|(neighbor, edge, neighbor_edge)| {
+            let neighbor = corners.get(&neighbor?)?;
+            let top = tile[edge[0]].y.min(tile[edge[1]].y);
+            let bottom = neighbor[neighbor_edge[0]]
+                .y
+                .min(neighbor[neighbor_edge[1]].y);
+            let neighbor_top = neighbor[neighbor_edge[0]]
+                .y
+                .max(neighbor[neighbor_edge[1]].y);
+
+            if top <= neighbor_top {
+                return None;
+            }
+
+            let start = tile[edge[0]];
+            let end = tile[edge[1]];
+            let along = end - start;
+            // points from the edge towards the center of the tile.
+            let inward = tile[neighbor_edge[0]] - start;
+            let inward = Vector3::new(inward.x, 0.0, inward.z).normalized() * depth;
+            let center = start + along / 2.0 + inward / 2.0;
+
+            Some(Wall {
+                center: Vector3::new(center.x, (top + bottom) / 2.0, center.z),
+                size: Vector3::new(
+                    along.x.abs() + inward.x.abs(),
+                    top - bottom,
+                    along.z.abs() + inward.z.abs(),
+                ),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use godot::builtin::Vector3;
+
+    use super::{build, TileCorners, Wall};
+    use crate::terrain_builder::ChunkConfig;
+    use crate::world::city_data::TileCoords;
+
+    const TILE_SIZE: f32 = 16.0;
+
+    fn flat_tile((x, y): TileCoords, height: f32) -> TileCorners {
+        #[expect(clippy::cast_precision_loss)]
+        let (x, y) = (x as f32 * TILE_SIZE, y as f32 * TILE_SIZE);
+
+        [
+            Vector3::new(x, height, y),
+            Vector3::new(x + TILE_SIZE, height, y),
+            Vector3::new(x, height, y + TILE_SIZE),
+            Vector3::new(x + TILE_SIZE, height, y + TILE_SIZE),
+        ]
+    }
+
+    fn flat_corners(size: u32, height: f32) -> BTreeMap<TileCoords, TileCorners> {
+        (0..size)
+            .flat_map(|x| (0..size).map(move |y| (x, y)))
+            .map(|coords| (coords, flat_tile(coords, height)))
+            .collect()
+    }
+
+    #[test]
+    fn flat_chunk_has_no_walls() {
+        let chunk = ChunkConfig {
+            tile_coords: (0, 0),
+            size: 2,
+        };
+
+        let collision = build(&flat_corners(2, 8.0), &chunk, TILE_SIZE);
+
+        assert_eq!(collision.samples, 5);
+        assert_eq!(collision.heights.len(), 25);
+        assert!(collision
+            .heights
+            .iter()
+            .all(|height| (height - 8.0).abs() < f32::EPSILON));
+        assert!(collision.walls.is_empty());
+    }
+
+    #[test]
+    fn sloped_tile_is_interpolated() {
+        let chunk = ChunkConfig {
+            tile_coords: (0, 0),
+            size: 1,
+        };
+        let mut tile = flat_tile((0, 0), 0.0);
+
+        tile[1].y = 8.0;
+        tile[3].y = 8.0;
+
+        let collision = build(&BTreeMap::from([((0, 0), tile)]), &chunk, TILE_SIZE);
+
+        assert_eq!(&collision.heights[0..3], &[0.0, 4.0, 8.0]);
+    }
+
+    #[test]
+    fn raised_tile_has_walls_on_the_inside() {
+        let chunk = ChunkConfig {
+            tile_coords: (0, 0),
+            size: 3,
+        };
+        let mut corners = flat_corners(3, 0.0);
+
+        corners.insert((1, 1), flat_tile((1, 1), 8.0));
+
+        let collision = build(&corners, &chunk, TILE_SIZE);
+
+        // the shared edge uses the lower tile, the center of the tile is raised.
+        assert!(collision.heights[2 * 7 + 2].abs() < f32::EPSILON);
+        assert!((collision.heights[3 * 7 + 3] - 8.0).abs() < f32::EPSILON);
+
+        assert_eq!(collision.walls.len(), 4);
+        assert!(collision.walls.contains(&Wall {
+            center: Vector3::new(20.0, 4.0, 24.0),
+            size: Vector3::new(8.0, 8.0, 16.0),
+        }));
+    }
+}
@@ -0,0 +1,609 @@
+/*
+ * Copyright (c) SimChopper; Jovan Gerodetti and contributors.
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::ops::RangeInclusive;
+
+use itertools::Itertools;
+
+use super::TerrainRotation;
+use crate::world::city_data::{Compass, TerrainSlope, Tile, TileCoords, TileList, TileListExt};
+
+/// Maximum number of altitude levels an empty tile may be raised or lowered to fit its neighbors.
+const MAX_ALTITUDE_ADJUSTMENT: u32 = 1;
+
+/// Maximum number of assignments that are tried for a single region of tiles before giving up.
+const MAX_STEPS: usize = 50_000;
+
+/// Slope and altitude of a tile. Slopes are always normalized to the terrain rotation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Candidate {
+    slope: TerrainSlope,
+    altitude: u32,
+}
+
+impl Candidate {
+    fn of(tile: &Tile, rotation: TerrainRotation) -> Self {
+        Self {
+            slope: rotation.normalize_slope(tile.terrain.slope),
+            altitude: tile.altitude,
+        }
+    }
+
+    /// Whether the slope table of this tile allows the other tile in the given direction.
+    fn accepts(self, direction: Compass, other: Self) -> bool {
+        let Ok(offset) = i8::try_from(i64::from(other.altitude) - i64::from(self.altitude)) else {
+            return false;
+        };
+
+        self.slope
+            .valid_neighbors(direction)
+            .iter()
+            .any(|(slope, alt)| *slope == other.slope && *alt == offset)
+    }
+
+    /// Two neighboring tiles only leave no hole in the mesh if both accept each other.
+    fn fits(self, direction: Compass, other: Self) -> bool {
+        self.accepts(direction, other) && other.accepts(direction.opposite(), self)
+    }
+}
+
+/// Why the slope of a tile could not be resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum UnresolvedReason {
+    /// No slope at any of the allowed altitudes fits the surrounding tiles.
+    NoCandidates,
+    /// The tile is part of a region of tiles that contradict each other.
+    Conflict,
+    /// The search for the region of the tile has been aborted after too many steps.
+    StepLimit,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct UnresolvedTile {
+    pub coordinates: TileCoords,
+    pub reason: UnresolvedReason,
+}
+
+/// Outcome of [`solve`].
+#[derive(Debug, Default)]
+pub(crate) struct SlopeSolverReport {
+    /// Number of tiles that received a slope which fits all of their neighbors.
+    pub resolved: usize,
+    /// Resolved tiles that had to be raised or lowered.
+    pub adjusted: Vec<TileCoords>,
+    /// Tiles that still do not fit their neighbors. They keep the closest slope that the
+    /// surrounding tiles allow, or their original slope if there is none.
+    pub unresolved: Vec<UnresolvedTile>,
+    /// Tiles that were not pending, but received a new slope to make room for a pending neighbor.
+    pub widened: Vec<TileCoords>,
+}
+
+/// Possible assignments of a pending tile.
+struct Domain {
+    /// Candidates that fit all fixed neighbors in both directions, closest altitude first.
+    strict: Vec<Candidate>,
+    /// Closest candidate that is at least accepted by all fixed neighbors.
+    fallback: Option<Candidate>,
+}
+
+/// Assigns a slope to every pending tile, so that it fits all of its neighbors.
+///
+/// The pending tiles are treated as variables of a constraint satisfaction problem, the slope
+/// tables of [`TerrainSlope`] are the constraints between neighboring tiles. Empty tiles may be
+/// raised or lowered by [`MAX_ALTITUDE_ADJUSTMENT`]. Connected regions of pending tiles are solved
+/// independently through backtracking with forward checking.
+///
+/// `pending` contains the tiles as they have been before their slope was removed from the tile
+/// list. If a pending tile has no candidate at all, its neighbors without buildings or surface
+/// water become pending as well, so they can be raised or lowered to make room for it. Tiles that
+/// can still not be resolved fall back to the best partial fit and are reported. `pinned` tiles
+/// keep their altitude, like tiles with buildings, and are never added.
+pub(crate) fn solve(
+    tilelist: &mut TileList,
+    pending: Vec<Tile>,
+    pinned: &BTreeSet<TileCoords>,
+    rotation: TerrainRotation,
+) -> SlopeSolverReport {
+    let mut report = SlopeSolverReport::default();
+    let mut originals: BTreeMap<TileCoords, Tile> = pending
+        .into_iter()
+        .map(|tile| (tile.coordinates, tile))
+        .collect();
+
+    // neighbors that have been added to make room for a tile without candidates.
+    let mut added = BTreeSet::new();
+    // tiles whose neighbors have already been added.
+    let mut expanded = BTreeSet::new();
+
+    // tiles without any candidate first add their neighbors to the pending tiles. If that does not
+    // help, they become fixed, which changes the domains of their neighbors.
+    let domains = loop {
+        let variables: BTreeSet<_> = originals.keys().copied().collect();
+        let domains: BTreeMap<_, _> = variables
+            .iter()
+            .filter_map(|coords| tilelist.get(coords))
+            .map(|tile| {
+                (
+                    tile.coordinates,
+                    domain(tilelist, &variables, pinned, tile, rotation),
+                )
+            })
+            .collect();
+
+        let empty: Vec<_> = domains
+            .iter()
+            .filter(|(_, domain)| domain.strict.is_empty())
+            .map(|(coords, _)| *coords)
+            .collect();
+
+        if empty.is_empty() {
+            break domains;
+        }
+
+        let mut grown = false;
+
+        for coords in &empty {
+            if added.contains(coords) || !expanded.insert(*coords) {
+                continue;
+            }
+
+            for original in free_neighbors(tilelist, &variables, pinned, *coords) {
+                if let Some(tile) = tilelist.get_mut(&original.coordinates) {
+                    tile.terrain.slope = TerrainSlope::Undetermined;
+                }
+
+                added.insert(original.coordinates);
+                originals.insert(original.coordinates, original);
+                grown = true;
+            }
+        }
+
+        if grown {
+            continue;
+        }
+
+        for coords in empty {
+            let Some(original) = originals.remove(&coords) else {
+                continue;
+            };
+
+            // added neighbors have not been asked to be resolved, they keep their slope.
+            if added.remove(&coords) {
+                restore(tilelist, original, None, rotation);
+                continue;
+            }
+
+            restore(tilelist, original, domains[&coords].fallback, rotation);
+            report.unresolved.push(UnresolvedTile {
+                coordinates: coords,
+                reason: UnresolvedReason::NoCandidates,
+            });
+        }
+    };
+
+    let neighbors: BTreeMap<_, Vec<_>> = originals
+        .keys()
+        .filter_map(|coords| tilelist.get(coords))
+        .map(|tile| {
+            let neighbors = tilelist
+                .get_tile_neighbors(tile)
+                .filter(|(_, neighbor)| originals.contains_key(&neighbor.coordinates))
+                .map(|(dir, neighbor)| (dir, neighbor.coordinates))
+                .collect();
+
+            (tile.coordinates, neighbors)
+        })
+        .collect();
+
+    for region in regions(&neighbors) {
+        let region_domains = region
+            .iter()
+            .map(|coords| (*coords, domains[coords].strict.clone()))
+            .collect();
+
+        match solve_region(region_domains, &neighbors) {
+            Ok(assignment) => {
+                for (coords, candidate) in assignment {
+                    let Some(tile) = tilelist.get_mut(&coords) else {
+                        continue;
+                    };
+
+                    if added.contains(&coords) {
+                        apply(tile, candidate, rotation);
+                        report.widened.push(coords);
+                        continue;
+                    }
+
+                    if tile.altitude != candidate.altitude {
+                        report.adjusted.push(coords);
+                    }
+
+                    apply(tile, candidate, rotation);
+                    report.resolved += 1;
+                }
+            }
+
+            Err(reason) => {
+                for coords in region {
+                    let Some(original) = originals.remove(&coords) else {
+                        continue;
+                    };
+
+                    if added.contains(&coords) {
+                        restore(tilelist, original, None, rotation);
+                        continue;
+                    }
+
+                    restore(tilelist, original, domains[&coords].fallback, rotation);
+                    report.unresolved.push(UnresolvedTile {
+                        coordinates: coords,
+                        reason,
+                    });
+                }
+            }
+        }
+    }
+
+    report
+}
+
+/// Altitudes a pending tile can take. Pinned tiles and tiles with buildings stay at their altitude.
+fn altitudes(tile: &Tile, pinned: bool) -> RangeInclusive<u32> {
+    if pinned || tile.has_building() {
+        return tile.altitude..=tile.altitude;
+    }
+
+    tile.altitude.saturating_sub(MAX_ALTITUDE_ADJUSTMENT)..=tile.altitude + MAX_ALTITUDE_ADJUSTMENT
+}
+
+/// Neighbors of a tile that are not pending yet and may be raised or lowered. Returns the
+/// neighbors as they are in the tile list.
+fn free_neighbors(
+    tilelist: &TileList,
+    variables: &BTreeSet<TileCoords>,
+    pinned: &BTreeSet<TileCoords>,
+    coords: TileCoords,
+) -> Vec<Tile> {
+    let Some(tile) = tilelist.get(&coords) else {
+        return Vec::new();
+    };
+
+    tilelist
+        .get_tile_neighbors(tile)
+        .map(|(_, neighbor)| neighbor)
+        .filter(|neighbor| {
+            !variables.contains(&neighbor.coordinates)
+                && !pinned.contains(&neighbor.coordinates)
+                && !neighbor.has_building()
+                && !neighbor.has_surface_water()
+        })
+        .cloned()
+        .collect()
+}
+
+fn domain(
+    tilelist: &TileList,
+    variables: &BTreeSet<TileCoords>,
+    pinned: &BTreeSet<TileCoords>,
+    tile: &Tile,
+    rotation: TerrainRotation,
+) -> Domain {
+    let fixed: Vec<_> = tilelist
+        .get_tile_neighbors(tile)
+        .filter(|(_, neighbor)| !variables.contains(&neighbor.coordinates))
+        .map(|(dir, neighbor)| (dir, Candidate::of(neighbor, rotation)))
+        .collect();
+
+    let range = altitudes(tile, pinned.contains(&tile.coordinates));
+
+    let options: Vec<_> = match fixed.first() {
+        Some((dir, neighbor)) => neighbor
+            .slope
+            .valid_neighbors(dir.opposite())
+            .iter()
+            .filter_map(|(slope, offset)| {
+                Some(Candidate {
+                    slope: *slope,
+                    altitude: neighbor.altitude.checked_add_signed(i32::from(*offset))?,
+                })
+            })
+            .collect(),
+
+        // without fixed neighbors every slope is possible that fits next to an undetermined tile.
+        None => range
+            .clone()
+            .flat_map(|altitude| {
+                TerrainSlope::Undetermined
+                    .valid_neighbors(Compass::North)
+                    .iter()
+                    .map(move |(slope, _)| Candidate {
+                        slope: *slope,
+                        altitude,
+                    })
+            })
+            .collect(),
+    };
+
+    let mut relaxed: Vec<_> = options
+        .into_iter()
+        .filter(|candidate| range.contains(&candidate.altitude))
+        .filter(|candidate| {
+            fixed
+                .iter()
+                .all(|(dir, neighbor)| neighbor.accepts(dir.opposite(), *candidate))
+        })
+        .unique()
+        .collect();
+
+    relaxed.sort_by_key(|candidate| candidate.altitude.abs_diff(tile.altitude));
+
+    let fallback = relaxed.first().copied();
+    let strict = relaxed
+        .into_iter()
+        .filter(|candidate| {
+            fixed
+                .iter()
+                .all(|(dir, neighbor)| candidate.accepts(*dir, *neighbor))
+        })
+        .collect();
+
+    Domain { strict, fallback }
+}
+
+/// Connected regions of pending tiles.
+fn regions(neighbors: &BTreeMap<TileCoords, Vec<(Compass, TileCoords)>>) -> Vec<Vec<TileCoords>> {
+    let mut visited = BTreeSet::new();
+    let mut regions = Vec::new();
+
+    for start in neighbors.keys() {
+        if !visited.insert(*start) {
+            continue;
+        }
+
+        let mut region = Vec::new();
+        let mut queue = VecDeque::from([*start]);
+
+        while let Some(coords) = queue.pop_front() {
+            region.push(coords);
+
+            for (_, neighbor) in neighbors.get(&coords).into_iter().flatten() {
+                if visited.insert(*neighbor) {
+                    queue.push_back(*neighbor);
+                }
+            }
+        }
+
+        regions.push(region);
+    }
+
+    regions
+}
+
+/// A tile that has been assigned during the search.
+struct Decision {
+    coords: TileCoords,
+    options: Vec<Candidate>,
+    next: usize,
+    /// Domains of neighbors before they have been narrowed down by the current option.
+    narrowed: Vec<(TileCoords, Vec<Candidate>)>,
+}
+
+/// Backtracking search over a region. The tile with the fewest remaining candidates is assigned
+/// next and the candidates of its neighbors are narrowed down right away.
+fn solve_region(
+    mut domains: BTreeMap<TileCoords, Vec<Candidate>>,
+    neighbors: &BTreeMap<TileCoords, Vec<(Compass, TileCoords)>>,
+) -> Result<BTreeMap<TileCoords, Candidate>, UnresolvedReason> {
+    let mut assignment = BTreeMap::new();
+    let mut decisions: Vec<Decision> = Vec::new();
+    let mut steps = 0;
+
+    loop {
+        let next = domains
+            .iter()
+            .filter(|(coords, _)| !assignment.contains_key(*coords))
+            .min_by_key(|(_, options)| options.len());
+
+        let Some((coords, options)) = next else {
+            return Ok(assignment);
+        };
+
+        decisions.push(Decision {
+            coords: *coords,
+            options: options.clone(),
+            next: 0,
+            narrowed: Vec::new(),
+        });
+
+        loop {
+            let Some(decision) = decisions.last_mut() else {
+                return Err(UnresolvedReason::Conflict);
+            };
+
+            // undo the previous option of this decision.
+            assignment.remove(&decision.coords);
+
+            for (coords, domain) in decision.narrowed.drain(..).rev() {
+                domains.insert(coords, domain);
+            }
+
+            let Some(candidate) = decision.options.get(decision.next).copied() else {
+                decisions.pop();
+                continue;
+            };
+
+            steps += 1;
+
+            if steps > MAX_STEPS {
+                return Err(UnresolvedReason::StepLimit);
+            }
+
+            decision.next += 1;
+            assignment.insert(decision.coords, candidate);
+
+            let mut exhausted = false;
+
+            for (dir, neighbor) in neighbors.get(&decision.coords).into_iter().flatten() {
+                if assignment.contains_key(neighbor) {
+                    continue;
+                }
+
+                let Some(domain) = domains.get_mut(neighbor) else {
+                    continue;
+                };
+
+                let remaining: Vec<_> = domain
+                    .iter()
+                    .copied()
+                    .filter(|option| candidate.fits(*dir, *option))
+                    .collect();
+
+                if remaining.len() == domain.len() {
+                    continue;
+                }
+
+                decision
+                    .narrowed
+                    .push((*neighbor, std::mem::replace(domain, remaining)));
+
+                if domain.is_empty() {
+                    exhausted = true;
+                    break;
+                }
+            }
+
+            if !exhausted {
+                break;
+            }
+        }
+    }
+}
+
+fn apply(tile: &mut Tile, candidate: Candidate, rotation: TerrainRotation) {
+    tile.terrain.slope = rotation.to_reverted().normalize_slope(candidate.slope);
+    tile.altitude = candidate.altitude;
+}
+
+fn restore(
+    tilelist: &mut TileList,
+    mut original: Tile,
+    fallback: Option<Candidate>,
+    rotation: TerrainRotation,
+) {
+    if let Some(candidate) = fallback {
+        apply(&mut original, candidate, rotation);
+    }
+
+    tilelist.insert(original.coordinates, original);
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeSet;
+
+    use crate::objects::scene_object_registry::Buildings;
+    use crate::terrain_builder::TerrainRotation;
+    use crate::world::city_data::fixtures::{city_tiles, flat_tilelist, tile};
+    use crate::world::city_data::{TerrainSlope, TileCoords, TileList, TileListExt};
+
+    use super::{solve, UnresolvedReason};
+
+    /// Removes the slopes of the pending tiles like the terrain builder does and solves them.
+    fn solve_pending(tilelist: &mut TileList, pending: &[TileCoords]) -> super::SlopeSolverReport {
+        let originals = pending
+            .iter()
+            .filter_map(|coords| {
+                let tile = tilelist.get_mut(coords)?;
+                let original = tile.clone();
+
+                tile.terrain.slope = TerrainSlope::Undetermined;
+                Some(original)
+            })
+            .collect();
+
+        solve(
+            tilelist,
+            originals,
+            &BTreeSet::new(),
+            TerrainRotation::default(),
+        )
+    }
+
+    fn is_valid(tilelist: &TileList) -> bool {
+        tilelist.values().all(|tile| {
+            !tilelist
+                .validate_tile_slope(tile, TerrainRotation::default())
+                .is_invalid()
+        })
+    }
+
+    #[test]
+    fn broken_tile_is_flattened() {
+        let mut tilelist = flat_tilelist(3, 2);
+
+        tilelist.insert((1, 1), tile((1, 1), 2, TerrainSlope::North));
+
+        let report = solve_pending(&mut tilelist, &[(1, 1)]);
+
+        assert_eq!(report.resolved, 1);
+        assert!(report.unresolved.is_empty());
+        assert_eq!(tilelist[&(1, 1)].terrain.slope, TerrainSlope::None);
+        assert!(is_valid(&tilelist));
+    }
+
+    #[test]
+    fn connected_tiles_are_solved_together() {
+        let mut tilelist = flat_tilelist(5, 2);
+        let pending = [(1, 1), (2, 1), (2, 2), (3, 2)];
+
+        for coords in pending {
+            tilelist.insert(coords, tile(coords, 3, TerrainSlope::SouthWest));
+        }
+
+        let report = solve_pending(&mut tilelist, &pending);
+
+        assert_eq!(report.resolved, pending.len());
+        assert!(report.unresolved.is_empty());
+        assert!(is_valid(&tilelist));
+    }
+
+    #[test]
+    fn neighbors_make_room_for_a_tile_without_candidates() {
+        let mut tilelist = flat_tilelist(3, 2);
+
+        // the corners of the tile are shared by neighbors at different altitudes.
+        tilelist.insert((1, 2), tile((1, 2), 3, TerrainSlope::None));
+        tilelist.insert((1, 1), tile((1, 1), 2, TerrainSlope::North));
+
+        let report = solve_pending(&mut tilelist, &[(1, 1)]);
+
+        assert_eq!(report.resolved, 1);
+        assert!(report.unresolved.is_empty());
+        assert!(!report.widened.is_empty());
+        assert!(is_valid(&tilelist));
+    }
+
+    #[test]
+    fn tile_between_cliffs_is_reported() {
+        // the surrounding tiles belong to a building, so they can not make room for the tile.
+        let mut tilelist = city_tiles(&[((0, 2), Buildings::HomeLowerClass1 as u8, 3)]);
+
+        for tile in tilelist.values_mut() {
+            tile.terrain.slope = TerrainSlope::VertialCliff;
+        }
+
+        tilelist.insert((1, 1), tile((1, 1), 0, TerrainSlope::North));
+
+        let report = solve_pending(&mut tilelist, &[(1, 1)]);
+
+        assert_eq!(report.resolved, 0);
+        assert_eq!(report.unresolved.len(), 1);
+        assert_eq!(report.unresolved[0].reason, UnresolvedReason::NoCandidates);
+        assert_eq!(tilelist[&(1, 1)].terrain.slope, TerrainSlope::North);
+    }
+}
@@ -11,7 +11,7 @@ use crate::world::city_data::TerrainSlope;
 
 const TERAIN_ROTATION_CORNERS: [u8; 4] = [0, 1, 3, 2];
 
-#[derive(GodotClass, Clone, Copy)]
+#[derive(GodotClass, Clone, Copy, Default)]
 #[class(base=RefCounted, init)]
 pub(crate) struct TerrainRotation {
     offset: u8,
@@ -27,6 +27,11 @@ impl TerrainRotation {
 }
 
 impl TerrainRotation {
+    /// The rotation of a city with the given compass, the same one the terrain node sets up.
+    pub fn from_compass(compass: u8) -> Self {
+        Self { offset: compass }
+    }
+
     fn get_corner(self, index: u8) -> u8 {
         let shifted_index = ((index + self.offset) % 4) as usize;
         let target_value = TERAIN_ROTATION_CORNERS.get(shifted_index).unwrap_or(&0);
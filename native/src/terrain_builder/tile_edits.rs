@@ -0,0 +1,203 @@
+/*
+ * Copyright (c) SimChopper; Jovan Gerodetti and contributors.
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::slope_solver::{self, SlopeSolverReport};
+use super::TerrainRotation;
+use crate::world::city_data::{TerrainSlope, TerrainType, TileCoords, TileList, TileListExt};
+
+/// Change of a single tile during play, e.g. a crater or a flood.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct TileEdit {
+    pub altitude: Option<u32>,
+    pub terrain_type: Option<TerrainType>,
+}
+
+/// Tile edits that wait for a rebuild.
+///
+/// Only one rebuild runs at a time. Edits that are made while a rebuild is running are kept for
+/// the next rebuild, which starts from the tiles of the running one.
+#[derive(Debug, Default)]
+pub(crate) struct EditQueue {
+    pending: BTreeMap<TileCoords, TileEdit>,
+    running: bool,
+}
+
+impl EditQueue {
+    /// The pending edit of a tile.
+    pub fn edit(&mut self, coords: TileCoords) -> &mut TileEdit {
+        self.pending.entry(coords).or_default()
+    }
+
+    /// Takes the pending edits for a new rebuild, unless a rebuild is still running.
+    pub fn start(&mut self) -> Option<BTreeMap<TileCoords, TileEdit>> {
+        if self.running {
+            return None;
+        }
+
+        self.running = true;
+        Some(std::mem::take(&mut self.pending))
+    }
+
+    /// Marks the running rebuild as finished.
+    pub fn finish(&mut self) {
+        self.running = false;
+    }
+}
+
+/// The tile and its eight neighbors.
+pub(super) fn surrounding((x, y): TileCoords) -> impl Iterator<Item = TileCoords> {
+    (x.saturating_sub(1)..=x.saturating_add(1))
+        .flat_map(move |x| (y.saturating_sub(1)..=y.saturating_add(1)).map(move |y| (x, y)))
+}
+
+/// Applies the edits to the tiles and fixes the slopes around them.
+///
+/// Edited tiles keep their new altitude. Their neighbors only receive a new slope if they no
+/// longer fit, the slope solver may raise or lower them. Returns all tiles whose surface has
+/// changed.
+pub(crate) fn apply(
+    tilelist: &mut TileList,
+    edits: &BTreeMap<TileCoords, TileEdit>,
+    rotation: TerrainRotation,
+) -> (BTreeSet<TileCoords>, SlopeSolverReport) {
+    for (coords, edit) in edits {
+        let Some(tile) = tilelist.get_mut(coords) else {
+            continue;
+        };
+
+        if let Some(altitude) = edit.altitude {
+            tile.altitude = altitude;
+        }
+
+        if let Some(terrain_type) = edit.terrain_type {
+            tile.terrain.ty = terrain_type;
+        }
+    }
+
+    let pinned: BTreeSet<_> = edits
+        .keys()
+        .filter(|coords| tilelist.contains_key(coords))
+        .copied()
+        .collect();
+
+    // neighbors with buildings or surface water are left alone, like while building the terrain.
+    let pending: Vec<_> = pinned
+        .iter()
+        .flat_map(|coords| surrounding(*coords))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .filter_map(|coords| tilelist.get(&coords))
+        .filter(|tile| {
+            pinned.contains(&tile.coordinates)
+                || !tile.has_building()
+                    && !tile.has_surface_water()
+                    && tilelist.validate_tile_slope(tile, rotation).is_invalid()
+        })
+        .cloned()
+        .collect();
+
+    let mut changed: BTreeSet<_> = pinned
+        .iter()
+        .copied()
+        .chain(pending.iter().map(|tile| tile.coordinates))
+        .collect();
+
+    for tile in &pending {
+        if let Some(tile) = tilelist.get_mut(&tile.coordinates) {
+            tile.terrain.slope = TerrainSlope::Undetermined;
+        }
+    }
+
+    let report = slope_solver::solve(tilelist, pending, &pinned, rotation);
+
+    // the solver may have changed neighbors to make room for the pending tiles.
+    changed.extend(report.widened.iter().copied());
+
+    (changed, report)
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use crate::terrain_builder::TerrainRotation;
+    use crate::world::city_data::fixtures::flat_tilelist;
+    use crate::world::city_data::{TerrainSlope, TerrainType, TileListExt};
+
+    use super::{apply, EditQueue, TileEdit};
+
+    #[test]
+    fn lowered_tile_keeps_its_altitude() {
+        let mut tilelist = flat_tilelist(5, 4);
+        let edits = BTreeMap::from([(
+            (2, 2),
+            TileEdit {
+                altitude: Some(3),
+                ..TileEdit::default()
+            },
+        )]);
+
+        let (changed, report) = apply(&mut tilelist, &edits, TerrainRotation::default());
+
+        assert!(report.unresolved.is_empty());
+        assert!(changed.contains(&(2, 2)));
+        assert_eq!(tilelist[&(2, 2)].altitude, 3);
+        assert!(tilelist.values().all(|tile| !tilelist
+            .validate_tile_slope(tile, TerrainRotation::default())
+            .is_invalid()));
+    }
+
+    #[test]
+    fn changed_terrain_type_does_not_touch_neighbors() {
+        let mut tilelist = flat_tilelist(3, 4);
+        let edits = BTreeMap::from([(
+            (1, 1),
+            TileEdit {
+                terrain_type: Some(TerrainType::Shoreline),
+                ..TileEdit::default()
+            },
+        )]);
+
+        let (changed, _) = apply(&mut tilelist, &edits, TerrainRotation::default());
+
+        assert_eq!(changed.into_iter().collect::<Vec<_>>(), vec![(1, 1)]);
+        assert_eq!(tilelist[&(1, 1)].terrain.ty, TerrainType::Shoreline);
+        assert_eq!(tilelist[&(1, 1)].terrain.slope, TerrainSlope::None);
+    }
+
+    #[test]
+    fn overlapping_rebuilds_keep_all_edits() {
+        let mut tilelist = flat_tilelist(6, 4);
+        let mut queue = EditQueue::default();
+
+        queue.edit((1, 1)).altitude = Some(5);
+
+        let first = queue.start().unwrap();
+
+        // the second batch is made while the first rebuild is running.
+        queue.edit((4, 4)).altitude = Some(3);
+        queue.edit((4, 4)).terrain_type = Some(TerrainType::Shoreline);
+
+        assert!(queue.start().is_none());
+
+        apply(&mut tilelist, &first, TerrainRotation::default());
+        queue.finish();
+
+        let second = queue.start().unwrap();
+
+        assert_eq!(second.len(), 1);
+
+        apply(&mut tilelist, &second, TerrainRotation::default());
+        queue.finish();
+
+        assert_eq!(tilelist[&(1, 1)].altitude, 5);
+        assert_eq!(tilelist[&(4, 4)].altitude, 3);
+        assert_eq!(tilelist[&(4, 4)].terrain.ty, TerrainType::Shoreline);
+    }
+}
@@ -37,6 +37,22 @@ impl fmt::Display for TileSurfaceType {
 /// The location of all four corners of a tile.
 type TileCorners = [Vector3; 4];
 
+/// Resolution of water surfaces, the most detailed surfaces of the terrain.
+pub(crate) const WATER_RESOLUTION: u8 = 3;
+
+/// Number of levels of detail until a surface of the given resolution is reduced to a single
+/// quad.
+pub(crate) const fn lod_levels(mut resolution: u8) -> u8 {
+    let mut levels = 1;
+
+    while resolution > 1 {
+        resolution /= 2;
+        levels += 1;
+    }
+
+    levels
+}
+
 /// A face of three vertecies
 pub type Face = [Vertex; 3];
 
@@ -74,6 +90,15 @@ impl TileSurface {
         self.resolution = value;
     }
 
+    /// Halves the resolution for every level of detail, but keeps at least one quad.
+    pub fn reduce_resolution(&mut self, lod: u8) {
+        self.resolution = self
+            .resolution
+            .checked_shr(u32::from(lod))
+            .unwrap_or(0)
+            .max(1);
+    }
+
     fn corners(&self) -> &TileCorners {
         &self.corners
     }
@@ -384,3 +409,41 @@ impl SurfaceAssociated for Vertex {
         self.surface
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{lod_levels, TileSurface, TileSurfaceType, WATER_RESOLUTION};
+
+    fn reduced(resolution: u8, lod: u8) -> u8 {
+        let mut surface = TileSurface::new(TileSurfaceType::Ground);
+
+        surface.set_resolution(resolution);
+        surface.reduce_resolution(lod);
+        surface.resolution()
+    }
+
+    #[test]
+    fn every_level_halves_the_resolution() {
+        assert_eq!(reduced(4, 0), 4);
+        assert_eq!(reduced(4, 1), 2);
+        assert_eq!(reduced(4, 2), 1);
+        assert_eq!(reduced(3, 1), 1);
+        assert_eq!(reduced(2, 1), 1);
+        assert_eq!(reduced(1, 1), 1);
+        assert_eq!(reduced(4, 9), 1);
+    }
+
+    #[test]
+    fn levels_end_with_a_single_quad() {
+        assert_eq!(lod_levels(1), 1);
+        assert_eq!(lod_levels(2), 2);
+        assert_eq!(lod_levels(3), 2);
+        assert_eq!(lod_levels(4), 3);
+
+        let last_level = lod_levels(WATER_RESOLUTION) - 1;
+
+        // the last level is the first one to reach a single quad.
+        assert_eq!(reduced(WATER_RESOLUTION, last_level), 1);
+        assert!(reduced(WATER_RESOLUTION, last_level - 1) > 1);
+    }
+}
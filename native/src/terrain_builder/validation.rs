@@ -0,0 +1,285 @@
+/*
+ * Copyright (c) SimChopper; Jovan Gerodetti and contributors.
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Display};
+use std::path::{Path, PathBuf};
+
+use godot::builtin::GString;
+use godot::classes::{ProjectSettings, RefCounted};
+use godot::prelude::{godot_api, GodotClass};
+
+use super::slope_solver::{SlopeSolverReport, UnresolvedTile};
+use super::{
+    fix_invalid_slopes, is_invalid_cliff, is_special_case, is_water_cliff, TerrainRotation,
+    TileSpecialCase,
+};
+use crate::road_navigation::Side;
+use crate::world::city_data::{TerrainSlope, TerrainType, TileCoords, TileList, TileListExt};
+use crate::world::city_loader::load_city_file;
+
+/// Chunk size that is used to pre-process the tiles of a map.
+const CHUNK_SIZE: u32 = 16;
+
+const MAP_EXTENSION: &str = ".sc2.mpz";
+
+/// All terrain problems of a city.
+///
+/// The problems are listed in a stable order, so reports of two versions of the slope tables can
+/// be compared with a plain diff.
+#[derive(Debug, Default)]
+pub(crate) struct TerrainReport {
+    /// Tiles whose slope does not fit all of their neighbors.
+    invalid_slopes: Vec<TileCoords>,
+    /// Invalid tiles that are handled by a special case of the terrain builder.
+    special_cases: BTreeMap<TileSpecialCase, Vec<TileCoords>>,
+    /// Neighboring dry tiles that leave a vertical cliff in the terrain.
+    invalid_cliffs: Vec<(TileCoords, TileCoords)>,
+    /// Surface water tiles that fall down to a neighbor.
+    water_cliffs: Vec<(TileCoords, TileCoords)>,
+    /// Tiles that still have an invalid slope after the slope solver ran.
+    unresolved: Vec<UnresolvedTile>,
+}
+
+impl TerrainReport {
+    /// Validates the tiles of a city as they are stored in the map.
+    pub(crate) fn new(tilelist: &TileList, rotation: TerrainRotation) -> Self {
+        let mut report = Self::default();
+
+        for tile in tilelist.values() {
+            let validation = tilelist.validate_tile_slope(tile, rotation);
+
+            if validation.is_invalid() {
+                report.invalid_slopes.push(tile.coordinates);
+
+                if let Some(special_case) = is_special_case(tile, tilelist, &validation) {
+                    report
+                        .special_cases
+                        .entry(special_case)
+                        .or_default()
+                        .push(tile.coordinates);
+                }
+            }
+
+            let neighbors = Side::ALL
+                .into_iter()
+                .filter_map(|side| side.neighbor(tile.coordinates))
+                .filter_map(|coords| tilelist.get(&coords));
+
+            for neighbor in neighbors {
+                let pair = (tile.coordinates, neighbor.coordinates);
+
+                match tile.terrain.ty {
+                    TerrainType::DryLand if is_invalid_cliff(tile, neighbor) => {
+                        report.invalid_cliffs.push(pair);
+                    }
+
+                    TerrainType::SurfaceWater
+                        if tile.terrain.slope == TerrainSlope::VertialCliff
+                            && is_water_cliff(tile, neighbor) =>
+                    {
+                        report.water_cliffs.push(pair);
+                    }
+
+                    _ => (),
+                }
+            }
+        }
+
+        report
+    }
+
+    /// Adds the tiles the slope solver could not resolve.
+    pub(crate) fn with_solver_report(mut self, solver_report: SlopeSolverReport) -> Self {
+        self.unresolved = solver_report.unresolved;
+        self.unresolved.sort_by_key(|tile| tile.coordinates);
+        self
+    }
+}
+
+fn write_section<T>(
+    f: &mut fmt::Formatter<'_>,
+    title: &str,
+    items: &[T],
+    mut write_item: impl FnMut(&mut fmt::Formatter<'_>, &T) -> fmt::Result,
+) -> fmt::Result {
+    writeln!(f, "{title}: {}", items.len())?;
+
+    for item in items {
+        write!(f, "  ")?;
+        write_item(f, item)?;
+        writeln!(f)?;
+    }
+
+    Ok(())
+}
+
+impl Display for TerrainReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_section(f, "invalid slopes", &self.invalid_slopes, |f, coords| {
+            write!(f, "{coords:?}")
+        })?;
+
+        for (special_case, tiles) in &self.special_cases {
+            write_section(
+                f,
+                &format!("special case {special_case:?}"),
+                tiles,
+                |f, coords| write!(f, "{coords:?}"),
+            )?;
+        }
+
+        write_section(f, "invalid cliffs", &self.invalid_cliffs, |f, (a, b)| {
+            write!(f, "{a:?} -> {b:?}")
+        })?;
+
+        write_section(f, "water cliffs", &self.water_cliffs, |f, (a, b)| {
+            write!(f, "{a:?} -> {b:?}")
+        })?;
+
+        write_section(f, "unresolved slopes", &self.unresolved, |f, tile| {
+            write!(f, "{:?} {:?}", tile.coordinates, tile.reason)
+        })
+    }
+}
+
+/// Validates the terrain of every map inside the directory and its sub directories, with the
+/// rotation of each city. Returns the reports of all maps, ordered by their path.
+pub(crate) fn report_maps(maps_directory: &Path) -> String {
+    let mut paths = Vec::new();
+    let mut output = String::new();
+
+    collect_map_paths(maps_directory, &mut paths);
+    paths.sort();
+
+    for path in paths {
+        let name = path.strip_prefix(maps_directory).unwrap_or(&path);
+
+        output.push_str(&format!("# {}\n", name.display()));
+
+        let city = match load_city_file(&path) {
+            Ok(city) => city,
+            Err(err) => {
+                output.push_str(&format!("failed to load map: {err}\n\n"));
+                continue;
+            }
+        };
+
+        let rotation = TerrainRotation::from_compass(city.simulator_settings.compass);
+        let report = TerrainReport::new(&city.tilelist, rotation);
+        let (_, solver_report) = fix_invalid_slopes(rotation, CHUNK_SIZE, city.tilelist);
+
+        output.push_str(&format!("{}\n", report.with_solver_report(solver_report)));
+    }
+
+    output
+}
+
+/// Collects the paths of all maps inside the directory and its sub directories. Directories that
+/// can not be read are skipped.
+fn collect_map_paths(directory: &Path, paths: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(directory) else {
+        return;
+    };
+
+    for path in entries.filter_map(Result::ok).map(|entry| entry.path()) {
+        if path.is_dir() {
+            collect_map_paths(&path, paths);
+        } else if path.to_string_lossy().ends_with(MAP_EXTENSION) {
+            paths.push(path);
+        }
+    }
+}
+
+/// Headless validation of the terrain of the bundled maps.
+#[derive(GodotClass)]
+#[class(base = RefCounted, init)]
+struct TerrainValidation;
+
+#[godot_api]
+impl TerrainValidation {
+    /// Validate the terrain of every map inside the directory and its sub directories. Returns a
+    /// report that lists the terrain problems of each map.
+    #[func]
+    #[expect(clippy::needless_pass_by_value)]
+    fn report_maps(maps_directory: GString) -> GString {
+        let maps_directory = ProjectSettings::singleton().globalize_path(&maps_directory);
+
+        report_maps(Path::new(&maps_directory.to_string())).into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use crate::terrain_builder::TerrainRotation;
+    use crate::world::city_data::fixtures::{flat_tilelist, tile};
+    use crate::world::city_data::TerrainSlope;
+
+    use super::{report_maps, TerrainReport};
+
+    #[test]
+    fn flat_terrain_has_no_problems() {
+        let report = TerrainReport::new(&flat_tilelist(3, 2), TerrainRotation::default());
+
+        assert_eq!(
+            report.to_string(),
+            "invalid slopes: 0\ninvalid cliffs: 0\nwater cliffs: 0\nunresolved slopes: 0\n"
+        );
+    }
+
+    #[test]
+    fn raised_plateau_is_an_invalid_cliff() {
+        let mut tilelist = flat_tilelist(3, 2);
+
+        tilelist.insert((1, 1), tile((1, 1), 2, TerrainSlope::All));
+
+        let report = TerrainReport::new(&tilelist, TerrainRotation::default());
+
+        assert!(report.invalid_slopes.contains(&(1, 1)));
+        assert_eq!(
+            report.invalid_cliffs,
+            vec![
+                ((1, 1), (1, 0)),
+                ((1, 1), (2, 1)),
+                ((1, 1), (1, 2)),
+                ((1, 1), (0, 1))
+            ]
+        );
+    }
+
+    #[test]
+    fn report_lists_problems_in_order() {
+        let mut tilelist = flat_tilelist(4, 2);
+
+        tilelist.insert((2, 2), tile((2, 2), 2, TerrainSlope::All));
+        tilelist.insert((0, 3), tile((0, 3), 2, TerrainSlope::All));
+
+        let report = TerrainReport::new(&tilelist, TerrainRotation::default()).to_string();
+        let first = report.find("(0, 3)").expect("tile (0, 3) is reported");
+        let second = report.find("(2, 2)").expect("tile (2, 2) is reported");
+
+        assert!(report.starts_with("invalid slopes: "));
+        assert!(first < second);
+    }
+
+    #[test]
+    fn bundled_maps_are_reported() {
+        let maps_directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("../resources/Maps");
+        let report = report_maps(&maps_directory);
+        let maps = report.lines().filter(|line| line.starts_with("# ")).count();
+
+        assert!(report.starts_with("# "));
+        assert!(maps > 1);
+        assert_eq!(
+            report.matches("invalid slopes: ").count(),
+            maps,
+            "every map loads: {report}"
+        );
+    }
+}
@@ -8,6 +8,7 @@
 pub mod async_support;
 pub mod logger;
 mod numbers;
+pub(crate) mod spatial_index;
 
 #[cfg(debug_assertions)]
 use godot::builtin::{
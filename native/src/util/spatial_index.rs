@@ -0,0 +1,391 @@
+/*
+ * Copyright (c) SimChopper; Jovan Gerodetti and contributors.
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::ops::{Add, Sub};
+
+use godot::builtin::{Vector2, Vector2i, Vector3, Vector3i};
+
+/// A position that can be sorted into the cells of a uniform grid.
+pub(crate) trait GridPosition: Copy + Add<Output = Self> + Sub<Output = Self> {
+    /// Integer coordinates of a grid cell.
+    type Cell: Copy + Eq + Hash;
+
+    fn splat(value: f32) -> Self;
+
+    fn distance_squared_to(self, other: Self) -> f32;
+
+    /// Distance to the closest edge of the cell that contains the position.
+    fn distance_to_cell_edge(self, cell_size: f32) -> f32;
+
+    fn cell(self, cell_size: f32) -> Self::Cell;
+
+    /// The smallest and the largest coordinates of both cells along every axis.
+    fn cell_range(a: Self::Cell, b: Self::Cell) -> (Self::Cell, Self::Cell);
+
+    /// The largest difference between two cells along any axis.
+    fn ring_distance(a: Self::Cell, b: Self::Cell) -> i32;
+
+    /// All cells of the box between the two cells, both inclusive.
+    fn cells_between(min: Self::Cell, max: Self::Cell) -> impl Iterator<Item = Self::Cell>;
+
+    /// The cells on the outer shell of the box that reaches `ring` cells around the center cell.
+    fn ring_cells(center: Self::Cell, ring: i32) -> impl Iterator<Item = Self::Cell>;
+}
+
+/// The offsets of both outer edges of a ring. The ring around the center cell only has one.
+fn ring_edges(ring: i32) -> impl Iterator<Item = i32> {
+    (-ring..=ring).step_by(usize::try_from(ring * 2).unwrap_or_default().max(1))
+}
+
+/// The offsets of the cells on the outline of a square ring.
+fn square_ring(ring: i32) -> impl Iterator<Item = (i32, i32)> {
+    let rows = (-ring..=ring).flat_map(move |x| ring_edges(ring).map(move |y| (x, y)));
+    let columns = ring_edges(ring).flat_map(move |x| (1 - ring..ring).map(move |y| (x, y)));
+
+    rows.chain(columns)
+}
+
+impl GridPosition for Vector2 {
+    type Cell = Vector2i;
+
+    fn splat(value: f32) -> Self {
+        Vector2::splat(value)
+    }
+
+    fn distance_squared_to(self, other: Self) -> f32 {
+        Vector2::distance_squared_to(self, other)
+    }
+
+    fn distance_to_cell_edge(self, cell_size: f32) -> f32 {
+        let origin = (self / cell_size).floor() * cell_size;
+
+        (self.x - origin.x)
+            .min(origin.x + cell_size - self.x)
+            .min(self.y - origin.y)
+            .min(origin.y + cell_size - self.y)
+    }
+
+    fn cell(self, cell_size: f32) -> Self::Cell {
+        (self / cell_size).floor().cast_int()
+    }
+
+    fn cell_range(a: Self::Cell, b: Self::Cell) -> (Self::Cell, Self::Cell) {
+        (
+            Vector2i::new(a.x.min(b.x), a.y.min(b.y)),
+            Vector2i::new(a.x.max(b.x), a.y.max(b.y)),
+        )
+    }
+
+    fn ring_distance(a: Self::Cell, b: Self::Cell) -> i32 {
+        (a.x - b.x).abs().max((a.y - b.y).abs())
+    }
+
+    fn cells_between(min: Self::Cell, max: Self::Cell) -> impl Iterator<Item = Self::Cell> {
+        (min.x..=max.x).flat_map(move |x| (min.y..=max.y).map(move |y| Vector2i::new(x, y)))
+    }
+
+    fn ring_cells(center: Self::Cell, ring: i32) -> impl Iterator<Item = Self::Cell> {
+        square_ring(ring).map(move |(x, y)| center + Vector2i::new(x, y))
+    }
+}
+
+impl GridPosition for Vector3 {
+    type Cell = Vector3i;
+
+    fn splat(value: f32) -> Self {
+        Vector3::splat(value)
+    }
+
+    fn distance_squared_to(self, other: Self) -> f32 {
+        Vector3::distance_squared_to(self, other)
+    }
+
+    fn distance_to_cell_edge(self, cell_size: f32) -> f32 {
+        let origin = (self / cell_size).floor() * cell_size;
+
+        (self.x - origin.x)
+            .min(origin.x + cell_size - self.x)
+            .min(self.y - origin.y)
+            .min(origin.y + cell_size - self.y)
+            .min(self.z - origin.z)
+            .min(origin.z + cell_size - self.z)
+    }
+
+    fn cell(self, cell_size: f32) -> Self::Cell {
+        (self / cell_size).floor().cast_int()
+    }
+
+    fn cell_range(a: Self::Cell, b: Self::Cell) -> (Self::Cell, Self::Cell) {
+        (
+            Vector3i::new(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z)),
+            Vector3i::new(a.x.max(b.x), a.y.max(b.y), a.z.max(b.z)),
+        )
+    }
+
+    fn ring_distance(a: Self::Cell, b: Self::Cell) -> i32 {
+        (a.x - b.x)
+            .abs()
+            .max((a.y - b.y).abs())
+            .max((a.z - b.z).abs())
+    }
+
+    fn cells_between(min: Self::Cell, max: Self::Cell) -> impl Iterator<Item = Self::Cell> {
+        (min.x..=max.x)
+            .flat_map(move |x| (min.y..=max.y).map(move |y| (x, y)))
+            .flat_map(move |(x, y)| (min.z..=max.z).map(move |z| Vector3i::new(x, y, z)))
+    }
+
+    fn ring_cells(center: Self::Cell, ring: i32) -> impl Iterator<Item = Self::Cell> {
+        // the full squares at both ends of the z axis, and the outlines in between.
+        let faces = ring_edges(ring).flat_map(move |z| {
+            (-ring..=ring).flat_map(move |x| (-ring..=ring).map(move |y| (x, y, z)))
+        });
+        let sides =
+            (1 - ring..ring).flat_map(move |z| square_ring(ring).map(move |(x, y)| (x, y, z)));
+
+        faces
+            .chain(sides)
+            .map(move |(x, y, z)| center + Vector3i::new(x, y, z))
+    }
+}
+
+/// Uniform grid of items, either on the ground plane or in space.
+///
+/// Positions are only read when an item is inserted, queries never have to look at the scene
+/// tree.
+#[derive(Debug)]
+pub(crate) struct SpatialIndex<P: GridPosition, T> {
+    cell_size: f32,
+    cells: HashMap<P::Cell, Vec<(P, T)>>,
+    /// The smallest and largest cell that have ever been occupied.
+    bounds: Option<(P::Cell, P::Cell)>,
+}
+
+impl<P: GridPosition, T> SpatialIndex<P, T> {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+            bounds: None,
+        }
+    }
+
+    pub fn insert(&mut self, position: P, item: T) {
+        let cell = position.cell(self.cell_size);
+
+        self.cells.entry(cell).or_default().push((position, item));
+        self.bounds = Some(match self.bounds {
+            None => (cell, cell),
+            Some((min, max)) => (P::cell_range(min, cell).0, P::cell_range(max, cell).1),
+        });
+    }
+
+    /// Removes the item at the given position. Returns `false` if there is no such item.
+    pub fn remove(&mut self, position: P, item: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        let cell = position.cell(self.cell_size);
+
+        let Some(items) = self.cells.get_mut(&cell) else {
+            return false;
+        };
+
+        let Some(index) = items.iter().position(|(_, other)| other == item) else {
+            return false;
+        };
+
+        items.swap_remove(index);
+
+        if items.is_empty() {
+            self.cells.remove(&cell);
+        }
+
+        true
+    }
+
+    /// The item that is closest to the point.
+    pub fn nearest(&self, point: P) -> Option<&T> {
+        self.k_nearest(point, 1).into_iter().next()
+    }
+
+    /// The `k` items that are closest to the point, ordered by their distance.
+    ///
+    /// The grid is searched in rings around the cell of the point, until no unvisited cell can
+    /// contain an item that is closer than the ones that have already been found.
+    pub fn k_nearest(&self, point: P, k: usize) -> Vec<&T> {
+        let Some((min, max)) = self.bounds.filter(|_| k > 0) else {
+            return Vec::new();
+        };
+
+        let center = point.cell(self.cell_size);
+        let edge = point.distance_to_cell_edge(self.cell_size);
+        let mut found: Vec<(f32, &T)> = Vec::new();
+        let mut ring = 0;
+        let mut reach = 0.0;
+
+        loop {
+            let items = P::ring_cells(center, ring)
+                .filter_map(|cell| self.cells.get(&cell))
+                .flatten()
+                .map(|(position, item)| (position.distance_squared_to(point), item));
+
+            found.extend(items);
+            found.sort_by(|a, b| a.0.total_cmp(&b.0));
+            found.truncate(k);
+
+            // distance from the point to the edge of the visited cells.
+            let visited = edge + reach;
+
+            let complete = found
+                .get(k - 1)
+                .is_some_and(|(distance, _)| *distance <= visited * visited);
+
+            let covers_all =
+                P::ring_distance(center, min) <= ring && P::ring_distance(center, max) <= ring;
+
+            if complete || covers_all {
+                break;
+            }
+
+            ring += 1;
+            reach += self.cell_size;
+        }
+
+        found.into_iter().map(|(_, item)| item).collect()
+    }
+
+    /// All items within the radius around the point.
+    pub fn within_radius(&self, point: P, radius: f32) -> impl Iterator<Item = &T> + '_ {
+        let min = (point - P::splat(radius)).cell(self.cell_size);
+        let max = (point + P::splat(radius)).cell(self.cell_size);
+
+        P::cells_between(min, max)
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .filter(move |(position, _)| position.distance_squared_to(point) <= radius * radius)
+            .map(|(_, item)| item)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use godot::builtin::{Vector2, Vector2i, Vector3, Vector3i};
+
+    use super::{GridPosition, SpatialIndex};
+
+    fn index(points: &[(f32, f32)]) -> SpatialIndex<Vector2, usize> {
+        let mut index = SpatialIndex::new(4.0);
+
+        for (item, (x, y)) in points.iter().enumerate() {
+            index.insert(Vector2::new(*x, *y), item);
+        }
+
+        index
+    }
+
+    #[test]
+    fn nearest_item_in_another_row() {
+        // the second point shares the row of the query, but the first one is closer.
+        let index = index(&[(10.0, 13.0), (30.0, 10.0), (-40.0, -40.0)]);
+
+        assert_eq!(index.nearest(Vector2::new(10.0, 10.0)), Some(&0));
+        assert_eq!(index.nearest(Vector2::new(27.0, 10.0)), Some(&1));
+        assert_eq!(index.nearest(Vector2::new(-100.0, -60.0)), Some(&2));
+    }
+
+    #[test]
+    fn nearest_item_far_away() {
+        let index = index(&[(400.0, 400.0), (410.0, 400.0)]);
+
+        assert_eq!(index.nearest(Vector2::ZERO), Some(&0));
+        assert_eq!(
+            SpatialIndex::<Vector2, usize>::new(4.0).nearest(Vector2::ZERO),
+            None
+        );
+    }
+
+    #[test]
+    fn k_nearest_items_are_ordered() {
+        let index = index(&[
+            (0.0, 0.0),
+            (9.0, 0.0),
+            (3.0, 0.0),
+            (0.0, -5.0),
+            (50.0, 50.0),
+        ]);
+
+        assert_eq!(index.k_nearest(Vector2::new(1.0, 0.0), 3), vec![&0, &2, &3]);
+        assert_eq!(index.k_nearest(Vector2::new(1.0, 0.0), 10).len(), 5);
+        assert!(index.k_nearest(Vector2::new(1.0, 0.0), 0).is_empty());
+    }
+
+    #[test]
+    fn rings_only_contain_their_shell() {
+        for (ring, count) in [(0, 1), (1, 8), (2, 16)] {
+            let center = Vector2i::new(3, -2);
+            let cells: HashSet<_> = Vector2::ring_cells(center, ring).collect();
+
+            assert_eq!(cells.len(), count);
+            assert_eq!(Vector2::ring_cells(center, ring).count(), count);
+            assert!(cells
+                .iter()
+                .all(|cell| Vector2::ring_distance(center, *cell) == ring));
+        }
+
+        for (ring, count) in [(0, 1), (1, 26), (2, 98)] {
+            let center = Vector3i::new(3, -2, 1);
+            let cells: HashSet<_> = Vector3::ring_cells(center, ring).collect();
+
+            assert_eq!(cells.len(), count);
+            assert_eq!(Vector3::ring_cells(center, ring).count(), count);
+            assert!(cells
+                .iter()
+                .all(|cell| Vector3::ring_distance(center, *cell) == ring));
+        }
+    }
+
+    #[test]
+    fn items_within_radius() {
+        let index = index(&[(0.0, 0.0), (3.0, 4.0), (6.0, 0.0), (-2.0, -2.0)]);
+
+        let mut found: Vec<_> = index.within_radius(Vector2::ZERO, 5.0).copied().collect();
+        found.sort_unstable();
+
+        assert_eq!(found, vec![0, 1, 3]);
+    }
+
+    #[test]
+    fn removed_items_are_not_found() {
+        let mut index = index(&[(0.0, 0.0), (8.0, 0.0)]);
+
+        assert!(index.remove(Vector2::ZERO, &0));
+        assert!(!index.remove(Vector2::ZERO, &0));
+        assert_eq!(index.k_nearest(Vector2::ZERO, 2), vec![&1]);
+        assert_eq!(index.nearest(Vector2::ZERO), Some(&1));
+    }
+
+    #[test]
+    fn nearest_item_in_space() {
+        let mut index = SpatialIndex::new(4.0);
+
+        index.insert(Vector3::new(3.5, 0.0, 3.5), 0);
+        index.insert(Vector3::new(4.5, 9.0, 4.5), 1);
+        index.insert(Vector3::new(-0.5, 0.0, -0.5), 2);
+
+        assert_eq!(index.nearest(Vector3::new(4.0, 0.0, 4.0)), Some(&0));
+        assert_eq!(index.nearest(Vector3::new(4.0, 7.0, 4.0)), Some(&1));
+        assert_eq!(
+            index.within_radius(Vector3::ZERO, 1.0).collect::<Vec<_>>(),
+            vec![&2]
+        );
+    }
+}
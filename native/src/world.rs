@@ -7,3 +7,12 @@
 
 pub(crate) mod city_coords_feature;
 pub(crate) mod city_data;
+pub(crate) mod city_loader;
+pub(crate) mod drying;
+pub(crate) mod fire_department;
+pub(crate) mod fire_spread;
+pub(crate) mod missions;
+pub(crate) mod network_pieces;
+pub(crate) mod power_grid;
+pub(crate) mod save_game;
+pub(crate) mod sc2_file;
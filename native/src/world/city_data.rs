@@ -7,13 +7,15 @@
 
 use std::collections::{BTreeMap, HashSet};
 
-use godot::builtin::{VarArray, VarDictionary};
+use godot::builtin::{varray, VarArray, VarDictionary, Variant};
 use godot::global::godot_warn;
 use godot::meta::error::ConvertError;
-use godot::meta::FromGodot;
+use godot::meta::{FromGodot, ToGodot};
 
 use crate::objects::scene_object_registry::Buildings;
 use crate::terrain_builder::TerrainRotation;
+use crate::world::city_loader::msgpack::Value;
+use crate::world::sc2_file::city_name;
 
 mod terrain_slope;
 
@@ -23,6 +25,21 @@ pub(crate) trait TryFromDictionary: Sized {
     fn try_from_dict(value: &VarDictionary) -> Result<Self, TryFromDictError>;
 }
 
+/// Counterpart of [`TryFromDictionary`] for the maps, which are decoded without the engine.
+pub(crate) trait TryFromValue: Sized {
+    fn try_from_value(value: &Value) -> Result<Self, TryFromDictError>;
+}
+
+/// Conversion of a single field of a map, like [`FromGodot`] for dictionaries.
+trait FromValue: Sized {
+    fn from_value(value: &Value) -> Result<Self, ErasedConvertError>;
+}
+
+/// Inverse of [`TryFromDictionary`], to hand city data back to `GDScript`.
+pub(crate) trait ToDictionary {
+    fn to_dict(&self) -> VarDictionary;
+}
+
 #[derive(Debug, thiserror::Error)]
 pub(crate) enum TryFromDictError {
     #[error("dictionary key \"{0}\" is missing")]
@@ -39,6 +56,14 @@ pub(crate) struct ErasedConvertError {
     message: String,
 }
 
+impl ErasedConvertError {
+    fn unexpected(expected: &str, value: &Value) -> Self {
+        Self {
+            message: format!("expected {expected}, found {}", value.type_name()),
+        }
+    }
+}
+
 impl From<ConvertError> for ErasedConvertError {
     fn from(value: ConvertError) -> Self {
         Self {
@@ -59,6 +84,21 @@ pub(crate) enum Compass {
     SouthEast,
 }
 
+impl Compass {
+    pub const fn opposite(self) -> Self {
+        match self {
+            Self::North => Self::South,
+            Self::East => Self::West,
+            Self::South => Self::North,
+            Self::West => Self::East,
+            Self::NorthWest => Self::SouthEast,
+            Self::NorthEast => Self::SouthWest,
+            Self::SouthWest => Self::NorthEast,
+            Self::SouthEast => Self::NorthWest,
+        }
+    }
+}
+
 fn get_valid_tile_slopes_from_neighbors<'a>(
     tile: &'a Tile,
     rotation: TerrainRotation,
@@ -71,33 +111,9 @@ fn get_valid_tile_slopes_from_neighbors<'a>(
                 .try_into()
                 .unwrap_or(i8::MIN);
 
-            let options = match dir {
-                Compass::North => rotation
-                    .normalize_slope(neighbor_tile.terrain.slope)
-                    .valid_south_neighbors(),
-                Compass::East => rotation
-                    .normalize_slope(neighbor_tile.terrain.slope)
-                    .valid_west_neighbors(),
-                Compass::South => rotation
-                    .normalize_slope(neighbor_tile.terrain.slope)
-                    .valid_north_neighbors(),
-                Compass::West => rotation
-                    .normalize_slope(neighbor_tile.terrain.slope)
-                    .valid_east_neighbor(),
-
-                Compass::NorthWest => rotation
-                    .normalize_slope(neighbor_tile.terrain.slope)
-                    .valid_south_east_neighbors(),
-                Compass::NorthEast => rotation
-                    .normalize_slope(neighbor_tile.terrain.slope)
-                    .valid_south_west_neighbors(),
-                Compass::SouthWest => rotation
-                    .normalize_slope(neighbor_tile.terrain.slope)
-                    .valid_north_east_neighbors(),
-                Compass::SouthEast => rotation
-                    .normalize_slope(neighbor_tile.terrain.slope)
-                    .valid_north_west_neighbors(),
-            };
+            let options = rotation
+                .normalize_slope(neighbor_tile.terrain.slope)
+                .valid_neighbors(dir.opposite());
 
             let available_options = options
                 .iter()
@@ -115,12 +131,6 @@ pub(crate) trait TileListExt {
 
     /// Validate the slope of a tile. Returns the number of neighbors that do not fit with this tile.
     fn validate_tile_slope(&self, tile: &Tile, rotation: TerrainRotation) -> TileValidationResult;
-
-    fn valid_slopes(
-        &self,
-        tile: &Tile,
-        rotation: TerrainRotation,
-    ) -> HashSet<&'static TerrainSlope>;
 }
 
 #[derive(Default, Debug)]
@@ -170,38 +180,34 @@ impl TileListExt for TileList {
                 },
             )
     }
-
-    fn valid_slopes(
-        &self,
-        tile: &Tile,
-        rotation: TerrainRotation,
-    ) -> HashSet<&'static TerrainSlope> {
-        let neighbors = get_valid_tile_slopes_from_neighbors(tile, rotation, self);
-
-        neighbors
-            .map(|(_, _, options)| options)
-            .reduce(|a, b| a.intersection(&b).copied().collect())
-            .unwrap_or_default()
-    }
 }
 
 #[derive(Debug)]
 pub(crate) struct City {
+    /// `None` if the map does not store a valid name.
+    pub name: Option<String>,
     pub simulator_settings: SimulatorSettings,
     pub buildings: BTreeMap<TileCoords, Building>,
+    pub networks: BTreeMap<TileCoords, Building>,
     pub tilelist: TileList,
+    pub city_size: u32,
 }
 
-impl TryFromDictionary for City {
-    fn try_from_dict(value: &VarDictionary) -> Result<Self, TryFromDictError> {
+impl TryFromValue for City {
+    fn try_from_value(value: &Value) -> Result<Self, TryFromDictError> {
         Ok(Self {
-            simulator_settings: get_dict_key(value, "simulator_settings")
-                .and_then(|value| SimulatorSettings::try_from_dict(&value))?,
-
-            buildings: get_dict_key(value, "buildings")
-                .and_then(|value| BTreeMap::try_from_dict(&value))?,
-            tilelist: get_dict_key(value, "tilelist")
-                .and_then(|value| BTreeMap::try_from_dict(&value))?,
+            // the converted maps strip the length of the name from the `CNAM` chunk, in which
+            // case no name is found.
+            name: get_value_field::<String>(value, "city_name")
+                .ok()
+                .and_then(|raw| city_name(raw.as_bytes())),
+            simulator_settings: get_value_key(value, "simulator_settings")
+                .and_then(SimulatorSettings::try_from_value)?,
+
+            buildings: get_value_key(value, "buildings").and_then(BTreeMap::try_from_value)?,
+            networks: get_value_key(value, "networks").and_then(BTreeMap::try_from_value)?,
+            tilelist: get_value_key(value, "tilelist").and_then(BTreeMap::try_from_value)?,
+            city_size: get_value_field(value, "city_size")?,
         })
     }
 }
@@ -227,6 +233,33 @@ impl TryFromDictionary for Building {
     }
 }
 
+impl TryFromValue for Building {
+    fn try_from_value(value: &Value) -> Result<Self, TryFromDictError> {
+        Ok(Self {
+            size: get_value_field(value, "size")?,
+            name: get_value_field(value, "name")?,
+            id: get_value_field(value, "building_id")?,
+            tile_coords: get_value_field(value, "tile_coords")?,
+        })
+    }
+}
+
+impl ToDictionary for Building {
+    fn to_dict(&self) -> VarDictionary {
+        let mut dict = VarDictionary::new();
+
+        dict.set("size", self.size);
+        dict.set("name", self.name.as_str());
+        dict.set("building_id", self.id);
+        dict.set(
+            "tile_coords",
+            &varray![self.tile_coords.0, self.tile_coords.1],
+        );
+
+        dict
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TerrainType {
     DryLand,
@@ -253,6 +286,18 @@ impl From<u8> for TerrainType {
     }
 }
 
+impl From<TerrainType> for u8 {
+    fn from(value: TerrainType) -> Self {
+        match value {
+            TerrainType::DryLand => 0,
+            TerrainType::Underwater => 1,
+            TerrainType::Shoreline => 2,
+            TerrainType::SurfaceWater => 3,
+            TerrainType::MoreSurfaceWater => 4,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct TileTerrainInfo {
     pub ty: TerrainType,
@@ -269,6 +314,12 @@ impl From<u32> for TileTerrainInfo {
     }
 }
 
+impl From<&TileTerrainInfo> for u32 {
+    fn from(value: &TileTerrainInfo) -> Self {
+        (u32::from(u8::from(value.ty)) << 4) | u32::from(u8::from(value.slope))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct Tile {
     pub terrain: TileTerrainInfo,
@@ -300,55 +351,144 @@ impl Tile {
             TerrainType::SurfaceWater | TerrainType::MoreSurfaceWater
         )
     }
+
+    /// Whether water can be taken from the tile, either from the sea or from surface water.
+    pub fn is_water_source(&self) -> bool {
+        self.has_surface_water() || self.terrain.ty == TerrainType::Underwater
+    }
 }
 
-impl TryFromDictionary for Tile {
-    fn try_from_dict(value: &VarDictionary) -> Result<Self, TryFromDictError> {
+impl TryFromValue for Tile {
+    fn try_from_value(value: &Value) -> Result<Self, TryFromDictError> {
         Ok(Self {
-            altitude: get_dict_key(value, "altitude")?,
-            terrain: TileTerrainInfo::from(get_dict_key::<u32>(value, "terrain")?),
-            coordinates: array_to_tuple(&get_dict_key(value, "coordinates")?)?,
-            building: get_dict_key_optional(value, "building")?
-                .map(|value| Building::try_from_dict(&value))
+            altitude: get_value_field(value, "altitude")?,
+            terrain: TileTerrainInfo::from(get_value_field::<u32>(value, "terrain")?),
+            coordinates: get_value_field(value, "coordinates")?,
+            building: value
+                .get("building")
+                .filter(|building| !matches!(building, Value::Nil))
+                .map(Building::try_from_value)
                 .transpose()?,
         })
     }
 }
 
+impl ToDictionary for Tile {
+    fn to_dict(&self) -> VarDictionary {
+        let mut dict = VarDictionary::new();
+
+        dict.set("altitude", self.altitude);
+        dict.set("terrain", u32::from(&self.terrain));
+        dict.set(
+            "coordinates",
+            &varray![self.coordinates.0, self.coordinates.1],
+        );
+        dict.set(
+            "building",
+            &self
+                .building
+                .as_ref()
+                .map_or_else(Variant::nil, |building| building.to_dict().to_variant()),
+        );
+
+        dict
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct SimulatorSettings {
     pub sea_level: u32,
+    pub compass: u8,
 }
 
-impl TryFromDictionary for SimulatorSettings {
-    fn try_from_dict(value: &VarDictionary) -> Result<Self, TryFromDictError> {
+impl TryFromValue for SimulatorSettings {
+    fn try_from_value(value: &Value) -> Result<Self, TryFromDictError> {
         Ok(Self {
-            sea_level: get_dict_key(value, "GlobalSeaLevel")?,
+            sea_level: get_value_field(value, "GlobalSeaLevel")?,
+            compass: get_value_field(value, "Compass")?,
         })
     }
 }
 
-impl<T: TryFromDictionary> TryFromDictionary for BTreeMap<TileCoords, T> {
-    fn try_from_dict(value: &VarDictionary) -> Result<Self, TryFromDictError> {
-        value
-            .iter_shared()
+impl<T: TryFromValue> TryFromValue for BTreeMap<TileCoords, T> {
+    fn try_from_value(value: &Value) -> Result<Self, TryFromDictError> {
+        let Value::Map(entries) = value else {
+            return Err(TryFromDictError::InvalidType(
+                "tile map".into(),
+                ErasedConvertError::unexpected("map", value),
+            ));
+        };
+
+        entries
+            .iter()
             .map(|(key, value)| {
-                let key = key
-                    .try_to()
-                    .map_err(ErasedConvertError::from)
-                    .map_err(TryFromDictError::InvalidKey)
-                    .and_then(|val| array_to_tuple(&val))?;
+                let key = TileCoords::from_value(key).map_err(TryFromDictError::InvalidKey)?;
 
-                let value: VarDictionary = value.try_to().map_err(|err| {
-                    TryFromDictError::InvalidType(format!("{key:?}").into(), err.into())
-                })?;
+                if !matches!(value, Value::Map(_)) {
+                    return Err(TryFromDictError::InvalidType(
+                        format!("{key:?}").into(),
+                        ErasedConvertError::unexpected("map", value),
+                    ));
+                }
 
-                Ok((key, T::try_from_dict(&value)?))
+                Ok((key, T::try_from_value(value)?))
             })
             .collect()
     }
 }
 
+impl FromValue for u8 {
+    fn from_value(value: &Value) -> Result<Self, ErasedConvertError> {
+        int_from_value(value)
+    }
+}
+
+impl FromValue for u32 {
+    fn from_value(value: &Value) -> Result<Self, ErasedConvertError> {
+        int_from_value(value)
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: &Value) -> Result<Self, ErasedConvertError> {
+        match value {
+            Value::Str(string) => Ok(string.clone()),
+            _ => Err(ErasedConvertError::unexpected("string", value)),
+        }
+    }
+}
+
+impl FromValue for TileCoords {
+    fn from_value(value: &Value) -> Result<Self, ErasedConvertError> {
+        match value {
+            Value::Array(items) if items.len() == 2 => {
+                Ok((u32::from_value(&items[0])?, u32::from_value(&items[1])?))
+            }
+            _ => Err(ErasedConvertError::unexpected("tile coordinates", value)),
+        }
+    }
+}
+
+fn int_from_value<T: TryFrom<i64>>(value: &Value) -> Result<T, ErasedConvertError> {
+    let Value::Int(int) = value else {
+        return Err(ErasedConvertError::unexpected("integer", value));
+    };
+
+    T::try_from(*int).map_err(|_| ErasedConvertError {
+        message: format!("integer {int} is out of range"),
+    })
+}
+
+fn get_value_key<'a>(value: &'a Value, key: &'static str) -> Result<&'a Value, TryFromDictError> {
+    value.get(key).ok_or(TryFromDictError::MissingKey(key))
+}
+
+fn get_value_field<T: FromValue>(value: &Value, key: &'static str) -> Result<T, TryFromDictError> {
+    get_value_key(value, key).and_then(|field| {
+        T::from_value(field).map_err(|err| TryFromDictError::InvalidType(key.into(), err))
+    })
+}
+
 fn get_dict_key<T: FromGodot>(
     value: &VarDictionary,
     key: &'static str,
@@ -360,22 +500,6 @@ fn get_dict_key<T: FromGodot>(
         .map_err(|err| TryFromDictError::InvalidType(key.into(), err.into()))
 }
 
-fn get_dict_key_optional<T: FromGodot>(
-    value: &VarDictionary,
-    key: &'static str,
-) -> Result<Option<T>, TryFromDictError> {
-    let variant = value.get_or_nil(key);
-
-    if variant.is_nil() {
-        return Ok(None);
-    }
-
-    variant
-        .try_to()
-        .map_err(|err| TryFromDictError::InvalidType(key.into(), err.into()))
-        .map(Some)
-}
-
 fn array_to_tuple(value: &VarArray) -> Result<TileCoords, TryFromDictError> {
     Ok((
         value
@@ -390,3 +514,87 @@ fn array_to_tuple(value: &VarArray) -> Result<TileCoords, TryFromDictError> {
             .map_err(|err| TryFromDictError::InvalidType("(x, _)".into(), err.into()))?,
     ))
 }
+
+/// Tiles and cities for unit tests.
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use std::collections::BTreeMap;
+
+    use super::{
+        Building, City, SimulatorSettings, TerrainSlope, TerrainType, Tile, TileCoords, TileList,
+        TileTerrainInfo,
+    };
+
+    /// Side length of the cities created by [`city_tiles`] and [`city`].
+    pub(crate) const CITY_SIZE: u32 = 8;
+
+    /// A dry tile without a building.
+    pub(crate) fn tile(coordinates: TileCoords, altitude: u32, slope: TerrainSlope) -> Tile {
+        Tile {
+            terrain: TileTerrainInfo {
+                ty: TerrainType::DryLand,
+                slope,
+            },
+            altitude,
+            building: None,
+            coordinates,
+        }
+    }
+
+    /// A square of flat dry tiles.
+    pub(crate) fn flat_tilelist(size: u32, altitude: u32) -> TileList {
+        (0..size)
+            .flat_map(|x| (0..size).map(move |y| (x, y)))
+            .map(|coords| (coords, tile(coords, altitude, TerrainSlope::None)))
+            .collect()
+    }
+
+    fn building(anchor: TileCoords, id: u8, size: u8) -> Building {
+        Building {
+            size,
+            name: format!("{id:#04x}"),
+            id,
+            tile_coords: anchor,
+        }
+    }
+
+    /// A flat city with the buildings `(anchor, id, size)`. Like in the city files, the anchor is
+    /// the tile with the lowest x and the highest y coordinate of a building.
+    pub(crate) fn city_tiles(buildings: &[(TileCoords, u8, u8)]) -> TileList {
+        let mut tiles = flat_tilelist(CITY_SIZE, 0);
+
+        for (anchor, id, size) in buildings {
+            let building = building(*anchor, *id, *size);
+
+            for dx in 0..u32::from(*size) {
+                for dy in 0..u32::from(*size) {
+                    let tile = tiles
+                        .get_mut(&(anchor.0 + dx, anchor.1 - dy))
+                        .expect("building is inside the city");
+
+                    tile.building = Some(building.clone());
+                }
+            }
+        }
+
+        tiles
+    }
+
+    /// The same city as [`city_tiles`], including its list of buildings.
+    pub(crate) fn city(buildings: &[(TileCoords, u8, u8)]) -> City {
+        City {
+            name: None,
+            simulator_settings: SimulatorSettings {
+                sea_level: 0,
+                compass: 0,
+            },
+            buildings: buildings
+                .iter()
+                .map(|(anchor, id, size)| (*anchor, building(*anchor, *id, *size)))
+                .collect(),
+            networks: BTreeMap::new(),
+            tilelist: city_tiles(buildings),
+            city_size: CITY_SIZE,
+        }
+    }
+}
@@ -5,6 +5,7 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/.
  */
 
+use super::Compass;
 use crate::util::logger;
 
 /// Describes the raised corners of a tile
@@ -133,6 +134,20 @@ pub(crate) enum TerrainSlope {
 }
 
 impl TerrainSlope {
+    /// Slopes and altitude offsets a tile in the given direction of this slope can have.
+    pub const fn valid_neighbors(self, direction: Compass) -> &'static [(Self, i8)] {
+        match direction {
+            Compass::North => self.valid_north_neighbors(),
+            Compass::East => self.valid_east_neighbor(),
+            Compass::South => self.valid_south_neighbors(),
+            Compass::West => self.valid_west_neighbors(),
+            Compass::NorthWest => self.valid_north_west_neighbors(),
+            Compass::NorthEast => self.valid_north_east_neighbors(),
+            Compass::SouthWest => self.valid_south_west_neighbors(),
+            Compass::SouthEast => self.valid_south_east_neighbors(),
+        }
+    }
+
     #[expect(clippy::too_many_lines)]
     pub const fn valid_north_neighbors(self) -> &'static [(Self, i8)] {
         match self {
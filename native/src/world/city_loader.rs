@@ -0,0 +1,231 @@
+/*
+ * Copyright (c) SimChopper; Jovan Gerodetti and contributors.
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Instant;
+
+use godot::classes::FileAccess;
+use godot::prelude::*;
+use godot::task;
+use kanal::ReceiveError;
+
+use crate::util::async_support::{godot_future, GodotFuture};
+use crate::util::logger;
+use crate::world::city_data::{City, ToDictionary, TryFromDictError, TryFromValue};
+use crate::world::sc2_file::{self, Sc2FileError};
+
+pub(crate) mod msgpack;
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum CityLoaderError {
+    #[error("unable to read \"{0}\"")]
+    Read(String),
+    #[error("unable to decompress city data")]
+    Decompress,
+    #[error(transparent)]
+    MsgPack(#[from] msgpack::MsgPackError),
+    #[error("city data is not a map")]
+    InvalidDocument,
+    #[error("map header field \"{0}\" has an unexpected type")]
+    InvalidHeader(&'static str),
+    #[error(transparent)]
+    CityData(#[from] TryFromDictError),
+    #[error(transparent)]
+    Sc2File(#[from] Sc2FileError),
+}
+
+/// Loads a city map on a background thread.
+///
+/// Supports the compressed `.sc2.mpz` maps as well as the original `.sc2` files.
+#[derive(GodotClass)]
+#[class(base = RefCounted, init)]
+struct CityLoader;
+
+#[godot_api]
+impl CityLoader {
+    /// Load the city at `path`. The returned future completes with a [`CityHandle`], or `null` if
+    /// the city could not be loaded.
+    #[func]
+    #[expect(clippy::needless_pass_by_value)]
+    fn load_async(path: GString) -> Gd<GodotFuture> {
+        let path = path.to_string();
+        let (resolve, future) = godot_future::<Option<Gd<CityHandle>>>();
+        let (tx, rx) = kanal::bounded::<Result<City, CityLoaderError>>(1);
+
+        std::thread::spawn({
+            let path = path.clone();
+
+            move || {
+                let timer = Instant::now();
+                let result = load_city(&path);
+
+                logger::info!(
+                    "city load time for \"{}\": {}ms",
+                    path,
+                    timer.elapsed().as_millis()
+                );
+
+                if let Err(err) = tx.send(result) {
+                    logger::error!("Failed to send loaded city: {}", err);
+                }
+            }
+        });
+
+        task::spawn(async move {
+            match rx.as_async().recv().await {
+                Ok(Ok(city)) => resolve(Some(CityHandle::new_gd(Arc::new(city)))),
+                Ok(Err(err)) => {
+                    logger::error!("failed to load city \"{}\": {}", path, err);
+                    resolve(None);
+                }
+                Err(ReceiveError::SendClosed | ReceiveError::Closed) => {
+                    logger::error!("city loader thread has disconnected before completing!");
+                    resolve(None);
+                }
+            }
+        });
+
+        future
+    }
+}
+
+/// Load the city at `path` on the current thread.
+fn load_city(path: &str) -> Result<City, CityLoaderError> {
+    let bytes = FileAccess::get_file_as_bytes(path);
+
+    if bytes.is_empty() {
+        return Err(CityLoaderError::Read(path.to_owned()));
+    }
+
+    parse_city(bytes.as_slice(), path)
+}
+
+/// Load the city at a path of the file system instead of the godot resources, for tools and
+/// tests that run without the engine.
+pub(crate) fn load_city_file(path: &Path) -> Result<City, CityLoaderError> {
+    let bytes =
+        std::fs::read(path).map_err(|_| CityLoaderError::Read(path.display().to_string()))?;
+
+    parse_city(&bytes, &path.to_string_lossy())
+}
+
+/// Parse the content of a city file. The file name decides if it is an original `.sc2` file or a
+/// compressed `.sc2.mpz` map.
+fn parse_city(bytes: &[u8], file_name: &str) -> Result<City, CityLoaderError> {
+    let is_sc2 = Path::new(file_name)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("sc2"));
+
+    if is_sc2 {
+        return Ok(sc2_file::read_city(bytes)?);
+    }
+
+    let document = msgpack::decode(&decompress_map(bytes)?)?;
+
+    if !matches!(document, msgpack::Value::Map(_)) {
+        return Err(CityLoaderError::InvalidDocument);
+    }
+
+    Ok(City::try_from_value(&document)?)
+}
+
+/// Decompress the content of a `.sc2.mpz` map into its `MessagePack` document. The maps are
+/// compressed with the DEFLATE mode of godot, which writes a zlib stream.
+pub(crate) fn decompress_map(bytes: &[u8]) -> Result<Vec<u8>, CityLoaderError> {
+    miniz_oxide::inflate::decompress_to_vec_zlib(bytes).map_err(|_| CityLoaderError::Decompress)
+}
+
+/// Shared handle to a loaded city. The city data is parsed once and can be passed to every part
+/// of the world that has to be built from it.
+#[derive(GodotClass)]
+#[class(base = RefCounted, no_init)]
+pub struct CityHandle {
+    city: Arc<City>,
+}
+
+impl CityHandle {
+    fn new_gd(city: Arc<City>) -> Gd<Self> {
+        Gd::from_object(Self { city })
+    }
+
+    pub(crate) fn city(&self) -> &Arc<City> {
+        &self.city
+    }
+}
+
+#[godot_api]
+impl CityHandle {
+    /// The name of the city, empty if the map does not store a valid name.
+    #[func]
+    fn name(&self) -> GString {
+        self.city.name.as_deref().unwrap_or_default().into()
+    }
+
+    #[func]
+    fn city_size(&self) -> u32 {
+        self.city.city_size
+    }
+
+    #[func]
+    fn sea_level(&self) -> u32 {
+        self.city.simulator_settings.sea_level
+    }
+
+    #[func]
+    fn compass(&self) -> u8 {
+        self.city.simulator_settings.compass
+    }
+
+    #[func]
+    fn building_count(&self) -> u32 {
+        u32::try_from(self.city.buildings.len()).unwrap_or(u32::MAX)
+    }
+
+    #[func]
+    fn network_count(&self) -> u32 {
+        u32::try_from(self.city.networks.len()).unwrap_or(u32::MAX)
+    }
+
+    /// All network sections of the city as building dictionaries.
+    #[func]
+    fn network_sections(&self) -> Array<VarDictionary> {
+        self.city
+            .networks
+            .values()
+            .map(ToDictionary::to_dict)
+            .collect()
+    }
+
+    /// The tile at the given coordinates as a dictionary. The dictionary is empty if the
+    /// coordinates are outside of the city.
+    #[func]
+    fn tile(&self, x: u32, y: u32) -> VarDictionary {
+        self.city
+            .tilelist
+            .get(&(x, y))
+            .map(ToDictionary::to_dict)
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use super::parse_city;
+
+    #[test]
+    fn bundled_map_parses_without_the_engine() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../resources/Maps/Demo.sc2.mpz");
+        let bytes = std::fs::read(path).expect("demo map is readable");
+        let city = parse_city(&bytes, "Demo.sc2.mpz").expect("demo map is valid");
+
+        assert_eq!(city.simulator_settings.compass, 3);
+        assert_eq!(city.tilelist.len(), 128 * 128);
+    }
+}
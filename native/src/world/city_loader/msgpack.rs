@@ -0,0 +1,291 @@
+/*
+ * Copyright (c) SimChopper; Jovan Gerodetti and contributors.
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! Minimal `MessagePack` decoder. It does not depend on the engine, so maps can also be read
+//! outside of godot, like in the tests.
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum MsgPackError {
+    #[error("unexpected end of data at byte {0}")]
+    UnexpectedEnd(usize),
+    #[error("unsupported type marker 0x{marker:02X} at byte {offset}")]
+    UnsupportedType { marker: u8, offset: usize },
+    #[error("expected a map at byte {0}")]
+    ExpectedMap(usize),
+    #[error("expected a string at byte {0}")]
+    ExpectedString(usize),
+    #[error("expected an integer at byte {0}")]
+    ExpectedInteger(usize),
+}
+
+/// A decoded `MessagePack` value.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Value {
+    Nil,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+    Bin(Vec<u8>),
+    Array(Vec<Value>),
+    Map(Vec<(Value, Value)>),
+}
+
+impl Value {
+    /// The value of a map entry with a string key.
+    pub(crate) fn get(&self, key: &str) -> Option<&Value> {
+        let Self::Map(entries) = self else {
+            return None;
+        };
+
+        entries
+            .iter()
+            .find(|(entry_key, _)| matches!(entry_key, Self::Str(entry_key) if entry_key == key))
+            .map(|(_, value)| value)
+    }
+
+    pub(crate) fn type_name(&self) -> &'static str {
+        match self {
+            Self::Nil => "nil",
+            Self::Bool(_) => "bool",
+            Self::Int(_) => "integer",
+            Self::Float(_) => "float",
+            Self::Str(_) => "string",
+            Self::Bin(_) => "binary",
+            Self::Array(_) => "array",
+            Self::Map(_) => "map",
+        }
+    }
+}
+
+pub(crate) fn decode(data: &[u8]) -> Result<Value, MsgPackError> {
+    Reader::new(data).read_value()
+}
+
+pub(crate) struct Reader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self { data, offset: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], MsgPackError> {
+        let bytes = self
+            .data
+            .get(self.offset..self.offset + len)
+            .ok_or(MsgPackError::UnexpectedEnd(self.offset))?;
+
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N], MsgPackError> {
+        self.take(N)
+            .map(|bytes| bytes.try_into().expect("slice has the requested length"))
+    }
+
+    fn read_u8(&mut self) -> Result<u8, MsgPackError> {
+        self.take_array::<1>().map(|[byte]| byte)
+    }
+
+    fn read_u16(&mut self) -> Result<usize, MsgPackError> {
+        self.take_array().map(u16::from_be_bytes).map(usize::from)
+    }
+
+    fn read_u32(&mut self) -> Result<usize, MsgPackError> {
+        self.take_array()
+            .map(u32::from_be_bytes)
+            .map(|value| value as usize)
+    }
+
+    fn read_str(&mut self, len: usize) -> Result<Value, MsgPackError> {
+        self.take(len)
+            .map(|bytes| Value::Str(String::from_utf8_lossy(bytes).into_owned()))
+    }
+
+    fn read_bin(&mut self, len: usize) -> Result<Value, MsgPackError> {
+        self.take(len).map(|bytes| Value::Bin(bytes.to_vec()))
+    }
+
+    fn read_array(&mut self, len: usize) -> Result<Value, MsgPackError> {
+        (0..len)
+            .map(|_| self.read_value())
+            .collect::<Result<_, _>>()
+            .map(Value::Array)
+    }
+
+    fn read_map(&mut self, len: usize) -> Result<Value, MsgPackError> {
+        let mut map = Vec::new();
+
+        for _ in 0..len {
+            let key = self.read_value()?;
+            let value = self.read_value()?;
+
+            map.push((key, value));
+        }
+
+        Ok(Value::Map(map))
+    }
+
+    /// Read the header of a map and return the number of key value pairs that follow.
+    pub(crate) fn read_map_len(&mut self) -> Result<usize, MsgPackError> {
+        let offset = self.offset;
+
+        match self.read_u8()? {
+            marker @ 0x80..=0x8F => Ok(usize::from(marker & 0x0F)),
+            0xDE => self.read_u16(),
+            0xDF => self.read_u32(),
+            _ => Err(MsgPackError::ExpectedMap(offset)),
+        }
+    }
+
+    /// Read the bytes of the next string or binary value, without converting them to UTF-8.
+    pub(crate) fn read_raw_str(&mut self) -> Result<&'a [u8], MsgPackError> {
+        let offset = self.offset;
+
+        let len = match self.read_u8()? {
+            marker @ 0xA0..=0xBF => usize::from(marker & 0x1F),
+            0xC4 | 0xD9 => usize::from(self.read_u8()?),
+            0xC5 | 0xDA => self.read_u16()?,
+            0xC6 | 0xDB => self.read_u32()?,
+            _ => return Err(MsgPackError::ExpectedString(offset)),
+        };
+
+        self.take(len)
+    }
+
+    /// Read the next integer value.
+    pub(crate) fn read_int(&mut self) -> Result<i64, MsgPackError> {
+        let offset = self.offset;
+
+        match self.read_u8()? {
+            marker @ (0x00..=0x7F | 0xE0..=0xFF) => Ok(i64::from(i8::from_be_bytes([marker]))),
+            0xCC => self.read_u8().map(i64::from),
+            0xCD => self.take_array().map(u16::from_be_bytes).map(i64::from),
+            0xCE => self.take_array().map(u32::from_be_bytes).map(i64::from),
+            0xCF | 0xD3 => self.take_array().map(i64::from_be_bytes),
+            0xD0 => self.take_array().map(i8::from_be_bytes).map(i64::from),
+            0xD1 => self.take_array().map(i16::from_be_bytes).map(i64::from),
+            0xD2 => self.take_array().map(i32::from_be_bytes).map(i64::from),
+            _ => Err(MsgPackError::ExpectedInteger(offset)),
+        }
+    }
+
+    /// Skip over the next value without decoding it.
+    pub(crate) fn skip_value(&mut self) -> Result<(), MsgPackError> {
+        let offset = self.offset;
+        let marker = self.read_u8()?;
+
+        let skip_bytes = match marker {
+            0x00..=0x7F | 0xC0 | 0xC2 | 0xC3 | 0xE0..=0xFF => 0,
+            0x80..=0x8F => return self.skip_values(usize::from(marker & 0x0F) * 2),
+            0x90..=0x9F => return self.skip_values(usize::from(marker & 0x0F)),
+            0xA0..=0xBF => usize::from(marker & 0x1F),
+            0xCC | 0xD0 => 1,
+            0xCD | 0xD1 => 2,
+            0xCA | 0xCE | 0xD2 => 4,
+            0xCB | 0xCF | 0xD3 => 8,
+            0xC4 | 0xD9 => usize::from(self.read_u8()?),
+            0xC5 | 0xDA => self.read_u16()?,
+            0xC6 | 0xDB => self.read_u32()?,
+            0xDC => {
+                let len = self.read_u16()?;
+                return self.skip_values(len);
+            }
+            0xDD => {
+                let len = self.read_u32()?;
+                return self.skip_values(len);
+            }
+            0xDE => {
+                let len = self.read_u16()?;
+                return self.skip_values(len * 2);
+            }
+            0xDF => {
+                let len = self.read_u32()?;
+                return self.skip_values(len * 2);
+            }
+            0xC1 | 0xC7..=0xC9 | 0xD4..=0xD8 => {
+                return Err(MsgPackError::UnsupportedType { marker, offset })
+            }
+        };
+
+        self.take(skip_bytes).map(drop)
+    }
+
+    pub(crate) fn skip_values(&mut self, count: usize) -> Result<(), MsgPackError> {
+        (0..count).try_for_each(|_| self.skip_value())
+    }
+
+    pub(crate) fn read_value(&mut self) -> Result<Value, MsgPackError> {
+        let offset = self.offset;
+        let marker = self.read_u8()?;
+
+        match marker {
+            0x00..=0x7F | 0xE0..=0xFF | 0xCC..=0xD3 => {
+                // integers are read again as a whole.
+                self.offset = offset;
+                self.read_int().map(Value::Int)
+            }
+            0x80..=0x8F => self.read_map(usize::from(marker & 0x0F)),
+            0x90..=0x9F => self.read_array(usize::from(marker & 0x0F)),
+            0xA0..=0xBF => self.read_str(usize::from(marker & 0x1F)),
+            0xC0 => Ok(Value::Nil),
+            0xC2 => Ok(Value::Bool(false)),
+            0xC3 => Ok(Value::Bool(true)),
+            0xC4 => {
+                let len = usize::from(self.read_u8()?);
+                self.read_bin(len)
+            }
+            0xC5 => {
+                let len = self.read_u16()?;
+                self.read_bin(len)
+            }
+            0xC6 => {
+                let len = self.read_u32()?;
+                self.read_bin(len)
+            }
+            0xCA => Ok(Value::Float(f64::from(f32::from_be_bytes(
+                self.take_array()?,
+            )))),
+            0xCB => Ok(Value::Float(f64::from_be_bytes(self.take_array()?))),
+            0xD9 => {
+                let len = usize::from(self.read_u8()?);
+                self.read_str(len)
+            }
+            0xDA => {
+                let len = self.read_u16()?;
+                self.read_str(len)
+            }
+            0xDB => {
+                let len = self.read_u32()?;
+                self.read_str(len)
+            }
+            0xDC => {
+                let len = self.read_u16()?;
+                self.read_array(len)
+            }
+            0xDD => {
+                let len = self.read_u32()?;
+                self.read_array(len)
+            }
+            0xDE => {
+                let len = self.read_u16()?;
+                self.read_map(len)
+            }
+            0xDF => {
+                let len = self.read_u32()?;
+                self.read_map(len)
+            }
+            0xC1 | 0xC7..=0xC9 | 0xD4..=0xD8 => {
+                Err(MsgPackError::UnsupportedType { marker, offset })
+            }
+        }
+    }
+}
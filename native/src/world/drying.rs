@@ -0,0 +1,124 @@
+/*
+ * Copyright (c) SimChopper; Jovan Gerodetti and contributors.
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! How fast wet surfaces dry.
+//!
+//! The wetness of a fresh patch is `1.0`. Further hits soak the patch up to [`MAX_WETNESS`] and
+//! make it grow, the patch is dry once its wetness reaches zero.
+
+/// Wetness of a patch that has been hit by water for a long time.
+pub(crate) const MAX_WETNESS: f32 = 4.0;
+
+/// Wetness gained per second of being hit by water.
+const SOAK_RATE: f32 = 2.0;
+
+/// Wetness lost per second at night, without any heat.
+const BASE_RATE: f32 = 0.02;
+
+/// Additional wetness lost per second in bright sunlight.
+const SUN_RATE: f32 = 0.1;
+
+/// Brightness of the sun around noon in lux.
+const SUN_LUX_FULL: f32 = 73_728.0;
+
+/// Additional wetness lost per second right next to a fire of strength `1.0`.
+const HEAT_RATE: f32 = 0.5;
+
+/// Distance in meters at which the heat of a fire stops drying the ground.
+pub(crate) const HEAT_RADIUS: f32 = 48.0;
+
+/// Growth of a patch per unit of wetness above a fresh patch.
+const GROWTH: f32 = 0.25;
+
+/// The kind of surface a patch of water landed on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum Surface {
+    /// Roads and rails heat up in the sun and do not absorb any water.
+    Asphalt,
+    Roof,
+    /// The ground holds the water the longest.
+    #[default]
+    Grass,
+}
+
+impl Surface {
+    fn drying_factor(self) -> f32 {
+        match self {
+            Self::Asphalt => 1.5,
+            Self::Roof => 1.0,
+            Self::Grass => 0.6,
+        }
+    }
+}
+
+/// Heat of a fire of the given strength at a distance in meters.
+pub(crate) fn heat(distance: f32, strength: f32) -> f32 {
+    strength.max(0.0) * (1.0 - distance / HEAT_RADIUS).max(0.0)
+}
+
+/// Wetness a patch on the surface loses per second.
+pub(crate) fn drying_rate(surface: Surface, sun_brightness: f32, heat: f32) -> f32 {
+    let sun = (sun_brightness / SUN_LUX_FULL).clamp(0.0, 1.0);
+
+    (BASE_RATE + sun * SUN_RATE + heat * HEAT_RATE) * surface.drying_factor()
+}
+
+/// Wetness of a patch after being hit by water for `delta` seconds.
+pub(crate) fn soak(wetness: f32, delta: f32) -> f32 {
+    (wetness.max(1.0) + delta * SOAK_RATE).min(MAX_WETNESS)
+}
+
+/// Size of a patch relative to a fresh patch.
+pub(crate) fn patch_scale(wetness: f32) -> f32 {
+    1.0 + (wetness - 1.0).clamp(0.0, MAX_WETNESS) * GROWTH
+}
+
+/// Opacity of a patch, it only starts to fade during the last bit of drying.
+pub(crate) fn opacity(wetness: f32) -> f32 {
+    wetness.clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{drying_rate, heat, patch_scale, soak, Surface, HEAT_RADIUS, MAX_WETNESS};
+
+    #[test]
+    fn sun_and_heat_speed_up_drying() {
+        let night = drying_rate(Surface::Roof, 0.0, 0.0);
+        let noon = drying_rate(Surface::Roof, 80_000.0, 0.0);
+        let fire = drying_rate(Surface::Roof, 0.0, heat(10.0, 1.0));
+
+        assert!(night > 0.0);
+        assert!(noon > night);
+        assert!(fire > night);
+        assert!(drying_rate(Surface::Asphalt, 0.0, 0.0) > drying_rate(Surface::Grass, 0.0, 0.0));
+    }
+
+    #[test]
+    fn heat_fades_with_distance() {
+        assert!((heat(0.0, 2.0) - 2.0).abs() < f32::EPSILON);
+        assert!(heat(10.0, 1.0) > heat(20.0, 1.0));
+        assert!(heat(HEAT_RADIUS + 1.0, 1.0).abs() < f32::EPSILON);
+        assert!(heat(0.0, -1.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn repeated_hits_grow_the_patch() {
+        let mut wetness = 1.0;
+
+        for _ in 0..10 {
+            wetness = soak(wetness, 0.5);
+        }
+
+        assert!((wetness - MAX_WETNESS).abs() < f32::EPSILON);
+        assert!(patch_scale(wetness) > patch_scale(1.0));
+        assert!((patch_scale(0.5) - 1.0).abs() < f32::EPSILON);
+
+        // a patch that has almost dried is soaked again.
+        assert!(soak(0.1, 0.0) >= 1.0);
+    }
+}
@@ -0,0 +1,117 @@
+/*
+ * Copyright (c) SimChopper; Jovan Gerodetti and contributors.
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! Dispatch rules of the fire engines.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::road_navigation::Side;
+use crate::world::city_data::{TileCoords, TileList};
+
+/// Number of fire engines a station can have on the road at the same time.
+pub(crate) const ENGINES_PER_STATION: usize = 2;
+
+/// Number of tiles between two tiles when moving along the axes.
+pub(crate) fn distance(a: TileCoords, b: TileCoords) -> u32 {
+    a.0.abs_diff(b.0) + a.1.abs_diff(b.1)
+}
+
+/// The closest fire station that still has an engine available. `deployed` holds the number of
+/// engines each station has on the road.
+pub(crate) fn nearest_station(
+    stations: &[TileCoords],
+    deployed: &BTreeMap<TileCoords, usize>,
+    building: TileCoords,
+) -> Option<TileCoords> {
+    stations
+        .iter()
+        .copied()
+        .filter(|station| deployed.get(station).copied().unwrap_or(0) < ENGINES_PER_STATION)
+        .min_by_key(|station| distance(*station, building))
+}
+
+/// The tiles right next to a building, from where a fire engine can reach it. The tiles are
+/// ordered by their distance to `from`.
+pub(crate) fn access_tiles(
+    tiles: &TileList,
+    building: TileCoords,
+    from: TileCoords,
+) -> Vec<TileCoords> {
+    let footprint: BTreeSet<TileCoords> = tiles
+        .values()
+        .filter(|tile| {
+            tile.building
+                .as_ref()
+                .is_some_and(|tile_building| tile_building.tile_coords == building)
+        })
+        .map(|tile| tile.coordinates)
+        .collect();
+
+    let mut access: Vec<_> = footprint
+        .iter()
+        .flat_map(|coords| {
+            Side::ALL
+                .into_iter()
+                .filter_map(|side| side.neighbor(*coords))
+        })
+        .filter(|coords| !footprint.contains(coords))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+
+    access.sort_by_key(|coords| distance(*coords, from));
+    access
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use super::{access_tiles, nearest_station, ENGINES_PER_STATION};
+    use crate::objects::scene_object_registry::Buildings;
+    use crate::world::city_data::fixtures::city_tiles;
+
+    #[test]
+    fn closest_station_is_dispatched() {
+        let stations = [(2, 2), (10, 3), (5, 20)];
+
+        let deployed = BTreeMap::new();
+
+        assert_eq!(nearest_station(&stations, &deployed, (8, 4)), Some((10, 3)));
+        assert_eq!(
+            nearest_station(&stations, &deployed, (4, 17)),
+            Some((5, 20))
+        );
+        assert_eq!(nearest_station(&[], &deployed, (4, 17)), None);
+    }
+
+    #[test]
+    fn busy_stations_are_skipped() {
+        let stations = [(2, 2), (10, 3)];
+        let mut deployed = BTreeMap::from([((10, 3), ENGINES_PER_STATION - 1)]);
+
+        assert_eq!(nearest_station(&stations, &deployed, (8, 4)), Some((10, 3)));
+
+        deployed.insert((10, 3), ENGINES_PER_STATION);
+        assert_eq!(nearest_station(&stations, &deployed, (8, 4)), Some((2, 2)));
+
+        deployed.insert((2, 2), ENGINES_PER_STATION);
+        assert_eq!(nearest_station(&stations, &deployed, (8, 4)), None);
+    }
+
+    #[test]
+    fn access_tiles_surround_the_building() {
+        let tiles = city_tiles(&[((2, 3), Buildings::HomeLowerClass1 as u8, 2)]);
+
+        let access = access_tiles(&tiles, (2, 3), (5, 3));
+
+        assert_eq!(access.len(), 8);
+        assert_eq!(access[0], (4, 3));
+        assert!(!access.contains(&(2, 2)));
+        assert!(!access.contains(&(1, 1)));
+    }
+}
@@ -0,0 +1,394 @@
+/*
+ * Copyright (c) SimChopper; Jovan Gerodetti and contributors.
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use godot::classes::{Node, RefCounted};
+use godot::obj::Gd;
+use godot::register::GodotClass;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::util::logger;
+use crate::world::city_data::{Building, City, TileCoords, TileList};
+
+/// Spreads fire from burning buildings to the buildings on neighbouring tiles.
+///
+/// Buildings are identified by the coordinates of their anchor tile. All randomness comes from a
+/// seeded rng, so the same seed and the same sequence of calls always ignite the same buildings.
+#[derive(Debug)]
+pub(crate) struct FireSpread {
+    rng: StdRng,
+    burning: BTreeMap<TileCoords, f32>,
+}
+
+impl FireSpread {
+    /// Fires below this strength are too weak to jump over to other buildings.
+    pub const MIN_SPREAD_STRENGTH: f32 = 0.5;
+
+    /// Maximum distance in tiles between two buildings that fire can bridge.
+    const SPREAD_RANGE: u32 = 2;
+
+    /// Chance of a full strength fire to ignite an adjacent, fully flammable building in a single
+    /// step.
+    const SPREAD_RATE: f64 = 0.25;
+
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            burning: BTreeMap::new(),
+        }
+    }
+
+    /// Updates the fire strength of a building. A strength of zero extinguishes the fire.
+    pub fn set_fire_strength(&mut self, building: TileCoords, strength: f32) {
+        if strength <= 0.0 {
+            self.burning.remove(&building);
+            return;
+        }
+
+        self.burning.insert(building, strength.min(1.0));
+    }
+
+    /// All burning buildings and their fire strength.
+    pub fn burning(&self) -> impl Iterator<Item = (TileCoords, f32)> + '_ {
+        self.burning
+            .iter()
+            .map(|(building, strength)| (*building, *strength))
+    }
+
+    /// Chance of each building that is not burning yet to catch fire during the next step.
+    ///
+    /// Buildings that can not catch fire are omitted.
+    pub fn ignition_chances(&self, tiles: &TileList) -> BTreeMap<TileCoords, f64> {
+        // chance of every building to not be ignited by any of the burning buildings.
+        let mut spared: BTreeMap<TileCoords, f64> = BTreeMap::new();
+
+        let sources = self
+            .burning
+            .iter()
+            .filter(|(_, strength)| **strength >= Self::MIN_SPREAD_STRENGTH);
+
+        for (source, strength) in sources {
+            let Some(source_building) = tiles.get(source).and_then(|tile| tile.building.as_ref())
+            else {
+                continue;
+            };
+
+            let size_weight = f64::from(source_building.size.max(1)).sqrt();
+
+            for (target, (distance, flammability)) in neighbours(source_building, tiles) {
+                if self.burning.contains_key(&target) || flammability <= 0.0 {
+                    continue;
+                }
+
+                let chance =
+                    (Self::SPREAD_RATE * f64::from(*strength) * size_weight * flammability
+                        / f64::from(distance.pow(2)))
+                    .min(1.0);
+
+                *spared.entry(target).or_insert(1.0) *= 1.0 - chance;
+            }
+        }
+
+        spared
+            .into_iter()
+            .map(|(building, spared)| (building, 1.0 - spared))
+            .collect()
+    }
+
+    /// Rolls the ignition of all buildings next to a fire. Returns the buildings that caught
+    /// fire, they start burning at full strength.
+    pub fn step(&mut self, tiles: &TileList) -> Vec<TileCoords> {
+        let ignited: Vec<_> = self
+            .ignition_chances(tiles)
+            .into_iter()
+            .filter(|(_, chance)| self.rng.random::<f64>() < *chance)
+            .map(|(building, _)| building)
+            .collect();
+
+        for building in &ignited {
+            self.burning.insert(*building, 1.0);
+        }
+
+        ignited
+    }
+}
+
+/// Finds all buildings within [`FireSpread::SPREAD_RANGE`] of the source building. Returns the
+/// distance in tiles between both buildings and the flammability of the neighbour.
+fn neighbours(source: &Building, tiles: &TileList) -> BTreeMap<TileCoords, (u32, f64)> {
+    let (anchor_x, anchor_y) = source.tile_coords;
+    let size = u32::from(source.size.max(1));
+
+    let footprint: Vec<TileCoords> = tiles
+        .range((anchor_x.saturating_sub(size - 1), 0)..=(anchor_x + size - 1, u32::MAX))
+        .filter(|((_, y), tile)| {
+            y.abs_diff(anchor_y) < size
+                && tile
+                    .building
+                    .as_ref()
+                    .is_some_and(|building| building.tile_coords == source.tile_coords)
+        })
+        .map(|(coords, _)| *coords)
+        .collect();
+
+    let Some(min_x) = footprint.iter().map(|(x, _)| *x).min() else {
+        return BTreeMap::new();
+    };
+    let max_x = footprint.iter().map(|(x, _)| *x).max().unwrap_or(min_x);
+    let min_y = footprint.iter().map(|(_, y)| *y).min().unwrap_or(anchor_y);
+    let max_y = footprint.iter().map(|(_, y)| *y).max().unwrap_or(anchor_y);
+
+    let range = FireSpread::SPREAD_RANGE;
+    let mut neighbours = BTreeMap::new();
+
+    let candidates = tiles
+        .range((min_x.saturating_sub(range), 0)..=(max_x.saturating_add(range), u32::MAX))
+        .filter(|((_, y), _)| {
+            *y >= min_y.saturating_sub(range) && *y <= max_y.saturating_add(range)
+        });
+
+    for ((x, y), tile) in candidates {
+        let Some(building) = tile.building.as_ref() else {
+            continue;
+        };
+
+        if building.tile_coords == source.tile_coords {
+            continue;
+        }
+
+        let distance = footprint
+            .iter()
+            .map(|(fx, fy)| x.abs_diff(*fx).max(y.abs_diff(*fy)))
+            .min()
+            .unwrap_or(u32::MAX);
+
+        if distance > range {
+            continue;
+        }
+
+        neighbours
+            .entry(building.tile_coords)
+            .and_modify(|(current, _)| *current = distance.min(*current))
+            .or_insert((distance, flammability(building.id)));
+    }
+
+    neighbours
+}
+
+/// How easily a building catches fire, from `0.0` (never) to `1.0`.
+pub(crate) fn flammability(building_id: u8) -> f64 {
+    match building_id {
+        // gas stations and chemical plants
+        0x7C | 0x7F | 0x85 | 0x9F | 0xBC => 1.0,
+        // homes, apartments, condominiums and the mayor's house
+        0x70..=0x7B | 0x8C..=0x93 | 0xAE..=0xB1 | 0xF3 => 0.9,
+        // trees
+        0x06..=0x0C => 0.8,
+        // commercial and industrial buildings, construction sites and abandoned buildings
+        0x7D..=0x7E
+        | 0x80..=0x84
+        | 0x86..=0x8B
+        | 0x94..=0x9E
+        | 0xA0..=0xAD
+        | 0xB2..=0xBB
+        | 0xBD..=0xC5 => 0.6,
+        // civic buildings, depots, the library and the church
+        0xD0..=0xD4 | 0xD6..=0xDA | 0xEC | 0xED | 0xF5 | 0xF7 => 0.4,
+        // parks
+        0x0D | 0xD5 => 0.3,
+        // ground, networks, power plants, airport, sea port, military and arcologies
+        _ => 0.0,
+    }
+}
+
+/// Runs the [`FireSpread`] of a loaded city and hands new ignitions to the building nodes.
+#[derive(GodotClass)]
+#[class(base = RefCounted, no_init)]
+pub struct FireSimulation {
+    spread: FireSpread,
+    city: Arc<City>,
+    /// Buildings that have to start burning and the strength of their fire.
+    pending_ignitions: BTreeMap<TileCoords, f32>,
+    /// Buildings that started burning and still have to be reported to the fire department.
+    alarms: Vec<(TileCoords, Gd<Node>)>,
+    elapsed: f64,
+}
+
+impl FireSimulation {
+    /// Seconds between two spread steps.
+    const STEP_INTERVAL: f64 = 5.0;
+
+    pub(crate) fn new_gd(city: Arc<City>, seed: u64) -> Gd<Self> {
+        Gd::from_object(Self {
+            spread: FireSpread::new(seed),
+            city,
+            pending_ignitions: BTreeMap::new(),
+            alarms: Vec::new(),
+            elapsed: 0.0,
+        })
+    }
+
+    /// Reports the current fire strength of a burning building.
+    pub(crate) fn set_fire_strength(&mut self, building: TileCoords, strength: f32) {
+        self.spread.set_fire_strength(building, strength);
+    }
+
+    /// Checks if fire has spread to the building and returns the strength of the new fire. Every
+    /// ignition can only be taken once.
+    pub(crate) fn take_ignition(&mut self, building: TileCoords) -> Option<f32> {
+        self.pending_ignitions.remove(&building)
+    }
+
+    /// Raises the alarm for a building that just started burning.
+    pub(crate) fn report_alarm(&mut self, building: TileCoords, node: Gd<Node>) {
+        self.alarms.push((building, node));
+    }
+
+    /// All alarms that have been raised since the last call.
+    pub(crate) fn take_alarms(&mut self) -> Vec<(TileCoords, Gd<Node>)> {
+        std::mem::take(&mut self.alarms)
+    }
+
+    pub(crate) fn is_burning(&self, building: TileCoords) -> bool {
+        self.spread
+            .burning()
+            .any(|(burning, _)| burning == building)
+    }
+
+    /// The anchor tile and fire strength of every burning building, as it is stored in a saved
+    /// session.
+    pub(crate) fn fires(&self) -> Vec<(TileCoords, f32)> {
+        self.spread.burning().collect()
+    }
+
+    /// The city buildings that are burning and their fire strength. Fires on tiles that are not
+    /// a building of the city are left out.
+    pub(crate) fn burning_buildings(&self) -> Vec<(Building, f32)> {
+        self.spread
+            .burning()
+            .filter_map(|(coords, strength)| {
+                Some((self.city.buildings.get(&coords)?.clone(), strength))
+            })
+            .collect()
+    }
+
+    /// Sets a building on fire with the given strength, e.g. when a saved session is restored.
+    pub(crate) fn ignite(&mut self, building: TileCoords, strength: f32) {
+        self.spread.set_fire_strength(building, strength);
+        self.pending_ignitions.insert(building, strength);
+        // give the building a full step interval to take the ignition.
+        self.elapsed = 0.0;
+    }
+
+    pub(crate) fn advance(&mut self, delta: f64) {
+        self.elapsed += delta;
+
+        if self.elapsed < Self::STEP_INTERVAL {
+            return;
+        }
+
+        self.elapsed = 0.0;
+
+        // Buildings without a fire feature never take their ignition and must not keep burning.
+        for building in std::mem::take(&mut self.pending_ignitions).into_keys() {
+            self.spread.set_fire_strength(building, 0.0);
+        }
+
+        let ignited = self.spread.step(&self.city.tilelist);
+
+        if !ignited.is_empty() {
+            logger::info!("Fire spread to buildings: {:?}", ignited);
+        }
+
+        self.pending_ignitions
+            .extend(ignited.into_iter().map(|building| (building, 1.0)));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::FireSpread;
+    use crate::world::city_data::fixtures::city_tiles as fixture;
+
+    const HOME: u8 = 0x70;
+    const ROAD: u8 = 0x1D;
+
+    #[test]
+    fn chance_decreases_with_distance() {
+        let tiles = fixture(&[
+            ((2, 2), HOME, 1),
+            ((3, 2), HOME, 1),
+            ((4, 2), HOME, 1),
+            ((5, 2), HOME, 1),
+        ]);
+        let mut spread = FireSpread::new(0);
+
+        spread.set_fire_strength((2, 2), 1.0);
+
+        let chances = spread.ignition_chances(&tiles);
+
+        assert!(chances[&(3, 2)] > chances[&(4, 2)]);
+        assert!(chances[&(4, 2)] > 0.0);
+        assert!(!chances.contains_key(&(5, 2)));
+    }
+
+    #[test]
+    fn weak_fires_and_non_flammable_buildings() {
+        let tiles = fixture(&[((2, 2), HOME, 1), ((3, 2), HOME, 1), ((1, 2), ROAD, 1)]);
+        let mut spread = FireSpread::new(0);
+
+        spread.set_fire_strength((2, 2), FireSpread::MIN_SPREAD_STRENGTH / 2.0);
+        assert!(spread.ignition_chances(&tiles).is_empty());
+
+        spread.set_fire_strength((2, 2), 1.0);
+        let chances = spread.ignition_chances(&tiles);
+
+        assert!(chances.contains_key(&(3, 2)));
+        assert!(!chances.contains_key(&(1, 2)));
+    }
+
+    #[test]
+    fn large_buildings_spread_further() {
+        let small = fixture(&[((2, 3), HOME, 1), ((3, 3), HOME, 1)]);
+        let large = fixture(&[((1, 3), HOME, 2), ((3, 3), HOME, 1)]);
+        let mut spread = FireSpread::new(0);
+
+        spread.set_fire_strength((2, 3), 1.0);
+        let small_chance = spread.ignition_chances(&small)[&(3, 3)];
+
+        spread.set_fire_strength((2, 3), 0.0);
+        spread.set_fire_strength((1, 3), 1.0);
+        let large_chance = spread.ignition_chances(&large)[&(3, 3)];
+
+        assert!(large_chance > small_chance);
+    }
+
+    #[test]
+    fn spread_is_deterministic() {
+        let buildings: Vec<_> = (0..8)
+            .flat_map(|x| (0..8).map(move |y| ((x, y), HOME, 1)))
+            .collect();
+        let tiles = fixture(&buildings);
+
+        let run = |seed| {
+            let mut spread = FireSpread::new(seed);
+
+            spread.set_fire_strength((4, 4), 1.0);
+
+            (0..6).map(|_| spread.step(&tiles)).collect::<Vec<_>>()
+        };
+
+        let first = run(42);
+
+        assert_eq!(first, run(42));
+        assert!(first.iter().any(|ignited| !ignited.is_empty()));
+        assert!(first.concat().iter().all(|building| *building != (4, 4)));
+    }
+}
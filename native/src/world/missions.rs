@@ -0,0 +1,613 @@
+/*
+ * Copyright (c) SimChopper; Jovan Gerodetti and contributors.
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::collections::BTreeMap;
+
+use rand::rngs::StdRng;
+use rand::seq::{IndexedRandom, SliceRandom};
+use rand::SeedableRng;
+
+use crate::objects::scene_object_registry;
+use crate::world::city_data::{Building, City, TileCoords};
+use crate::world::fire_spread;
+use crate::world::save_game::{Reader, SaveGameError, Writer};
+
+/// Side length of a sector in tiles.
+pub(crate) const SECTOR_SIZE: u32 = 32;
+
+/// Number of in-game minutes per day.
+const MINUTES_PER_DAY: f64 = 24.0 * 60.0;
+
+/// Maximum number of fires that are started for a single fire mission.
+const MAX_MISSION_FIRES: usize = 3;
+
+/// Amount of the riot that is dispersed by one second of water impact.
+const RIOT_DISPERSAL_RATE: f64 = 0.1;
+
+/// Amount of the riot that is dispersed by one second of teargas at full density.
+const TEARGAS_DISPERSAL_RATE: f64 = 0.25;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Objective {
+    /// Extinguish a number of fires inside a sector.
+    ExtinguishFires { sector: TileCoords, count: u32 },
+    /// Fly to a building.
+    ReachBuilding { building: TileCoords, name: String },
+    /// Disperse the riot in front of a building.
+    ClearRiot { building: TileCoords },
+}
+
+/// Events of the game world that can advance a mission.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum MissionEvent {
+    FireExtinguished(TileCoords),
+    BuildingReached(TileCoords),
+    WaterImpact(TileCoords, f64),
+    TeargasImpact(TileCoords, f64),
+}
+
+/// Kind of objective of a [`MissionDefinition`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ObjectiveKind {
+    ExtinguishFires,
+    ReachBuilding,
+    ClearRiot,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum MissionDefinitionError {
+    #[error("there is no building at {0:?}")]
+    NoBuilding(TileCoords),
+    #[error("fire missions require at least one fire")]
+    NoFires,
+    #[error("sector {sector:?} has {available} buildings that can burn, {count} are required")]
+    NotEnoughFires {
+        sector: TileCoords,
+        available: usize,
+        count: u32,
+    },
+}
+
+/// A hand written mission of a mission chain.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct MissionDefinition {
+    pub objective: ObjectiveKind,
+    /// The target building, or any tile inside the target sector of a fire mission.
+    pub target: TileCoords,
+    /// Number of fires of a fire mission.
+    pub fire_count: u32,
+    /// Time limit in in-game minutes.
+    pub time_limit: f64,
+    pub reward: u32,
+}
+
+impl MissionDefinition {
+    /// Creates the mission for the city. The fires of a fire mission are started in the first
+    /// buildings of the sector that can burn.
+    pub fn build(&self, city: &City) -> Result<Mission, MissionDefinitionError> {
+        let objective = match self.objective {
+            ObjectiveKind::ExtinguishFires => return self.build_fire_mission(city),
+            ObjectiveKind::ReachBuilding => {
+                let building = self.building(city)?;
+
+                Objective::ReachBuilding {
+                    building: building.tile_coords,
+                    name: building.name.clone(),
+                }
+            }
+            ObjectiveKind::ClearRiot => Objective::ClearRiot {
+                building: self.building(city)?.tile_coords,
+            },
+        };
+
+        Ok(Mission::new(objective, self.time_limit, self.reward))
+    }
+
+    fn build_fire_mission(&self, city: &City) -> Result<Mission, MissionDefinitionError> {
+        let sector = sector_of(self.target);
+        let count = usize::try_from(self.fire_count).expect("fire count should fit into usize");
+
+        if count == 0 {
+            return Err(MissionDefinitionError::NoFires);
+        }
+
+        let ignitions: Vec<_> = city
+            .buildings
+            .values()
+            .filter(|building| {
+                sector_of(building.tile_coords) == sector
+                    && scene_object_registry::can_burn(building.id)
+            })
+            .map(|building| building.tile_coords)
+            .take(count)
+            .collect();
+
+        if ignitions.len() < count {
+            return Err(MissionDefinitionError::NotEnoughFires {
+                sector,
+                available: ignitions.len(),
+                count: self.fire_count,
+            });
+        }
+
+        let mut mission = Mission::new(
+            Objective::ExtinguishFires {
+                sector,
+                count: self.fire_count,
+            },
+            self.time_limit,
+            self.reward,
+        );
+
+        mission.ignitions = ignitions;
+        Ok(mission)
+    }
+
+    /// The building that covers the target tile.
+    fn building<'city>(
+        &self,
+        city: &'city City,
+    ) -> Result<&'city Building, MissionDefinitionError> {
+        city.tilelist
+            .get(&self.target)
+            .and_then(|tile| tile.building.as_ref())
+            .and_then(|building| city.buildings.get(&building.tile_coords))
+            .filter(|building| scene_object_registry::has_building(building.id))
+            .ok_or(MissionDefinitionError::NoBuilding(self.target))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MissionState {
+    Pending,
+    Active,
+    Succeeded,
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Mission {
+    pub objective: Objective,
+    /// Time limit in in-game minutes.
+    pub time_limit: f64,
+    pub reward: u32,
+    /// Buildings that start burning when the mission starts.
+    pub ignitions: Vec<TileCoords>,
+    progress: f64,
+    started_at: f64,
+    state: MissionState,
+}
+
+impl Mission {
+    fn new(objective: Objective, time_limit: f64, reward: u32) -> Self {
+        Self {
+            objective,
+            time_limit,
+            reward,
+            ignitions: Vec::new(),
+            progress: 0.0,
+            started_at: 0.0,
+            state: MissionState::Pending,
+        }
+    }
+
+    pub fn state(&self) -> MissionState {
+        self.state
+    }
+
+    pub fn title(&self) -> String {
+        match &self.objective {
+            Objective::ExtinguishFires { .. } => "Fire Emergency".into(),
+            Objective::ReachBuilding { name, .. } => format!("Urgent Flight to {name}"),
+            Objective::ClearRiot { .. } => "Riot".into(),
+        }
+    }
+
+    pub fn description(&self) -> String {
+        let minutes = self.time_limit;
+
+        match &self.objective {
+            Objective::ExtinguishFires { sector, count } => format!(
+                "Extinguish {count} fires in sector {}-{} within {minutes} minutes.",
+                sector.0, sector.1
+            ),
+            Objective::ReachBuilding { building, name } => format!(
+                "Reach {name} at {}-{} within {minutes} minutes.",
+                building.0, building.1
+            ),
+            Objective::ClearRiot { building } => format!(
+                "Clear the riot at {}-{} within {minutes} minutes.",
+                building.0, building.1
+            ),
+        }
+    }
+
+    /// Starts the mission at the given in-game time.
+    pub fn start(&mut self, now: f64) {
+        self.started_at = now;
+        self.state = MissionState::Active;
+    }
+
+    /// In-game minutes until the mission fails.
+    pub fn remaining_time(&self, now: f64) -> f64 {
+        // the in-game time wraps around at the end of each day.
+        let elapsed = (now - self.started_at).rem_euclid(MINUTES_PER_DAY);
+
+        (self.time_limit - elapsed).max(0.0)
+    }
+
+    pub fn handle_event(&mut self, event: MissionEvent) {
+        if self.state != MissionState::Active {
+            return;
+        }
+
+        let done = match (&self.objective, event) {
+            (
+                Objective::ExtinguishFires { sector, count },
+                MissionEvent::FireExtinguished(building),
+            ) if sector_of(building) == *sector => {
+                self.progress += 1.0;
+                self.progress >= f64::from(*count)
+            }
+            (Objective::ReachBuilding { building, .. }, MissionEvent::BuildingReached(target)) => {
+                *building == target
+            }
+            (Objective::ClearRiot { building }, MissionEvent::WaterImpact(target, amount))
+                if *building == target =>
+            {
+                self.progress += amount * RIOT_DISPERSAL_RATE;
+                self.progress >= 1.0
+            }
+            (Objective::ClearRiot { building }, MissionEvent::TeargasImpact(target, amount))
+                if *building == target =>
+            {
+                self.progress += amount * TEARGAS_DISPERSAL_RATE;
+                self.progress >= 1.0
+            }
+            _ => false,
+        };
+
+        if done {
+            self.state = MissionState::Succeeded;
+        }
+    }
+
+    /// Fails the mission once the time limit has been exceeded.
+    pub fn update(&mut self, now: f64) {
+        if self.state == MissionState::Active && self.remaining_time(now) <= 0.0 {
+            self.state = MissionState::Failed;
+        }
+    }
+
+    pub(crate) fn write(&self, writer: &mut Writer) {
+        match &self.objective {
+            Objective::ExtinguishFires { sector, count } => {
+                writer.u8(0);
+                writer.tile_coords(*sector);
+                writer.u32(*count);
+            }
+            Objective::ReachBuilding { building, name } => {
+                writer.u8(1);
+                writer.tile_coords(*building);
+                writer.str(name);
+            }
+            Objective::ClearRiot { building } => {
+                writer.u8(2);
+                writer.tile_coords(*building);
+            }
+        }
+
+        writer.f64(self.time_limit);
+        writer.u32(self.reward);
+        writer.seq(&self.ignitions, |writer, building| {
+            writer.tile_coords(*building)
+        });
+        writer.f64(self.progress);
+        writer.f64(self.started_at);
+        writer.u8(match self.state {
+            MissionState::Pending => 0,
+            MissionState::Active => 1,
+            MissionState::Succeeded => 2,
+            MissionState::Failed => 3,
+        });
+    }
+
+    pub(crate) fn read(reader: &mut Reader) -> Result<Self, SaveGameError> {
+        let objective = match reader.u8()? {
+            0 => Objective::ExtinguishFires {
+                sector: reader.tile_coords()?,
+                count: reader.u32()?,
+            },
+            1 => Objective::ReachBuilding {
+                building: reader.tile_coords()?,
+                name: reader.str()?,
+            },
+            2 => Objective::ClearRiot {
+                building: reader.tile_coords()?,
+            },
+            _ => return Err(SaveGameError::InvalidValue("mission objective")),
+        };
+
+        Ok(Self {
+            objective,
+            time_limit: reader.f64()?,
+            reward: reader.u32()?,
+            ignitions: reader.seq(Reader::tile_coords)?,
+            progress: reader.f64()?,
+            started_at: reader.f64()?,
+            state: match reader.u8()? {
+                0 => MissionState::Pending,
+                1 => MissionState::Active,
+                2 => MissionState::Succeeded,
+                3 => MissionState::Failed,
+                _ => return Err(SaveGameError::InvalidValue("mission state")),
+            },
+        })
+    }
+}
+
+/// Coordinates of the sector that contains the tile.
+pub(crate) fn sector_of((x, y): TileCoords) -> TileCoords {
+    (
+        (x / SECTOR_SIZE) * SECTOR_SIZE,
+        (y / SECTOR_SIZE) * SECTOR_SIZE,
+    )
+}
+
+/// Plans a chain of missions for the city. The same seed always results in the same chain.
+pub(crate) fn plan_missions(city: &City, seed: u64) -> Vec<Mission> {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    // only buildings that exist in the world can be part of a mission.
+    let candidates: Vec<&Building> = city
+        .buildings
+        .values()
+        .filter(|building| scene_object_registry::has_building(building.id))
+        .collect();
+
+    let mut missions: Vec<Mission> = [
+        plan_fire_mission(&candidates, &mut rng),
+        plan_reach_mission(&candidates, &mut rng),
+        plan_riot_mission(&candidates, &mut rng),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    missions.shuffle(&mut rng);
+    missions
+}
+
+fn plan_fire_mission(candidates: &[&Building], rng: &mut StdRng) -> Option<Mission> {
+    let mut sectors: BTreeMap<TileCoords, Vec<TileCoords>> = BTreeMap::new();
+
+    // only buildings with fire emission points can actually burn.
+    for building in candidates
+        .iter()
+        .filter(|building| scene_object_registry::can_burn(building.id))
+    {
+        sectors
+            .entry(sector_of(building.tile_coords))
+            .or_default()
+            .push(building.tile_coords);
+    }
+
+    let (sector, buildings) = sectors
+        .into_iter()
+        .max_by_key(|(_, buildings)| buildings.len())?;
+
+    let ignitions: Vec<_> = buildings
+        .choose_multiple(rng, MAX_MISSION_FIRES)
+        .copied()
+        .collect();
+    let count = u32::try_from(ignitions.len()).expect("fire count is small");
+
+    let mut mission = Mission::new(
+        Objective::ExtinguishFires { sector, count },
+        40.0 * f64::from(count),
+        750 * count,
+    );
+
+    mission.ignitions = ignitions;
+
+    Some(mission)
+}
+
+fn plan_reach_mission(candidates: &[&Building], rng: &mut StdRng) -> Option<Mission> {
+    // prefer civic buildings like the hospital or city hall.
+    let civic: Vec<_> = candidates
+        .iter()
+        .filter(|building| matches!(building.id, 0xD0..=0xDA | 0xF3 | 0xF5 | 0xF7))
+        .collect();
+
+    let building = civic
+        .choose(rng)
+        .copied()
+        .or_else(|| candidates.choose(rng))?;
+
+    Some(Mission::new(
+        Objective::ReachBuilding {
+            building: building.tile_coords,
+            name: building.name.clone(),
+        },
+        60.0,
+        500,
+    ))
+}
+
+fn plan_riot_mission(candidates: &[&Building], rng: &mut StdRng) -> Option<Mission> {
+    // riots break out in residential and commercial areas.
+    let streets: Vec<_> = candidates
+        .iter()
+        .filter(|building| building.id >= 0x70 && fire_spread::flammability(building.id) > 0.0)
+        .collect();
+
+    let building = streets.choose(rng)?;
+
+    Some(Mission::new(
+        Objective::ClearRiot {
+            building: building.tile_coords,
+        },
+        90.0,
+        1000,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        plan_missions, MissionDefinition, MissionDefinitionError, MissionEvent, MissionState,
+        Objective, ObjectiveKind,
+    };
+    use crate::objects::scene_object_registry::Buildings;
+    use crate::world::city_data::fixtures::city;
+
+    const BURNING_HOME: u8 = Buildings::HomeMiddleClass3 as u8;
+    const HOME: u8 = Buildings::HomeLowerClass1 as u8;
+
+    fn definition(
+        objective: ObjectiveKind,
+        target: (u32, u32),
+        fire_count: u32,
+    ) -> MissionDefinition {
+        MissionDefinition {
+            objective,
+            target,
+            fire_count,
+            time_limit: 30.0,
+            reward: 100,
+        }
+    }
+
+    #[test]
+    fn same_seed_plans_same_missions() {
+        let city = city(&[
+            ((1, 1), BURNING_HOME, 1),
+            ((3, 1), HOME, 1),
+            ((5, 1), HOME, 1),
+        ]);
+
+        let objectives = |seed| {
+            plan_missions(&city, seed)
+                .into_iter()
+                .map(|mission| (mission.objective, mission.ignitions))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(objectives(7).len(), 3);
+        assert_eq!(objectives(7), objectives(7));
+    }
+
+    #[test]
+    fn fires_only_start_in_buildings_that_burn() {
+        let city = city(&[
+            ((1, 1), BURNING_HOME, 1),
+            ((3, 1), HOME, 1),
+            ((4, 1), HOME, 1),
+            ((5, 1), HOME, 1),
+        ]);
+
+        let fire_mission = plan_missions(&city, 1)
+            .into_iter()
+            .find(|mission| matches!(mission.objective, Objective::ExtinguishFires { .. }))
+            .expect("city has a building that can burn");
+
+        assert_eq!(fire_mission.ignitions, vec![(1, 1)]);
+        assert_eq!(
+            fire_mission.objective,
+            Objective::ExtinguishFires {
+                sector: (0, 0),
+                count: 1
+            }
+        );
+    }
+
+    #[test]
+    fn extinguished_fires_complete_the_mission() {
+        let city = city(&[((1, 1), BURNING_HOME, 1), ((3, 1), BURNING_HOME, 1)]);
+        let mut mission = definition(ObjectiveKind::ExtinguishFires, (6, 6), 2)
+            .build(&city)
+            .unwrap();
+
+        assert_eq!(mission.ignitions, vec![(1, 1), (3, 1)]);
+
+        // events are ignored until the mission has started.
+        mission.handle_event(MissionEvent::FireExtinguished((1, 1)));
+        assert_eq!(mission.state(), MissionState::Pending);
+
+        mission.start(0.0);
+        mission.handle_event(MissionEvent::FireExtinguished((1, 1)));
+        // fires outside of the sector do not count.
+        mission.handle_event(MissionEvent::FireExtinguished((40, 40)));
+        assert_eq!(mission.state(), MissionState::Active);
+
+        mission.handle_event(MissionEvent::FireExtinguished((3, 1)));
+        assert_eq!(mission.state(), MissionState::Succeeded);
+    }
+
+    #[test]
+    fn water_and_teargas_disperse_the_riot() {
+        let city = city(&[((2, 3), HOME, 2)]);
+        let mut mission = definition(ObjectiveKind::ClearRiot, (3, 2), 0)
+            .build(&city)
+            .unwrap();
+
+        assert_eq!(mission.objective, Objective::ClearRiot { building: (2, 3) });
+
+        mission.start(0.0);
+        mission.handle_event(MissionEvent::WaterImpact((2, 3), 5.0));
+        mission.handle_event(MissionEvent::TeargasImpact((5, 5), 10.0));
+        assert!((mission.progress - 0.5).abs() < f64::EPSILON);
+        assert_eq!(mission.state(), MissionState::Active);
+
+        mission.handle_event(MissionEvent::TeargasImpact((2, 3), 2.0));
+        assert_eq!(mission.state(), MissionState::Succeeded);
+    }
+
+    #[test]
+    fn mission_fails_after_the_time_limit() {
+        let city = city(&[((1, 1), HOME, 1)]);
+        let mut mission = definition(ObjectiveKind::ReachBuilding, (1, 1), 0)
+            .build(&city)
+            .unwrap();
+
+        // the mission starts shortly before midnight.
+        mission.start(1430.0);
+        mission.update(10.0);
+        assert!((mission.remaining_time(10.0) - 10.0).abs() < f64::EPSILON);
+        assert_eq!(mission.state(), MissionState::Active);
+
+        mission.update(20.0);
+        assert_eq!(mission.state(), MissionState::Failed);
+
+        // a failed mission can not be completed anymore.
+        mission.handle_event(MissionEvent::BuildingReached((1, 1)));
+        assert_eq!(mission.state(), MissionState::Failed);
+    }
+
+    #[test]
+    fn invalid_definitions() {
+        let city = city(&[((1, 1), BURNING_HOME, 1), ((3, 1), HOME, 1)]);
+
+        assert!(matches!(
+            definition(ObjectiveKind::ReachBuilding, (5, 5), 0).build(&city),
+            Err(MissionDefinitionError::NoBuilding((5, 5)))
+        ));
+        assert!(matches!(
+            definition(ObjectiveKind::ExtinguishFires, (1, 1), 0).build(&city),
+            Err(MissionDefinitionError::NoFires)
+        ));
+        assert!(matches!(
+            definition(ObjectiveKind::ExtinguishFires, (1, 1), 2).build(&city),
+            Err(MissionDefinitionError::NotEnoughFires {
+                sector: (0, 0),
+                available: 1,
+                count: 2
+            })
+        ));
+    }
+}
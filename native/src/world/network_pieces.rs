@@ -0,0 +1,277 @@
+/*
+ * Copyright (c) SimChopper; Jovan Gerodetti and contributors.
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! Placement rules of the network pieces of a city.
+
+use num_enum::TryFromPrimitive;
+
+use crate::objects::scene_object_registry::{
+    Bridge, Highway, HighwayBridge, HighwayOnRamp, Powerlines, Rail, RailBridge, RailCrossing,
+    Road, SubwayTransition, Tunnel,
+};
+use crate::road_navigation::Side;
+use crate::world::city_data::{TerrainSlope, Tile, TileCoords, TileList};
+
+/// The network a piece belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Network {
+    Powerline,
+    Road,
+    Rail,
+    /// Pieces that are shared by a road and a railway.
+    RoadAndRail,
+}
+
+impl Network {
+    pub fn of(building_id: u8) -> Option<Self> {
+        let is_highway_over_rail = matches!(
+            Highway::try_from_primitive(building_id),
+            Ok(Highway::LeftRightRailTopBottom | Highway::TopBottomRailLeftRight)
+        );
+
+        if is::<Powerlines>(building_id) {
+            Some(Self::Powerline)
+        } else if is::<RailCrossing>(building_id) || is_highway_over_rail {
+            Some(Self::RoadAndRail)
+        } else if is::<Rail>(building_id)
+            || is::<RailBridge>(building_id)
+            || is::<SubwayTransition>(building_id)
+        {
+            Some(Self::Rail)
+        } else if is::<Road>(building_id)
+            || is::<Bridge>(building_id)
+            || is::<Tunnel>(building_id)
+            || is_highway(building_id)
+        {
+            Some(Self::Road)
+        } else {
+            None
+        }
+    }
+}
+
+fn is<T: TryFromPrimitive<Primitive = u8>>(building_id: u8) -> bool {
+    T::try_from_primitive(building_id).is_ok()
+}
+
+/// Number of tile heights by which a network piece is raised above its tile.
+///
+/// Bridges, raised powerlines and highways span above the ground, on-ramps climb up to the
+/// highway by themselves. Pieces would disappear under fully raised terrain, so they are raised
+/// with it.
+pub(crate) fn elevation(building_id: u8, tile: &Tile) -> u8 {
+    let raised_piece = is::<Bridge>(building_id)
+        || is::<RailBridge>(building_id)
+        || is::<Highway>(building_id)
+        || is::<HighwayBridge>(building_id)
+        || matches!(
+            Powerlines::try_from_primitive(building_id),
+            Ok(Powerlines::BridgeTopBottom)
+        );
+
+    u8::from(raised_piece) + terrain_elevation(tile)
+}
+
+/// Number of tile heights by which fully raised terrain lifts everything that is placed on it.
+pub(crate) fn terrain_elevation(tile: &Tile) -> u8 {
+    u8::from(matches!(tile.terrain.slope, TerrainSlope::All))
+}
+
+/// Straight road and rail pieces, vehicles are spawned on them.
+pub(crate) fn is_straight(building_id: u8) -> bool {
+    matches!(
+        Road::try_from_primitive(building_id),
+        Ok(Road::LeftRight
+            | Road::TopBottom
+            | Road::LeftRightPowerTopBottom
+            | Road::TopBottomPowerLeftRight)
+    ) || matches!(
+        Rail::try_from_primitive(building_id),
+        Ok(Rail::LeftRight
+            | Rail::TopBottom
+            | Rail::LeftRightPowerTopBottom
+            | Rail::TopBottomPowerLeftRight)
+    )
+}
+
+/// The tiles around a network piece in the order north, east, south and west. Pieces that can be
+/// turned inspect them to find their orientation.
+pub(crate) fn neighbors(tiles: &TileList, tile_coords: TileCoords) -> [Option<&Tile>; 4] {
+    Side::ALL.map(|side| {
+        side.neighbor(tile_coords)
+            .and_then(|neighbor| tiles.get(&neighbor))
+    })
+}
+
+/// Highways are two tiles wide. Every straight segment has its barrier on the east or south edge,
+/// it has to be turned around if the other half of the highway lies to the west or north.
+pub(crate) fn is_turned_highway_segment(building_id: u8, neighbors: [Option<&Tile>; 4]) -> bool {
+    let runs_left_right = matches!(
+        Highway::try_from_primitive(building_id),
+        Ok(Highway::LeftRight
+            | Highway::LeftRightRoadTopBottom
+            | Highway::LeftRightRailTopBottom
+            | Highway::LeftRightPowerTopBottom)
+    ) || matches!(
+        HighwayBridge::try_from_primitive(building_id),
+        Ok(HighwayBridge::LeftRight)
+    );
+
+    let runs_top_bottom = matches!(
+        Highway::try_from_primitive(building_id),
+        Ok(Highway::TopBottom
+            | Highway::TopBottomRoadLeftRight
+            | Highway::TopBottomRailLeftRight
+            | Highway::TopBottomPowerLeftRight)
+    ) || matches!(
+        HighwayBridge::try_from_primitive(building_id),
+        Ok(HighwayBridge::TopBottom)
+    );
+
+    let [north, east, south, west] = neighbors.map(|tile| {
+        tile.and_then(|tile| tile.building.as_ref())
+            .is_some_and(|building| is_highway(building.id))
+    });
+
+    if runs_left_right {
+        west && !east
+    } else if runs_top_bottom {
+        north && !south
+    } else {
+        false
+    }
+}
+
+fn is_highway(building_id: u8) -> bool {
+    is::<Highway>(building_id)
+        || is::<HighwayOnRamp>(building_id)
+        || is::<HighwayBridge>(building_id)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{elevation, is_straight, is_turned_highway_segment, neighbors, Network};
+    use crate::objects::scene_object_registry::{
+        Buildings, Highway, HighwayOnRamp, Powerlines, PylonBridge, Rail, RailBridge, RailCrossing,
+        Road, SubwayTransition, SuspensionBridge, Tunnel,
+    };
+    use crate::world::city_data::fixtures::{city_tiles, tile};
+    use crate::world::city_data::{TerrainSlope, TileList};
+
+    #[test]
+    fn pieces_belong_to_their_network() {
+        assert_eq!(
+            Network::of(Powerlines::LeftRight as u8),
+            Some(Network::Powerline)
+        );
+        assert_eq!(
+            Network::of(Powerlines::BridgeTopBottom as u8),
+            Some(Network::Powerline)
+        );
+        assert_eq!(Network::of(Road::LeftRight as u8), Some(Network::Road));
+        assert_eq!(
+            Network::of(Road::LeftRightPowerTopBottom as u8),
+            Some(Network::Road)
+        );
+        assert_eq!(
+            Network::of(SuspensionBridge::StartBottom as u8),
+            Some(Network::Road)
+        );
+        assert_eq!(
+            Network::of(PylonBridge::BridgeTopB as u8),
+            Some(Network::Road)
+        );
+        assert_eq!(Network::of(Tunnel::Top as u8), Some(Network::Road));
+        assert_eq!(Network::of(Highway::LeftRight as u8), Some(Network::Road));
+        assert_eq!(
+            Network::of(HighwayOnRamp::TopLeft as u8),
+            Some(Network::Road)
+        );
+        assert_eq!(Network::of(Rail::LeftRight as u8), Some(Network::Rail));
+        assert_eq!(Network::of(RailBridge::Pylon as u8), Some(Network::Rail));
+        assert_eq!(
+            Network::of(SubwayTransition::Top as u8),
+            Some(Network::Rail)
+        );
+        assert_eq!(
+            Network::of(RailCrossing::RoadLeftRightRailTopBottom as u8),
+            Some(Network::RoadAndRail)
+        );
+        assert_eq!(
+            Network::of(Highway::LeftRightRailTopBottom as u8),
+            Some(Network::RoadAndRail)
+        );
+        // buildings are not part of a network
+        assert_eq!(Network::of(Buildings::HomeLowerClass1 as u8), None);
+    }
+
+    #[test]
+    fn raised_pieces() {
+        let flat = tile((0, 0), 3, TerrainSlope::None);
+        let raised = tile((0, 0), 3, TerrainSlope::All);
+
+        assert_eq!(elevation(Road::LeftRight as u8, &flat), 0);
+        assert_eq!(elevation(Road::LeftRight as u8, &raised), 1);
+        assert_eq!(elevation(SuspensionBridge::StartBottom as u8, &flat), 1);
+        assert_eq!(elevation(Powerlines::BridgeTopBottom as u8, &flat), 1);
+        assert_eq!(elevation(Highway::LeftRight as u8, &flat), 1);
+        assert_eq!(elevation(Highway::LeftRight as u8, &raised), 2);
+        assert_eq!(elevation(HighwayOnRamp::TopLeft as u8, &flat), 0);
+    }
+
+    #[test]
+    fn straight_pieces() {
+        assert!(is_straight(Road::LeftRight as u8));
+        assert!(is_straight(Road::TopBottomPowerLeftRight as u8));
+        assert!(is_straight(Rail::TopBottom as u8));
+        assert!(!is_straight(Road::LeftTopBottomRight as u8));
+        assert!(!is_straight(SuspensionBridge::StartBottom as u8));
+    }
+
+    #[test]
+    fn neighbors_in_compass_order() {
+        let tiles: TileList = [(1, 0), (2, 1), (1, 2)]
+            .into_iter()
+            .map(|coords| (coords, tile(coords, 3, TerrainSlope::None)))
+            .collect();
+
+        let [north, east, south, west] = neighbors(&tiles, (1, 1));
+
+        assert_eq!(north.map(|tile| tile.coordinates), Some((1, 0)));
+        assert_eq!(east.map(|tile| tile.coordinates), Some((2, 1)));
+        assert_eq!(south.map(|tile| tile.coordinates), Some((1, 2)));
+        assert!(west.is_none());
+    }
+
+    #[test]
+    fn highway_segments_face_away_from_their_other_half() {
+        let left_right = Highway::LeftRight as u8;
+        let top_bottom = Highway::TopBottom as u8;
+        let tiles = city_tiles(&[
+            ((1, 1), left_right, 1),
+            ((2, 1), left_right, 1),
+            ((5, 1), top_bottom, 1),
+            ((5, 2), top_bottom, 1),
+            ((4, 4), HighwayOnRamp::TopLeft as u8, 1),
+            ((4, 5), top_bottom, 1),
+            ((1, 4), Road::TopBottom as u8, 1),
+            ((1, 5), top_bottom, 1),
+        ]);
+
+        let turned = |coords, id| is_turned_highway_segment(id, neighbors(&tiles, coords));
+
+        // the other half of a left to right segment lies to the east or west.
+        assert!(turned((2, 1), left_right));
+        assert!(!turned((1, 1), left_right));
+        // the other half of a top to bottom segment lies to the south or north.
+        assert!(turned((5, 2), top_bottom));
+        assert!(!turned((5, 1), top_bottom));
+        // on-ramps are part of the highway, roads are not.
+        assert!(turned((4, 5), top_bottom));
+        assert!(!turned((1, 5), top_bottom));
+    }
+}
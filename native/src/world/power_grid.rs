@@ -0,0 +1,307 @@
+/*
+ * Copyright (c) SimChopper; Jovan Gerodetti and contributors.
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! Power supply of the buildings of a city.
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+use godot::classes::RefCounted;
+use godot::obj::Gd;
+use godot::register::GodotClass;
+use num_enum::TryFromPrimitive;
+
+use crate::objects::scene_object_registry::{is_power_plant, Highway, Powerlines, Rail, Road};
+use crate::road_navigation::Side;
+use crate::util::logger;
+use crate::world::city_data::{City, TileCoords, TileList};
+
+/// Buildings below this id are rubble, trees and parks, they do not conduct power.
+const FIRST_BUILDING_ID: u8 = 0x70;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PowerTile {
+    /// A power line, either on its own or crossing a road, rail or highway.
+    Line,
+    /// A tile of a building. Like in the original game, buildings pass power on to their
+    /// neighbours.
+    Building { anchor: TileCoords },
+    /// A tile of a power plant.
+    Plant { anchor: TileCoords },
+}
+
+impl PowerTile {
+    fn anchor(self) -> Option<TileCoords> {
+        match self {
+            Self::Line => None,
+            Self::Building { anchor } | Self::Plant { anchor } => Some(anchor),
+        }
+    }
+}
+
+/// Connectivity graph of all tiles that conduct power.
+///
+/// Tiles are connected to their four direct neighbours, buildings are powered as soon as one of
+/// their tiles is connected to an intact power plant.
+#[derive(Debug, Default)]
+pub(crate) struct PowerNetwork {
+    tiles: BTreeMap<TileCoords, PowerTile>,
+    destroyed: BTreeSet<TileCoords>,
+    /// Anchors of all buildings that are connected to a power plant.
+    powered: BTreeSet<TileCoords>,
+}
+
+impl PowerNetwork {
+    pub fn new(tiles: &TileList) -> Self {
+        let tiles = tiles
+            .iter()
+            .filter_map(|(coords, tile)| {
+                let building = tile.building.as_ref()?;
+
+                let power_tile = if carries_power(building.id) {
+                    PowerTile::Line
+                } else if is_power_plant(building.id) {
+                    PowerTile::Plant {
+                        anchor: building.tile_coords,
+                    }
+                } else if building.id >= FIRST_BUILDING_ID {
+                    PowerTile::Building {
+                        anchor: building.tile_coords,
+                    }
+                } else {
+                    return None;
+                };
+
+                Some((*coords, power_tile))
+            })
+            .collect();
+
+        let mut network = Self {
+            tiles,
+            destroyed: BTreeSet::new(),
+            powered: BTreeSet::new(),
+        };
+
+        network.update();
+        network
+    }
+
+    pub fn is_powered(&self, building: TileCoords) -> bool {
+        self.powered.contains(&building)
+    }
+
+    /// Removes a building that burned down from the grid. The fire also takes down the power
+    /// lines right next to the building. Returns the buildings that lost their power.
+    ///
+    /// Nothing in the city is rebuilt yet, so burned down tiles stay out of the grid until the
+    /// city is loaded again.
+    pub fn burn_down(&mut self, building: TileCoords) -> Vec<TileCoords> {
+        let footprint: Vec<_> = self
+            .tiles
+            .iter()
+            .filter(|(_, tile)| tile.anchor() == Some(building))
+            .map(|(coords, _)| *coords)
+            .collect();
+
+        let lines: Vec<_> = footprint
+            .iter()
+            .flat_map(|coords| {
+                Side::ALL
+                    .into_iter()
+                    .filter_map(|side| side.neighbor(*coords))
+            })
+            .filter(|coords| self.tiles.get(coords) == Some(&PowerTile::Line))
+            .collect();
+
+        self.destroyed.extend(footprint);
+        self.destroyed.extend(lines);
+
+        let previously_powered = std::mem::take(&mut self.powered);
+
+        self.update();
+
+        previously_powered
+            .difference(&self.powered)
+            .filter(|anchor| **anchor != building)
+            .copied()
+            .collect()
+    }
+
+    /// Floods the grid from all intact power plants.
+    fn update(&mut self) {
+        let mut queue: VecDeque<TileCoords> = self
+            .tiles
+            .iter()
+            .filter(|(coords, tile)| {
+                matches!(tile, PowerTile::Plant { .. }) && !self.destroyed.contains(coords)
+            })
+            .map(|(coords, _)| *coords)
+            .collect();
+
+        let mut visited: BTreeSet<TileCoords> = queue.iter().copied().collect();
+
+        while let Some(coords) = queue.pop_front() {
+            if let Some(anchor) = self.tiles.get(&coords).and_then(|tile| tile.anchor()) {
+                self.powered.insert(anchor);
+            }
+
+            for neighbor in Side::ALL
+                .into_iter()
+                .filter_map(|side| side.neighbor(coords))
+            {
+                if !self.tiles.contains_key(&neighbor) || self.destroyed.contains(&neighbor) {
+                    continue;
+                }
+
+                if visited.insert(neighbor) {
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+    }
+}
+
+/// Network pieces that carry a power line.
+fn carries_power(building_id: u8) -> bool {
+    Powerlines::try_from_primitive(building_id).is_ok()
+        || matches!(
+            Road::try_from_primitive(building_id),
+            Ok(Road::LeftRightPowerTopBottom | Road::TopBottomPowerLeftRight)
+        )
+        || matches!(
+            Rail::try_from_primitive(building_id),
+            Ok(Rail::LeftRightPowerTopBottom | Rail::TopBottomPowerLeftRight)
+        )
+        || matches!(
+            Highway::try_from_primitive(building_id),
+            Ok(Highway::LeftRightPowerTopBottom | Highway::TopBottomPowerLeftRight)
+        )
+}
+
+/// The [`PowerNetwork`] of a loaded city, shared by all building nodes.
+#[derive(GodotClass)]
+#[class(base = RefCounted, no_init)]
+pub struct PowerGrid {
+    network: PowerNetwork,
+}
+
+impl PowerGrid {
+    pub(crate) fn new_gd(city: &City) -> Gd<Self> {
+        let network = PowerNetwork::new(&city.tilelist);
+
+        logger::info!("{} buildings are powered", network.powered.len());
+
+        Gd::from_object(Self { network })
+    }
+
+    pub(crate) fn is_powered(&self, building: TileCoords) -> bool {
+        self.network.is_powered(building)
+    }
+
+    /// Reports a building that has burned down.
+    pub(crate) fn burn_down(&mut self, building: TileCoords) {
+        let outage = self.network.burn_down(building);
+
+        if !outage.is_empty() {
+            logger::info!(
+                "{} buildings lost their power after {:?} burned down",
+                outage.len(),
+                building
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::PowerNetwork;
+    use crate::objects::scene_object_registry::{Buildings, Powerlines, Road};
+    use crate::world::city_data::fixtures::city_tiles as fixture;
+
+    const PLANT: u8 = Buildings::PowerplantMicrowave as u8;
+    const COAL_PLANT: u8 = Buildings::PowerplantCoal as u8;
+    const HOME: u8 = Buildings::HomeLowerClass1 as u8;
+    const LINE: u8 = Powerlines::LeftRight as u8;
+    const ROAD: u8 = Road::LeftRight as u8;
+    const ROAD_AND_LINE: u8 = Road::LeftRightPowerTopBottom as u8;
+
+    #[test]
+    fn power_flows_through_lines_and_crossings() {
+        let tiles = fixture(&[
+            ((1, 2), PLANT, 2),
+            ((3, 2), LINE, 1),
+            ((4, 2), ROAD_AND_LINE, 1),
+            ((5, 2), HOME, 1),
+            ((5, 5), HOME, 1),
+        ]);
+        let network = PowerNetwork::new(&tiles);
+
+        assert!(network.is_powered((1, 2)));
+        assert!(network.is_powered((5, 2)));
+        assert!(!network.is_powered((5, 5)));
+    }
+
+    #[test]
+    fn every_plant_supplies_power() {
+        let tiles = fixture(&[
+            ((1, 3), COAL_PLANT, 3),
+            ((4, 2), LINE, 1),
+            ((5, 2), HOME, 1),
+        ]);
+        let network = PowerNetwork::new(&tiles);
+
+        assert!(network.is_powered((1, 3)));
+        assert!(network.is_powered((5, 2)));
+    }
+
+    #[test]
+    fn roads_do_not_conduct() {
+        let tiles = fixture(&[((1, 2), PLANT, 2), ((3, 2), ROAD, 1), ((4, 2), HOME, 1)]);
+        let network = PowerNetwork::new(&tiles);
+
+        assert!(!network.is_powered((4, 2)));
+    }
+
+    #[test]
+    fn buildings_pass_power_on() {
+        let tiles = fixture(&[
+            ((1, 2), PLANT, 2),
+            ((3, 2), HOME, 1),
+            ((4, 2), HOME, 1),
+            ((5, 2), HOME, 1),
+        ]);
+        let mut network = PowerNetwork::new(&tiles);
+
+        assert!(network.is_powered((5, 2)));
+        assert_eq!(network.burn_down((4, 2)), vec![(5, 2)]);
+        assert!(network.is_powered((3, 2)));
+    }
+
+    #[test]
+    fn burned_down_plant_cuts_the_grid() {
+        let tiles = fixture(&[((1, 2), PLANT, 2), ((3, 2), LINE, 1), ((4, 2), HOME, 1)]);
+        let mut network = PowerNetwork::new(&tiles);
+
+        assert_eq!(network.burn_down((1, 2)), vec![(4, 2)]);
+        assert!(!network.is_powered((1, 2)));
+    }
+
+    #[test]
+    fn fire_takes_down_adjacent_lines() {
+        let tiles = fixture(&[
+            ((1, 2), PLANT, 2),
+            ((3, 2), LINE, 1),
+            ((4, 2), LINE, 1),
+            ((5, 2), LINE, 1),
+            ((6, 2), HOME, 1),
+            ((4, 3), HOME, 1),
+        ]);
+        let mut network = PowerNetwork::new(&tiles);
+
+        assert!(network.is_powered((6, 2)));
+        assert_eq!(network.burn_down((4, 3)), vec![(6, 2)]);
+    }
+}
@@ -0,0 +1,443 @@
+/*
+ * Copyright (c) SimChopper; Jovan Gerodetti and contributors.
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! Compact binary format for saved sessions.
+//!
+//! A save only contains the state that changes while playing. The city itself is not part of the
+//! save, a session is always restored on top of a freshly built city. All values are stored in
+//! little endian byte order, sequences are prefixed with their length.
+
+use godot::builtin::{Basis, Transform3D, Vector3};
+
+use super::city_data::TileCoords;
+use super::missions::Mission;
+
+const MAGIC: &[u8; 4] = b"SCSV";
+const VERSION: u16 = 1;
+
+/// A car that drives on the road network.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SavedCar {
+    /// Name of the car spawner that owns the car.
+    pub spawner: String,
+    pub transform: Transform3D,
+    /// The navigation node the car is heading to.
+    pub target: Option<TileCoords>,
+}
+
+/// A water decal that has been left behind by the water cannon.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SavedDecal {
+    pub transform: Transform3D,
+    pub size: Vector3,
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SaveGame {
+    /// Path of the map the session has been played on.
+    pub map: String,
+    pub helicopter: Transform3D,
+    /// Resource paths of all upgrades the player owns.
+    pub upgrades: Vec<String>,
+    /// Money in the wallet of the player.
+    pub balance: u32,
+    /// Liters of water in the tank of the helicopter.
+    pub water: f32,
+    /// Time of the solar setup in ms.
+    pub time: u32,
+    /// Burning buildings and their fire strength.
+    pub fires: Vec<(TileCoords, f32)>,
+    pub cars: Vec<SavedCar>,
+    pub decals: Vec<SavedDecal>,
+    /// The remaining mission chain, starting with the active mission.
+    pub missions: Vec<Mission>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum SaveGameError {
+    #[error("file is not a saved session")]
+    InvalidHeader,
+    #[error("save version {0} is not supported")]
+    UnsupportedVersion(u16),
+    #[error("save ends unexpectedly")]
+    Truncated,
+    #[error("save contains an invalid string")]
+    InvalidString,
+    #[error("save contains an invalid {0}")]
+    InvalidValue(&'static str),
+    #[error("save belongs to the map {0}")]
+    OtherMap(String),
+}
+
+impl SaveGame {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer = Writer::default();
+
+        writer.bytes(MAGIC);
+        writer.u16(VERSION);
+
+        writer.str(&self.map);
+        writer.transform(self.helicopter);
+        writer.seq(&self.upgrades, |writer, path| writer.str(path));
+        writer.u32(self.balance);
+        writer.f32(self.water);
+        writer.u32(self.time);
+
+        writer.seq(&self.fires, |writer, (building, strength)| {
+            writer.tile_coords(*building);
+            writer.f32(*strength);
+        });
+
+        writer.seq(&self.cars, |writer, car| {
+            writer.str(&car.spawner);
+            writer.transform(car.transform);
+            writer.option(car.target.as_ref(), |writer, target| {
+                writer.tile_coords(*target);
+            });
+        });
+
+        writer.seq(&self.decals, |writer, decal| {
+            writer.transform(decal.transform);
+            writer.vector3(decal.size);
+        });
+
+        writer.seq(&self.missions, |writer, mission| mission.write(writer));
+
+        writer.finish()
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self, SaveGameError> {
+        let mut reader = Reader::new(data);
+
+        if reader.bytes(MAGIC.len())? != MAGIC {
+            return Err(SaveGameError::InvalidHeader);
+        }
+
+        let version = reader.u16()?;
+
+        if version != VERSION {
+            return Err(SaveGameError::UnsupportedVersion(version));
+        }
+
+        Ok(Self {
+            map: reader.str()?,
+            helicopter: reader.transform()?,
+            upgrades: reader.seq(Reader::str)?,
+            balance: reader.u32()?,
+            water: reader.f32()?,
+            time: reader.u32()?,
+            fires: reader.seq(|reader| Ok((reader.tile_coords()?, reader.f32()?)))?,
+            cars: reader.seq(|reader| {
+                Ok(SavedCar {
+                    spawner: reader.str()?,
+                    transform: reader.transform()?,
+                    target: reader.option(Reader::tile_coords)?,
+                })
+            })?,
+            decals: reader.seq(|reader| {
+                Ok(SavedDecal {
+                    transform: reader.transform()?,
+                    size: reader.vector3()?,
+                })
+            })?,
+            missions: reader.seq(Mission::read)?,
+        })
+    }
+
+    /// Rejects the save if it has been made on a different map. Sessions can only be restored on
+    /// top of the city they have been played in.
+    pub fn for_map(self, map: &str) -> Result<Self, SaveGameError> {
+        if self.map != map {
+            return Err(SaveGameError::OtherMap(self.map));
+        }
+
+        Ok(self)
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Writer {
+    buffer: Vec<u8>,
+}
+
+impl Writer {
+    fn finish(self) -> Vec<u8> {
+        self.buffer
+    }
+
+    fn bytes(&mut self, value: &[u8]) {
+        self.buffer.extend_from_slice(value);
+    }
+
+    pub fn u8(&mut self, value: u8) {
+        self.buffer.push(value);
+    }
+
+    pub fn u16(&mut self, value: u16) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    pub fn u32(&mut self, value: u32) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    pub fn f32(&mut self, value: f32) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    pub fn f64(&mut self, value: f64) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    pub fn str(&mut self, value: &str) {
+        self.len(value.len());
+        self.bytes(value.as_bytes());
+    }
+
+    pub fn tile_coords(&mut self, (x, y): TileCoords) {
+        self.u32(x);
+        self.u32(y);
+    }
+
+    pub fn vector3(&mut self, value: Vector3) {
+        self.f32(value.x);
+        self.f32(value.y);
+        self.f32(value.z);
+    }
+
+    pub fn transform(&mut self, value: Transform3D) {
+        for row in value.basis.rows {
+            self.vector3(row);
+        }
+
+        self.vector3(value.origin);
+    }
+
+    pub fn option<T>(&mut self, value: Option<&T>, write: impl FnOnce(&mut Self, &T)) {
+        self.u8(value.is_some().into());
+
+        if let Some(value) = value {
+            write(self, value);
+        }
+    }
+
+    pub fn seq<T>(&mut self, items: &[T], mut write: impl FnMut(&mut Self, &T)) {
+        self.len(items.len());
+
+        for item in items {
+            write(self, item);
+        }
+    }
+
+    fn len(&mut self, len: usize) {
+        self.u32(
+            len.try_into()
+                .expect("saved sequences never exceed u32::MAX"),
+        );
+    }
+}
+
+pub(crate) struct Reader<'data> {
+    data: &'data [u8],
+}
+
+impl<'data> Reader<'data> {
+    fn new(data: &'data [u8]) -> Self {
+        Self { data }
+    }
+
+    fn bytes(&mut self, count: usize) -> Result<&'data [u8], SaveGameError> {
+        if self.data.len() < count {
+            return Err(SaveGameError::Truncated);
+        }
+
+        let (value, rest) = self.data.split_at(count);
+
+        self.data = rest;
+        Ok(value)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], SaveGameError> {
+        Ok(self
+            .bytes(N)?
+            .try_into()
+            .expect("slice has the length of the array"))
+    }
+
+    pub fn u8(&mut self) -> Result<u8, SaveGameError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub fn u16(&mut self) -> Result<u16, SaveGameError> {
+        self.array().map(u16::from_le_bytes)
+    }
+
+    pub fn u32(&mut self) -> Result<u32, SaveGameError> {
+        self.array().map(u32::from_le_bytes)
+    }
+
+    pub fn f32(&mut self) -> Result<f32, SaveGameError> {
+        self.array().map(f32::from_le_bytes)
+    }
+
+    pub fn f64(&mut self) -> Result<f64, SaveGameError> {
+        self.array().map(f64::from_le_bytes)
+    }
+
+    pub fn str(&mut self) -> Result<String, SaveGameError> {
+        let len = self.len()?;
+        let bytes = self.bytes(len)?;
+
+        String::from_utf8(bytes.to_vec()).map_err(|_| SaveGameError::InvalidString)
+    }
+
+    pub fn tile_coords(&mut self) -> Result<TileCoords, SaveGameError> {
+        Ok((self.u32()?, self.u32()?))
+    }
+
+    pub fn vector3(&mut self) -> Result<Vector3, SaveGameError> {
+        Ok(Vector3::new(self.f32()?, self.f32()?, self.f32()?))
+    }
+
+    pub fn transform(&mut self) -> Result<Transform3D, SaveGameError> {
+        let basis = Basis::from_rows(self.vector3()?, self.vector3()?, self.vector3()?);
+
+        Ok(Transform3D::new(basis, self.vector3()?))
+    }
+
+    pub fn option<T>(
+        &mut self,
+        read: impl FnOnce(&mut Self) -> Result<T, SaveGameError>,
+    ) -> Result<Option<T>, SaveGameError> {
+        match self.u8()? {
+            0 => Ok(None),
+            1 => read(self).map(Some),
+            _ => Err(SaveGameError::InvalidValue("option")),
+        }
+    }
+
+    pub fn seq<T>(
+        &mut self,
+        mut read: impl FnMut(&mut Self) -> Result<T, SaveGameError>,
+    ) -> Result<Vec<T>, SaveGameError> {
+        let len = self.len()?;
+
+        // every item takes up at least one byte, a larger length can only come from a corrupted
+        // save and must not cause a huge allocation.
+        if len > self.data.len() {
+            return Err(SaveGameError::Truncated);
+        }
+
+        (0..len).map(|_| read(self)).collect()
+    }
+
+    fn len(&mut self) -> Result<usize, SaveGameError> {
+        Ok(self.u32()? as usize)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use godot::builtin::{Basis, Transform3D, Vector3};
+
+    use super::{SaveGame, SaveGameError, SavedCar, SavedDecal};
+    use crate::objects::scene_object_registry::Buildings;
+    use crate::world::city_data::fixtures::city;
+    use crate::world::missions::{MissionDefinition, MissionEvent, ObjectiveKind};
+
+    const MAP: &str = "res://resources/Maps/Demo.sc2.mpz";
+
+    fn fixture() -> SaveGame {
+        let home = Buildings::HomeMiddleClass3 as u8;
+        let city = city(&[((1, 1), home, 1), ((3, 1), home, 1)]);
+        let mut mission = MissionDefinition {
+            objective: ObjectiveKind::ExtinguishFires,
+            target: (1, 1),
+            fire_count: 2,
+            time_limit: 80.0,
+            reward: 1500,
+        }
+        .build(&city)
+        .unwrap();
+
+        mission.start(600.0);
+        mission.handle_event(MissionEvent::FireExtinguished((3, 1)));
+
+        let rotated = Transform3D::new(
+            Basis::from_axis_angle(Vector3::UP, 1.2),
+            Vector3::new(120.5, 34.0, -8.25),
+        );
+
+        SaveGame {
+            map: MAP.into(),
+            helicopter: rotated,
+            upgrades: vec!["res://resources/Objects/Helis/Upgrades/canon.tres".into()],
+            balance: 2_500,
+            water: 640.5,
+            time: 1_234_567,
+            fires: vec![((12, 40), 0.75), ((13, 41), 1.0)],
+            cars: vec![
+                SavedCar {
+                    spawner: "CarSpawner2".into(),
+                    transform: rotated,
+                    target: Some((64, 3)),
+                },
+                SavedCar {
+                    spawner: "CarSpawner".into(),
+                    transform: Transform3D::IDENTITY,
+                    target: None,
+                },
+            ],
+            decals: vec![SavedDecal {
+                transform: rotated,
+                size: Vector3::new(4.0, 2.0, 4.0),
+            }],
+            missions: vec![mission],
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let save = fixture();
+        let restored = SaveGame::decode(&save.encode()).unwrap();
+
+        assert_eq!(restored.helicopter, save.helicopter);
+        assert_eq!(restored.upgrades, save.upgrades);
+        assert_eq!(restored.balance, save.balance);
+        assert_eq!(restored.water.to_bits(), save.water.to_bits());
+        assert_eq!(restored.time, save.time);
+        assert_eq!(restored.fires, save.fires);
+        assert_eq!(restored.cars, save.cars);
+        assert_eq!(restored.decals, save.decals);
+        assert_eq!(restored.missions, save.missions);
+    }
+
+    #[test]
+    fn rejects_saves_of_other_maps() {
+        let restored = SaveGame::decode(&fixture().encode()).unwrap();
+
+        assert!(matches!(
+            restored.clone().for_map("res://resources/Maps/rio.sc2.mpz"),
+            Err(SaveGameError::OtherMap(map)) if map == MAP
+        ));
+        assert!(restored.for_map(MAP).is_ok());
+    }
+
+    #[test]
+    fn rejects_foreign_and_truncated_data() {
+        let data = fixture().encode();
+
+        assert!(matches!(
+            SaveGame::decode(b"FORM...."),
+            Err(SaveGameError::InvalidHeader)
+        ));
+        assert!(matches!(
+            SaveGame::decode(&data[..data.len() - 1]),
+            Err(SaveGameError::Truncated)
+        ));
+    }
+}
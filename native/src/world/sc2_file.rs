@@ -0,0 +1,739 @@
+/*
+ * Copyright (c) SimChopper; Jovan Gerodetti and contributors.
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! Reader for the original SimCity 2000 `.sc2` city files.
+//!
+//! A `.sc2` file is an IFF container (`FORM` / `SCDH`) with one chunk per city data layer. Most of
+//! the chunks are compressed with a simple run length encoding.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Display};
+use std::ops::RangeInclusive;
+
+use super::city_data::{Building, City, SimulatorSettings, Tile, TileCoords, TileTerrainInfo};
+
+/// `.sc2` maps always have the same dimensions.
+const CITY_SIZE: u32 = 128;
+const TILE_COUNT: usize = (CITY_SIZE * CITY_SIZE) as usize;
+
+const FORM_HEADER: &[u8; 4] = b"FORM";
+const SC2_FORM_TYPE: &[u8; 4] = b"SCDH";
+
+/// Byte offsets of the simulator settings inside the `MISC` chunk.
+const MISC_COMPASS: usize = 0x0008;
+const MISC_GLOBAL_SEA_LEVEL: usize = 0x1014;
+
+/// Building ids that belong to a transport or utility network.
+const NETWORK_IDS: RangeInclusive<u8> = 0x0E..=0x6F;
+
+/// Lower 5 bits of an `ALTM` entry hold the tile altitude.
+const ALTITUDE_MASK: u16 = 0x1F;
+
+/// The corner of a multi-tile building that carries the building record. The anchor is the tile
+/// with the lowest x and highest y coordinate.
+const ANCHOR_CORNER: u8 = 0b0100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct ChunkId([u8; 4]);
+
+impl ChunkId {
+    const CNAM: Self = Self(*b"CNAM");
+    const ALTM: Self = Self(*b"ALTM");
+    const XTER: Self = Self(*b"XTER");
+    const XBLD: Self = Self(*b"XBLD");
+    const XZON: Self = Self(*b"XZON");
+    const XUND: Self = Self(*b"XUND");
+    const XTXT: Self = Self(*b"XTXT");
+    const XBIT: Self = Self(*b"XBIT");
+    const MISC: Self = Self(*b"MISC");
+    const PICT: Self = Self(*b"PICT");
+    const TEXT: Self = Self(*b"TEXT");
+    const SCEN: Self = Self(*b"SCEN");
+
+    /// Chunks that are stored without RLE compression.
+    fn is_compressed(self) -> bool {
+        !matches!(
+            self,
+            Self::CNAM | Self::ALTM | Self::PICT | Self::TEXT | Self::SCEN
+        )
+    }
+}
+
+impl Display for ChunkId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&String::from_utf8_lossy(&self.0))
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum Sc2FileError {
+    #[error("file is not a SimCity 2000 city")]
+    InvalidHeader,
+    #[error("chunk \"{0}\" exceeds the end of the file")]
+    Truncated(ChunkId),
+    #[error("chunk \"{0}\" is missing")]
+    MissingChunk(ChunkId),
+    #[error("chunk \"{id}\" contains {actual} bytes, expected {expected}")]
+    InvalidLength {
+        id: ChunkId,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("chunk \"{0}\" contains an invalid compressed run")]
+    InvalidRun(ChunkId),
+    #[error("simulator setting at offset {offset:#06x} is out of range: {value}")]
+    InvalidSetting { offset: usize, value: i32 },
+}
+
+/// Decoded chunks of a `.sc2` file.
+struct Sc2Chunks {
+    chunks: HashMap<ChunkId, Vec<u8>>,
+}
+
+impl Sc2Chunks {
+    fn parse(data: &[u8]) -> Result<Self, Sc2FileError> {
+        let (header, body) = data
+            .split_first_chunk::<12>()
+            .ok_or(Sc2FileError::InvalidHeader)?;
+
+        if &header[0..4] != FORM_HEADER || &header[8..12] != SC2_FORM_TYPE {
+            return Err(Sc2FileError::InvalidHeader);
+        }
+
+        let mut chunks = HashMap::new();
+        let mut rest = body;
+
+        while let Some((chunk_header, chunk_body)) = rest.split_first_chunk::<8>() {
+            let (id, len) = chunk_header.split_at(4);
+            let id = ChunkId(id.try_into().expect("chunk id is 4 bytes long"));
+            let len =
+                u32::from_be_bytes(len.try_into().expect("chunk length is 4 bytes long")) as usize;
+
+            if chunk_body.len() < len {
+                return Err(Sc2FileError::Truncated(id));
+            }
+
+            let (content, remaining) = chunk_body.split_at(len);
+
+            let content = if id.is_compressed() {
+                decode_rle(content).ok_or(Sc2FileError::InvalidRun(id))?
+            } else {
+                content.to_vec()
+            };
+
+            chunks.insert(id, content);
+            rest = remaining;
+        }
+
+        Ok(Self { chunks })
+    }
+
+    fn get(&self, id: ChunkId) -> Result<&[u8], Sc2FileError> {
+        self.chunks
+            .get(&id)
+            .map(Vec::as_slice)
+            .ok_or(Sc2FileError::MissingChunk(id))
+    }
+
+    /// Get a chunk that stores `item_size` bytes for every tile of the map.
+    fn tile_layer(&self, id: ChunkId, item_size: usize) -> Result<&[u8], Sc2FileError> {
+        let chunk = self.get(id)?;
+        let expected = TILE_COUNT * item_size;
+
+        if chunk.len() < expected {
+            return Err(Sc2FileError::InvalidLength {
+                id,
+                expected,
+                actual: chunk.len(),
+            });
+        }
+
+        Ok(&chunk[..expected])
+    }
+
+    fn misc_value<T: TryFrom<i32>>(&self, offset: usize) -> Result<T, Sc2FileError> {
+        let misc = self.get(ChunkId::MISC)?;
+
+        let value = misc
+            .get(offset..offset + 4)
+            .and_then(|bytes| bytes.try_into().ok())
+            .map(i32::from_be_bytes)
+            .ok_or(Sc2FileError::InvalidLength {
+                id: ChunkId::MISC,
+                expected: offset + 4,
+                actual: misc.len(),
+            })?;
+
+        T::try_from(value).map_err(|_| Sc2FileError::InvalidSetting { offset, value })
+    }
+}
+
+/// Decode the run length encoding used by `.sc2` chunks.
+///
+/// A count byte between 1 and 127 is followed by that many literal bytes. A count byte between
+/// 129 and 255 is followed by a single byte that is repeated `count - 127` times.
+fn decode_rle(data: &[u8]) -> Option<Vec<u8>> {
+    let mut output = Vec::with_capacity(data.len() * 2);
+    let mut iter = data.iter().copied();
+
+    while let Some(count) = iter.next() {
+        match count {
+            1..=127 => {
+                for _ in 0..count {
+                    output.push(iter.next()?);
+                }
+            }
+            129..=255 => {
+                let value = iter.next()?;
+
+                output.extend(std::iter::repeat_n(value, usize::from(count - 127)));
+            }
+            0 | 128 => return None,
+        }
+    }
+
+    Some(output)
+}
+
+/// Read the city name from the content of a `CNAM` chunk.
+///
+/// The name is stored with a leading length byte in a fixed size buffer, which contains garbage
+/// after the name. Returns `None` if the length exceeds the buffer or the name is empty.
+pub(crate) fn city_name(cnam: &[u8]) -> Option<String> {
+    let (len, buffer) = cnam.split_first()?;
+    let name = buffer.get(..usize::from(*len))?;
+    let name = String::from_utf8_lossy(name).trim().to_owned();
+
+    (!name.is_empty()).then_some(name)
+}
+
+/// Read a `.sc2` file into the same [`City`] structure that is produced by the `.sc2.mpz` maps.
+pub(crate) fn read_city(data: &[u8]) -> Result<City, Sc2FileError> {
+    let chunks = Sc2Chunks::parse(data)?;
+
+    // cities that have never been named do not contain a name chunk.
+    let name = chunks.get(ChunkId::CNAM).ok().and_then(city_name);
+
+    let altitudes = chunks.tile_layer(ChunkId::ALTM, 2)?;
+    let terrain = chunks.tile_layer(ChunkId::XTER, 1)?;
+    let buildings = chunks.tile_layer(ChunkId::XBLD, 1)?;
+    let zones = chunks.tile_layer(ChunkId::XZON, 1)?;
+
+    // these layers are not part of the city data yet, but a valid city always contains them.
+    chunks.tile_layer(ChunkId::XUND, 1)?;
+    chunks.tile_layer(ChunkId::XTXT, 1)?;
+    chunks.tile_layer(ChunkId::XBIT, 1)?;
+
+    let sea_level = chunks.misc_value(MISC_GLOBAL_SEA_LEVEL)?;
+    let compass = chunks.misc_value(MISC_COMPASS)?;
+
+    let corners = |index: usize| zones[index] >> 4;
+
+    // every tile of a multi-tile building refers to the building record of its anchor tile.
+    let anchors: HashMap<TileCoords, TileCoords> = (0..TILE_COUNT)
+        .filter(|index| corners(*index) & ANCHOR_CORNER != 0)
+        .flat_map(|index| {
+            let anchor = tile_coords(index);
+            let size = u32::from(building_size(buildings[index]));
+
+            (0..size).flat_map(move |dx| {
+                (0..size).filter_map(move |dy| {
+                    Some(((anchor.0 + dx, anchor.1.checked_sub(dy)?), anchor))
+                })
+            })
+        })
+        .collect();
+
+    let mut city_buildings = BTreeMap::new();
+    let mut networks = BTreeMap::new();
+    let mut tilelist = BTreeMap::new();
+
+    for index in 0..TILE_COUNT {
+        let coordinates = tile_coords(index);
+        let building_id = buildings[index];
+        let tile_corners = corners(index);
+
+        let building = (building_id > 0 || tile_corners > 0).then(|| {
+            let size = building_size(building_id);
+            let anchor = if size > 1 {
+                anchors.get(&coordinates).copied().unwrap_or(coordinates)
+            } else {
+                coordinates
+            };
+
+            Building {
+                size,
+                name: building_name(building_id).to_owned(),
+                id: building_id,
+                tile_coords: anchor,
+            }
+        });
+
+        // network pieces are only listed as networks, they are not spawned as buildings.
+        if let Some(building) = building
+            .as_ref()
+            .filter(|_| tile_corners > 0 && !NETWORK_IDS.contains(&building_id))
+        {
+            city_buildings
+                .entry(building.tile_coords)
+                .or_insert_with(|| building.clone());
+        }
+
+        if let Some(building) = building
+            .as_ref()
+            .filter(|_| NETWORK_IDS.contains(&building_id))
+        {
+            networks.insert(
+                coordinates,
+                Building {
+                    tile_coords: coordinates,
+                    ..building.clone()
+                },
+            );
+        }
+
+        let altitude = u16::from_be_bytes([altitudes[index * 2], altitudes[index * 2 + 1]]);
+
+        tilelist.insert(
+            coordinates,
+            Tile {
+                terrain: TileTerrainInfo::from(u32::from(terrain[index])),
+                altitude: u32::from(altitude & ALTITUDE_MASK),
+                building,
+                coordinates,
+            },
+        );
+    }
+
+    Ok(City {
+        name,
+        simulator_settings: SimulatorSettings { sea_level, compass },
+        buildings: city_buildings,
+        networks,
+        tilelist,
+        city_size: CITY_SIZE,
+    })
+}
+
+fn tile_coords(index: usize) -> TileCoords {
+    let index = u32::try_from(index).expect("tile index is always smaller than TILE_COUNT");
+
+    (index / CITY_SIZE, index % CITY_SIZE)
+}
+
+/// Number of tiles along each side of a building.
+fn building_size(id: u8) -> u8 {
+    match id {
+        0x61..=0x6B | 0x8C..=0xAD | 0xEB..=0xF7 => 2,
+        0xAE..=0xC5 | 0xD0..=0xD6 | 0xF8..=0xFA => 3,
+        0xC9..=0xCF | 0xD7..=0xDA | 0xFB..=0xFF => 4,
+        _ => 1,
+    }
+}
+
+fn building_name(id: u8) -> &'static str {
+    BUILDING_NAMES[usize::from(id)]
+}
+
+const BUILDING_NAMES: [&str; 256] = [
+    "Clear Ground",                         // 0x00
+    "Rubble 1",                             // 0x01
+    "Rubble 2",                             // 0x02
+    "Rubble 3",                             // 0x03
+    "Rubble 4",                             // 0x04
+    "Radioactive Waste",                    // 0x05
+    "Tree",                                 // 0x06
+    "Couple O Trees",                       // 0x07
+    "More Trees",                           // 0x08
+    "Morer Trees",                          // 0x09
+    "Even More Trees",                      // 0x0A
+    "Tons O Trees",                         // 0x0B
+    "Veritable Jungle",                     // 0x0C
+    "Small Park",                           // 0x0D
+    "Power Line: Left-Right",               // 0x0E
+    "Power Line: Top-Bottom",               // 0x0F
+    "Power Line: HighTop-Bottom",           // 0x10
+    "Power Line: Left-HighRight",           // 0x11
+    "Power Line: Top-HighBottom",           // 0x12
+    "Power Line: HighLeft-Right",           // 0x13
+    "Power Line: Bottom-Right",             // 0x14
+    "Power Line: Bottom-Left",              // 0x15
+    "Power Line: Top-Left",                 // 0x16
+    "Power Line: Top-Right",                // 0x17
+    "Power Line: Right-Top-Left",           // 0x18
+    "Power Line: Right-Bottom-Left",        // 0x19
+    "Power Line: Top-Left-Bottom",          // 0x1A
+    "Power Line: Left-Top-Bottom",          // 0x1B
+    "Power Line: Left-Top-Bottom-Right",    // 0x1C
+    "Road: Left-Right",                     // 0x1D
+    "Road: Top-Bottom",                     // 0x1E
+    "Road: HighTop-Bottom",                 // 0x1F
+    "Road: Left-HighRight",                 // 0x20
+    "Road: Top-HighBottom",                 // 0x21
+    "Road: HighLeft-Right",                 // 0x22
+    "Road: Bottom-Right",                   // 0x23
+    "Road: Bottom-Left",                    // 0x24
+    "Road: Top-Left",                       // 0x25
+    "Road: Top-Right",                      // 0x26
+    "Road: Right-Top-Left",                 // 0x27
+    "Road: Right-Bottom-Left",              // 0x28
+    "Road: Top-Left-Bottom",                // 0x29
+    "Road: Left-Top-Bottom",                // 0x2A
+    "Road: Left-Top-Bottom-Right",          // 0x2B
+    "Rail: Left-Right",                     // 0x2C
+    "Rail: Top-Bottom",                     // 0x2D
+    "Rail: HighTop-Bottom",                 // 0x2E
+    "Rail: Left-HighRight",                 // 0x2F
+    "Rail: Top-HighBottom",                 // 0x30
+    "Rail: HighLeft-Right",                 // 0x31
+    "Rail: Bottom-Right",                   // 0x32
+    "Rail: Bottom-Left",                    // 0x33
+    "Rail: Top-Left",                       // 0x34
+    "Rail: Top-Right",                      // 0x35
+    "Rail: Right-Top-Left",                 // 0x36
+    "Rail: Right-Bottom-Left",              // 0x37
+    "Rail: Top-Left-Bottom",                // 0x38
+    "Rail: Left-Top-Bottom",                // 0x39
+    "Rail: Left-Top-Bottom-Right",          // 0x3A
+    "Rail: HighTop-Bottom",                 // 0x3B
+    "Rail: Left-HighRight",                 // 0x3C
+    "Rail: Top-HighBottom",                 // 0x3D
+    "Rail: HighLeft-Right",                 // 0x3E
+    "Tunnel: Top",                          // 0x3F
+    "Tunnel: Right",                        // 0x40
+    "Tunnel: Bottom",                       // 0x41
+    "Tunnel: Left",                         // 0x42
+    "Power:Top-Bottom, Road:Left-Right",    // 0x43
+    "Power:Left-Right, Road:Top-Bottom",    // 0x44
+    "Road:Left-Right, Rail:Top-Bottom",     // 0x45
+    "Road:Top-Bottom, Rail:Left-Right",     // 0x46
+    "Rail:Left-Right, Power:Top-Bottom",    // 0x47
+    "Rail:Top-Bottom, Power:Left-Right",    // 0x48
+    "Highway: Left-Right",                  // 0x49
+    "Highway: Top-Bottom",                  // 0x4A
+    "Highway:Left-Right, Road:Top-Bottom",  // 0x4B
+    " Highway:Top-Bottom, Road:Left-Right", // 0x4C
+    "Highway:Left-Right, Rail:Top-Bottom",  // 0x4D
+    "Highway:Top-Bottom, Rail:Left-Right",  // 0x4E
+    "Highway:Top-Bottom, Power:Left-Right", // 0x4F
+    "Highway:Left-Right, Power:Top-Bottom", // 0x50
+    "Suspension Bridge: Start:Bottom",      // 0x51
+    "Suspension Bridge: Middle:Bottom",     // 0x52
+    "Suspension Bridge: Center",            // 0x53
+    "Suspension Bridge: Middle:Top",        // 0x54
+    "Suspension Bridge: Start:Top",         // 0x55
+    "Raising Bridge: Tower",                // 0x56
+    "Bridge: Pylon",                        // 0x57
+    "Bridge: Deck",                         // 0x58
+    "Raising Bridge: Raised",               // 0x59
+    "Rail Bridge: Pylon",                   // 0x5A
+    "Rail Bridge: Deck",                    // 0x5B
+    "Raised Power Lines",                   // 0x5C
+    "Onramp: Highway:Top-Road:Left",        // 0x5D
+    "Onramp: Highway:Top-Road:Right",       // 0x5E
+    "Onramp: Highway:Bottom-Road:Left",     // 0x5F
+    "Onramp: Highway:Bottom-Road:Right",    // 0x60
+    "Highway: HighTop-Bottom",              // 0x61
+    "Highway: Left-HighRight",              // 0x62
+    "Highway: Top-HighBottom",              // 0x63
+    "Highway: HighLeft-Right",              // 0x64
+    "Highway: Bottom-Right",                // 0x65
+    "Highway: Bottom-Left",                 // 0x66
+    "Highway: Top-Left",                    // 0x67
+    "Highway: Top-Right",                   // 0x68
+    "Highway: Left-Top-Bottom-Right",       // 0x69
+    "Highway Reinforced Bridge Pylon",      // 0x6A
+    "Highway Reinforced Bridge",            // 0x6B
+    "Sub-Rail: Top",                        // 0x6C
+    "Sub-Rail: Right",                      // 0x6D
+    "Sub-Rail: Bottom",                     // 0x6E
+    "Sub-Rail: Left",                       // 0x6F
+    "Lower Class Homes 1",                  // 0x70
+    "Lower Class Homes 2",                  // 0x71
+    "Lower Class Homes 3",                  // 0x72
+    "Lower Class Homes 4",                  // 0x73
+    "Middle Class Homes 1",                 // 0x74
+    "Middle Class Homes 2",                 // 0x75
+    "Middle Class Homes 3",                 // 0x76
+    "Middle Class Homes 4",                 // 0x77
+    "Upper Class Homes 1",                  // 0x78
+    "Upper Class Homes 2",                  // 0x79
+    "Upper Class Homes 3",                  // 0x7A
+    "Upper Class Homes 4",                  // 0x7B
+    "Gas Station 1",                        // 0x7C
+    "Bed & Breakfast Inn",                  // 0x7D
+    "Convenience Store",                    // 0x7E
+    "Gas Station 2",                        // 0x7F
+    "Small Office Building 1",              // 0x80
+    "Small Office Building 2",              // 0x81
+    "Warehouse",                            // 0x82
+    "Cassidy's Toy Store",                  // 0x83
+    "Small WareHouse 1",                    // 0x84
+    "Chemical Storage",                     // 0x85
+    "Small WareHouse 2",                    // 0x86
+    "Industral Substation",                 // 0x87
+    "Construction 7",                       // 0x88
+    "Construction 8",                       // 0x89
+    "Abandoned Building 1",                 // 0x8A
+    "Abandoned Building 2",                 // 0x8B
+    "Cheap Apartments",                     // 0x8C
+    "Small Apartments 2",                   // 0x8D
+    "Small Apartments 3",                   // 0x8E
+    "Medium Apartments 1",                  // 0x8F
+    "Medium Apartments 2",                  // 0x90
+    "Medium Condominiums 1",                // 0x91
+    "Medium Condominiums 2",                // 0x92
+    "Medium Condominiums 3",                // 0x93
+    "Shopping Center",                      // 0x94
+    "Grocery Store",                        // 0x95
+    "Medium Office Building 1",             // 0x96
+    "Resort hotel",                         // 0x97
+    "Medium Office Building 2",             // 0x98
+    "Office/Retail",                        // 0x99
+    "Medium Office Building 3",             // 0x9A
+    "Medium Office Building 4",             // 0x9B
+    "Medium Office Building 5",             // 0x9C
+    "Medium Office Building 6",             // 0x9D
+    "Medium Warehouse",                     // 0x9E
+    "Chemical Processing 2",                // 0x9F
+    "Small Factory 1",                      // 0xA0
+    "Small Factory 2",                      // 0xA1
+    "Small Factory 3",                      // 0xA2
+    "Small Factory 4",                      // 0xA3
+    "Small Factory 5",                      // 0xA4
+    "Small Factory 6",                      // 0xA5
+    "Construction 3",                       // 0xA6
+    "Construction 4",                       // 0xA7
+    "Construction 5",                       // 0xA8
+    "Construction 6",                       // 0xA9
+    "Abandoned Building 3",                 // 0xAA
+    "Abandoned Building 4",                 // 0xAB
+    "Abandoned Building 5",                 // 0xAC
+    "Abandoned Building 6",                 // 0xAD
+    "Large Apartments 1",                   // 0xAE
+    "Large Apartments 2",                   // 0xAF
+    "Large Condominiums 1",                 // 0xB0
+    "Large Condominiums 2",                 // 0xB1
+    "Office Park",                          // 0xB2
+    "Office Tower 1",                       // 0xB3
+    "Mini Mall",                            // 0xB4
+    "Theater square",                       // 0xB5
+    "Drive In",                             // 0xB6
+    "Office Tower 2",                       // 0xB7
+    "Office Tower 3",                       // 0xB8
+    "Parking Lot",                          // 0xB9
+    "Historic Office",                      // 0xBA
+    "Corporate Headquarters",               // 0xBB
+    "Chemical Processing",                  // 0xBC
+    "Large Factory",                        // 0xBD
+    "Industrial Thingamajig",               // 0xBE
+    "Medium Factory",                       // 0xBF
+    "Large Warehouse 1",                    // 0xC0
+    "Large Warehouse 2",                    // 0xC1
+    "Construction 1",                       // 0xC2
+    "Construction 2",                       // 0xC3
+    "Abandoned Building 7",                 // 0xC4
+    "Abandoned Building 8",                 // 0xC5
+    "Hydoelectric Power Plant 1",           // 0xC6
+    "Hydoelectric Power Plant 2",           // 0xC7
+    "Wind Power Plant1",                    // 0xC8
+    "Gas Power Plant",                      // 0xC9
+    "Oil Power Plant",                      // 0xCA
+    "Nuclear Power Plant",                  // 0xCB
+    "Solar Power Plant",                    // 0xCC
+    "Microwave Power Plant",                // 0xCD
+    "Fusion Power Plant",                   // 0xCE
+    "Coal Power Plant",                     // 0xCF
+    "City Hall",                            // 0xD0
+    "Hospital",                             // 0xD1
+    "Police Station",                       // 0xD2
+    "Fire Station",                         // 0xD3
+    "Museum",                               // 0xD4
+    "Big Park",                             // 0xD5
+    "School",                               // 0xD6
+    "Stadium",                              // 0xD7
+    "Prison",                               // 0xD8
+    "College",                              // 0xD9
+    "Zoo",                                  // 0xDA
+    "Statue",                               // 0xDB
+    "Water Pump",                           // 0xDC
+    "Runway",                               // 0xDD
+    "Runway Intersection",                  // 0xDE
+    "Seaport Pier",                         // 0xDF
+    "Crane",                                // 0xE0
+    "Civilian Control Tower",               // 0xE1
+    "Miliary Control Tower",                // 0xE2
+    "Warehouse",                            // 0xE3
+    "Airport Building 1",                   // 0xE4
+    "Airport Building 1",                   // 0xE5
+    "Tarmac",                               // 0xE6
+    "F-15b",                                // 0xE7
+    "Military Hangar",                      // 0xE8
+    "Subway Station",                       // 0xE9
+    "Radar",                                // 0xEA
+    "Water Tower",                          // 0xEB
+    "Bus Depot",                            // 0xEC
+    "Rail Depot",                           // 0xED
+    "Civilian Parking Lot",                 // 0xEE
+    "Military Parking Lot",                 // 0xEF
+    "Loading Bay",                          // 0xF0
+    "Top Secret",                           // 0xF1
+    "Cargo Yard",                           // 0xF2
+    "Mayor's House",                        // 0xF3
+    "Water Treatment",                      // 0xF4
+    "Library",                              // 0xF5
+    "Big Hangar",                           // 0xF6
+    "Church",                               // 0xF7
+    "Marina",                               // 0xF8
+    "Missile Silo",                         // 0xF9
+    "Desalinization",                       // 0xFA
+    "Plymouth Arcology",                    // 0xFB
+    "Forest Arcology",                      // 0xFC
+    "Darco",                                // 0xFD
+    "Launch Arcology",                      // 0xFE
+    "Braun Llama Dome",                     // 0xFF
+];
+
+#[cfg(test)]
+mod test {
+    use super::{
+        city_name, decode_rle, read_city, tile_coords, ChunkId, Sc2FileError,
+        MISC_GLOBAL_SEA_LEVEL, TILE_COUNT,
+    };
+
+    /// Encode the data as literal runs, which is always valid.
+    fn encode_rle(data: &[u8]) -> Vec<u8> {
+        data.chunks(127)
+            .flat_map(|run| {
+                std::iter::once(u8::try_from(run.len()).expect("runs are at most 127 bytes long"))
+                    .chain(run.iter().copied())
+            })
+            .collect()
+    }
+
+    fn tile_index(coords: (u32, u32)) -> usize {
+        (0..TILE_COUNT)
+            .find(|index| tile_coords(*index) == coords)
+            .expect("coordinates are inside the city")
+    }
+
+    /// A flat city with a single road piece and a single house.
+    fn fixture(sea_level: i32) -> Vec<u8> {
+        let road = tile_index((1, 1));
+        let house = tile_index((3, 3));
+
+        let mut buildings = vec![0; TILE_COUNT];
+        let mut zones = vec![0; TILE_COUNT];
+
+        buildings[road] = 0x1D;
+        buildings[house] = 0x70;
+        zones[road] = 0xF0;
+        zones[house] = 0xF1;
+
+        let mut misc = vec![0; MISC_GLOBAL_SEA_LEVEL + 4];
+        misc[MISC_GLOBAL_SEA_LEVEL..].copy_from_slice(&sea_level.to_be_bytes());
+
+        let mut cnam = b"\x07VOLCANO\0PlaceWindow::DrawHouse".to_vec();
+        cnam.resize(32, 0);
+
+        let chunks = [
+            (ChunkId::CNAM, cnam),
+            (ChunkId::ALTM, vec![0; TILE_COUNT * 2]),
+            (ChunkId::XTER, vec![0; TILE_COUNT]),
+            (ChunkId::XBLD, buildings),
+            (ChunkId::XZON, zones),
+            (ChunkId::XUND, vec![0; TILE_COUNT]),
+            (ChunkId::XTXT, vec![0; TILE_COUNT]),
+            (ChunkId::XBIT, vec![0; TILE_COUNT]),
+            (ChunkId::MISC, misc),
+        ];
+
+        let body: Vec<u8> = chunks
+            .into_iter()
+            .flat_map(|(id, content)| {
+                let content = if id.is_compressed() {
+                    encode_rle(&content)
+                } else {
+                    content
+                };
+                let len = u32::try_from(content.len()).expect("chunks fit into the file");
+
+                id.0.into_iter()
+                    .chain(len.to_be_bytes())
+                    .chain(content)
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        let len = u32::try_from(body.len() + 4).expect("fixture fits into the file");
+
+        b"FORM"
+            .iter()
+            .copied()
+            .chain(len.to_be_bytes())
+            .chain(*b"SCDH")
+            .chain(body)
+            .collect()
+    }
+
+    #[test]
+    fn networks_are_not_buildings() {
+        let city = read_city(&fixture(4)).expect("fixture is a valid city");
+
+        assert_eq!(city.simulator_settings.sea_level, 4);
+        assert_eq!(city.networks.keys().collect::<Vec<_>>(), vec![&(1, 1)]);
+        assert_eq!(city.buildings.keys().collect::<Vec<_>>(), vec![&(3, 3)]);
+        assert!(city
+            .networks
+            .keys()
+            .all(|coords| !city.buildings.contains_key(coords)));
+    }
+
+    #[test]
+    fn city_name_ends_after_its_length() {
+        let city = read_city(&fixture(4)).expect("fixture is a valid city");
+
+        assert_eq!(city.name.as_deref(), Some("VOLCANO"));
+        assert_eq!(
+            city_name(b"\x0ACape Wells\xFFyy"),
+            Some("Cape Wells".to_owned())
+        );
+        assert_eq!(city_name(b"\0garbage"), None);
+        assert_eq!(city_name(b"\x20TOKYO"), None);
+        assert_eq!(city_name(b""), None);
+    }
+
+    #[test]
+    fn reject_out_of_range_sea_level() {
+        let result = read_city(&fixture(-1));
+
+        assert!(matches!(
+            result,
+            Err(Sc2FileError::InvalidSetting {
+                offset: MISC_GLOBAL_SEA_LEVEL,
+                value: -1
+            })
+        ));
+    }
+
+    #[test]
+    fn decode_literal_and_repeated_runs() {
+        let data = [0x03, 0x01, 0x02, 0x03, 0x82, 0x07, 0x01, 0x09];
+
+        assert_eq!(
+            decode_rle(&data),
+            Some(vec![0x01, 0x02, 0x03, 0x07, 0x07, 0x07, 0x09])
+        );
+    }
+
+    #[test]
+    fn reject_truncated_runs() {
+        assert_eq!(decode_rle(&[0x03, 0x01]), None);
+        assert_eq!(decode_rle(&[0x85]), None);
+        assert_eq!(decode_rle(&[0x00]), None);
+    }
+}
@@ -0,0 +1,37 @@
+###
+# Copyright (c) SimChopper; Jovan Gerodetti and contributors.
+# This Source Code Form is subject to the terms of the Mozilla Public
+# License, v. 2.0. If a copy of the MPL was not distributed with this
+# file, You can obtain one at http://mozilla.org/MPL/2.0/.
+###
+
+## Writes the terrain validation report of all bundled maps.
+##
+## Usage: godot --headless --script res://src/Debug/TerrainReport.gd -- [output file]
+##
+## The report is printed if no output file is given. Reports of two revisions can be compared
+## with a plain diff.
+extends SceneTree
+
+const MAPS_DIRECTORY := "res://resources/Maps"
+
+
+func _init() -> void:
+	var args := OS.get_cmdline_user_args()
+	var report := TerrainValidation.report_maps(MAPS_DIRECTORY)
+
+	if args.is_empty():
+		print(report)
+		self.quit()
+		return
+
+	var file := FileAccess.open(args[0], FileAccess.WRITE)
+
+	if file == null:
+		printerr("unable to write terrain report to \"%s\": %s" % [args[0], FileAccess.get_open_error()])
+		self.quit(1)
+		return
+
+	file.store_string(report)
+	file.close()
+	self.quit()
@@ -9,19 +9,36 @@ extends Control
 
 const World := preload("res://src/Objects/World/World.gd")
 const LoadingScreen := preload("res://src/HUD/LoadingScreen.gd")
+const Helicopter := preload("res://src/Objects/Helicopters/Helicopter.gd")
 
 @onready var loading_screen: LoadingScreen = $LoadingScreen
 @onready var viewport: SubViewportContainer = $SubViewportContainer
 @onready var world: World = $SubViewportContainer/SubViewport/World
+@onready var water_gauge: ProgressBar = $WaterGauge
+@onready var upgrade_offer: Label = $UpgradeOffer
+@onready var mission_status: Label = $MissionStatus
+
+var helicopter: Helicopter
 
 func _ready():
 	world.loading_scale.connect(self._on_loading_scale)
 	world.loading_progress.connect(self._on_loading_progress)
+	world.water_tank.level_changed.connect(self._on_water_level_changed)
+	world.missions.mission_started.connect(self._on_mission_started)
+	world.missions.mission_completed.connect(self._on_mission_completed)
+	world.missions.mission_failed.connect(self._on_mission_failed)
+
+	helicopter = get_tree().get_first_node_in_group("player") as Helicopter
+	helicopter.wallet.balance_changed.connect(self._on_balance_changed)
+	self._update_upgrade_offer(helicopter.wallet.balance())
 
 
 func game_ready() -> void:
 	loading_screen.visible = false
 	viewport.visible = true
+	water_gauge.visible = true
+	upgrade_offer.visible = true
+	mission_status.visible = true
 	world.process_mode = Node.PROCESS_MODE_PAUSABLE
 
 func _on_loading_scale(total: int):
@@ -33,3 +50,67 @@ func _on_loading_progress(new_progress: int):
 
 	if loading_screen.completed_jobs == loading_screen.total_jobs:
 		self.game_ready()
+
+
+func _on_water_level_changed(level: float, capacity: float):
+	water_gauge.max_value = capacity
+	water_gauge.value = level
+
+
+func _unhandled_input(event: InputEvent) -> void:
+	if event.is_action_pressed("purchase_upgrade", false, true):
+		self._purchase_next_upgrade()
+
+
+## Buys the next available upgrade of the helicopter, but only if the player can afford it.
+func _purchase_next_upgrade() -> void:
+	if helicopter.upgrades_available.is_empty():
+		return
+
+	var upgrade: HelicopterUpgrade = helicopter.upgrades_available[0]
+
+	if not helicopter.wallet.can_afford(upgrade):
+		return
+
+	helicopter.purchase_upgrade(upgrade)
+
+
+## The wallet is still busy while it emits its signals, so the balance is passed in.
+func _update_upgrade_offer(balance: int) -> void:
+	if helicopter.upgrades_available.is_empty():
+		upgrade_offer.text = ""
+		return
+
+	var upgrade: HelicopterUpgrade = helicopter.upgrades_available[0]
+
+	upgrade_offer.text = "[U] Buy {name} for ${price} (balance ${balance})".format({
+		"name": upgrade.name,
+		"price": upgrade.price,
+		"balance": balance,
+	})
+
+	# upgrades that can not be afforded yet are dimmed.
+	upgrade_offer.modulate.a = 1.0 if upgrade.price <= balance else 0.5
+
+
+func _on_balance_changed(balance: int) -> void:
+	self._update_upgrade_offer(balance)
+
+
+## The missions node is still busy while it emits its signals, so only the arguments are used.
+func _on_mission_started(title: String, description: String) -> void:
+	mission_status.text = "{title}\n{description}".format({
+		"title": title,
+		"description": description,
+	})
+
+
+func _on_mission_completed(title: String, reward: int) -> void:
+	mission_status.text = "{title} completed, earned ${reward}".format({
+		"title": title,
+		"reward": reward,
+	})
+
+
+func _on_mission_failed(title: String) -> void:
+	mission_status.text = "{title} failed".format({ "title": title })
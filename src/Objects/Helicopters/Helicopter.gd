@@ -16,6 +16,7 @@ const Log := preload("res://src/util/Logger.gd")
 @export var RATE_OF_ROTATION := 1 # degrees / s
 
 @export var input_device: InputDevice
+@export var wallet: Wallet
 
 @export_group("Slots", "child_")
 
@@ -62,6 +63,7 @@ var upgrade_action_dispatch: Dictionary = {}
 # Called when the node enters the scene tree for the first time.
 func _ready():
 	self.rotor.power = 0
+	self.wallet.restore()
 	self.mount_upgrades()
 	self.input_device.set_mouse_mode(Input.MOUSE_MODE_CAPTURED)
 
@@ -252,6 +254,26 @@ func _unhandled_input(event: InputEvent):
 			target.call("action_end", action)
 			continue
 			
+func purchase_upgrade(upgrade: HelicopterUpgrade) -> bool:
+	if not self.wallet.purchase(upgrade, self.upgrades_available, self.upgrades_owned):
+		return false
+
+	self.mount_upgrades()
+	return true
+
+
+## Moves the upgrades of a saved session from the available to the owned upgrades.
+func restore_upgrades(resource_paths: PackedStringArray) -> void:
+	for upgrade in self.upgrades_available.duplicate():
+		if not resource_paths.has(upgrade.resource_path):
+			continue
+
+		self.upgrades_available.erase(upgrade)
+		self.upgrades_owned.append(upgrade)
+
+	self.mount_upgrades()
+
+
 func mount_upgrades():
 	for upgrade in self.upgrades_owned:
 		var scene = upgrade.object
@@ -267,7 +289,7 @@ func mount_upgrades():
 				break
 
 		if duplicate:
-			return
+			continue
 
 		self.child_upgrade_mount.add_child(object, true)
 		object.owner = self.get_tree().current_scene
@@ -288,6 +310,9 @@ func mount_upgrades():
 
 		self.input_device.subscribe(upgrade.action, Callable.create(object, "action"))
 
+		if not upgrade.secondary_action.is_empty():
+			self.input_device.subscribe(upgrade.secondary_action, Callable.create(object, "switch_mode"))
+
 
 func drag_force(velocity: Vector3, area: float) -> Vector3:
 	var velocity_magnitude := velocity.length()
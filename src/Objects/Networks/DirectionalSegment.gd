@@ -23,10 +23,10 @@ func set_orientation(north: Dictionary, east: Dictionary, south: Dictionary, wes
 
 		connected_range += range(start, end)
 
-	var north_weight := 1 if (north.building and north.building.building_id in connected_range) else 0
-	var south_weight := 1 if (south.building and south.building.building_id in connected_range) else 0
-	var west_weight := 1 if (west.building and west.building.building_id in connected_range) else 0
-	var east_weight := 1 if (east.building and east.building.building_id in connected_range) else 0
+	var north_weight := 1 if (north.get("building") and north.building.building_id in connected_range) else 0
+	var south_weight := 1 if (south.get("building") and south.building.building_id in connected_range) else 0
+	var west_weight := 1 if (west.get("building") and west.building.building_id in connected_range) else 0
+	var east_weight := 1 if (east.get("building") and east.building.building_id in connected_range) else 0
 
 	var east_west := east_weight + west_weight
 	var north_south := north_weight + south_weight
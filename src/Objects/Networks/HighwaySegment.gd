@@ -0,0 +1,15 @@
+###
+# Copyright (c) SimChopper; Jovan Gerodetti and contributors.
+# This Source Code Form is subject to the terms of the Mozilla Public
+# License, v. 2.0. If a copy of the MPL was not distributed with this
+# file, You can obtain one at http://mozilla.org/MPL/2.0/.
+###
+
+extends Node3D
+
+# Highways are two tiles wide. Every segment has its barrier on the east or south edge, the
+# networks turn the segment around if the other half of the highway lies to the west or north.
+
+func set_turned(turned: bool):
+	if turned:
+		self.rotation.y = deg_to_rad(180)
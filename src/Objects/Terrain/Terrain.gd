@@ -15,9 +15,12 @@ signal build_progress(steps: int)
 @export var terrain_material: Material
 @export var ocean_material: Material
 @export var world_constants: WorldConstants
+## Camera distance in meters after which the next, less detailed, level of the terrain is shown.
+@export var lod_distance := 600.0
 
 var city_coords_feature: CityCoordsFeature
 var builder: TerrainBuilder
+var chunk_nodes: Dictionary[Vector2i, MeshInstance3D] = {}
 
 func _ready() -> void:
 	assert(world_constants is WorldConstants, "Terrain.world_contstants is not of type WorldConstants")
@@ -25,13 +28,11 @@ func _ready() -> void:
 func _forward_progress(count: int):
 	self.build_progress.emit(count)
 
-func init(city: Dictionary):
+func init(city: CityHandle):
 	var rotation := TerrainRotation.new()
-	var simulator_settings: Dictionary = city.get("simulator_settings")
-	var compass: int = simulator_settings.get("Compass")
-	var sea_level: int = simulator_settings.get("GlobalSeaLevel")
-	var tilelist: Dictionary = city.get("tilelist")
-	var city_size: int = city.get("city_size")
+	var compass := city.compass()
+	var sea_level := city.sea_level()
+	var city_size := city.city_size()
 	
 	self.city_coords_feature = CityCoordsFeature.new(self.world_constants, sea_level)
 
@@ -42,7 +43,7 @@ func init(city: Dictionary):
 		"Water": ocean_material
 	}
 
-	self.builder = TerrainBuilderFactory.create(tilelist, rotation, materials)
+	self.builder = TerrainBuilderFactory.create(city, rotation, materials)
 
 	self.builder.set_city_size(city_size)
 	self.builder.set_tile_size(self.world_constants.tile_size)
@@ -60,29 +61,89 @@ func load_steps() -> int:
 func build_async():
 	var chunks: Array[TerrainChunk] = await self.builder.build_terain_async().completed
 	
-	for item in chunks:
-		var chunk: TerrainChunk = item
+	for chunk in chunks:
+		self._add_chunk(chunk)
+	
+	prints("generated terain:", self.get_child_count(), "nodes generated")
+
+
+## Changes the altitude of a tile, call [method rebuild_async] to update the terrain.
+func set_tile_altitude(x: int, y: int, altitude: int):
+	self.builder.set_tile_altitude(x, y, altitude)
+
+
+## Changes the terrain type of a tile, call [method rebuild_async] to update the terrain.
+func set_tile_terrain_type(x: int, y: int, terrain_type: int):
+	self.builder.set_tile_terrain_type(x, y, terrain_type)
+
+
+## Regenerates the chunks that are affected by the tile edits and replaces their meshes.
+func rebuild_async():
+	var chunks: Array[TerrainChunk] = await self.builder.rebuild_async().completed
+	
+	for chunk in chunks:
 		var tile_coords: Array[int] = chunk.tile_coords()
-		var translation := self.city_coords_feature.get_world_coords(tile_coords[0], tile_coords[1], 0)
+		var key := Vector2i(tile_coords[0], tile_coords[1])
+		var old_instance: MeshInstance3D = self.chunk_nodes.get(key)
 		
-		translation.y = 0
+		if old_instance != null:
+			self.remove_child(old_instance)
+			old_instance.queue_free()
 		
-		var occluder := create_terrain_occluder(chunk.mesh())
-		var mesh_instance := MeshInstance3D.new()
+		self._add_chunk(chunk)
+	
+	prints("rebuilt terrain:", chunks.size(), "chunks replaced")
 
-		mesh_instance.mesh = chunk.mesh()
-		mesh_instance.cast_shadow = MeshInstance3D.SHADOW_CASTING_SETTING_DOUBLE_SIDED
-		mesh_instance.create_trimesh_collision()
-		mesh_instance.add_child(occluder, false)
-		
-		self.add_child(mesh_instance, false)
+
+func _add_chunk(chunk: TerrainChunk):
+	var tile_coords: Array[int] = chunk.tile_coords()
+	var translation := self.city_coords_feature.get_world_coords(tile_coords[0], tile_coords[1], 0)
+	
+	translation.y = 0
+	
+	var occluder := create_terrain_occluder(chunk.mesh())
+	var mesh_instance := MeshInstance3D.new()
+
+	mesh_instance.mesh = chunk.mesh()
+	mesh_instance.cast_shadow = MeshInstance3D.SHADOW_CASTING_SETTING_DOUBLE_SIDED
+	mesh_instance.add_child(occluder, false)
+	
+	var collision_body := StaticBody3D.new()
+	
+	for shape in chunk.collision_shapes():
+		collision_body.add_child(shape, false)
+	
+	mesh_instance.add_child(collision_body, false)
+	
+	self.add_child(mesh_instance, false)
+	
+	mesh_instance.owner = get_tree().current_scene
+	occluder.owner = get_tree().current_scene
+	collision_body.owner = get_tree().current_scene
+	
+	for shape in collision_body.get_children():
+		shape.owner = get_tree().current_scene
+	
+	mesh_instance.translate(translation)
+	
+	var lod_meshes := chunk.lod_meshes()
+	
+	for lod in range(1, lod_meshes.size()):
+		var lod_instance := MeshInstance3D.new()
 		
-		mesh_instance.owner = get_tree().current_scene
-		occluder.owner = get_tree().current_scene
+		lod_instance.mesh = lod_meshes[lod]
+		lod_instance.cast_shadow = MeshInstance3D.SHADOW_CASTING_SETTING_DOUBLE_SIDED
+		lod_instance.visibility_range_begin = self.lod_distance * lod
+		# the last level stays visible up to the horizon
+		lod_instance.visibility_range_end = self.lod_distance * (lod + 1) if lod + 1 < lod_meshes.size() else 0.0
 		
-		mesh_instance.translate(translation)
+		mesh_instance.add_child(lod_instance, false)
+		lod_instance.owner = get_tree().current_scene
 	
-	prints("generated terain:", self.get_child_count(), "nodes generated")
+	if lod_meshes.size() > 1:
+		mesh_instance.visibility_range_end = self.lod_distance
+	
+	self.chunk_nodes[Vector2i(tile_coords[0], tile_coords[1])] = mesh_instance
 
 
 func create_terrain_occluder(mesh: ArrayMesh) -> OccluderInstance3D:
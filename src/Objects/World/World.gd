@@ -8,9 +8,7 @@
 extends Node3D
 
 const TimeBudget := preload("../../util/TimeBudget.gd")
-const MsgPack := preload("../../godot-msgpack/msgpack.gd")
 const SceneObjectRegistry := preload("res://src/SceneObjectRegistry.gd")
-const Networks := preload("res://src/Objects/World/Networks.gd")
 const CityCoordsFeature := preload("res://src/features/CityCoordsFeature.gd")
 const Terrain := preload("res://src/Objects/Terrain/Terrain.gd")
 const Backdrop := preload("res://src/Objects/World/Backdrop.gd")
@@ -20,18 +18,30 @@ signal loading_progress(count)
 signal loading_scale(count)
 
 @export var world_constants: WorldConstants
+@export var map_catalog: MapCatalog
 @export var gi_probes: GiProbes
+@export var water_tank: WaterTank
 
 @onready var terrain: Terrain = $Terrain
 @onready var networks: Networks = $Networks
 @onready var buildings: Buildings = $Buildings
+@onready var missions: Missions = $Missions
+@onready var fire_department: FireDepartment = $FireDepartment
+@onready var session: Session = $Session
 @onready var backdrop: Backdrop = $Backdrop
 
+## Restore the saved session once the city has been built again.
+static var restore_session := false
+
+## The map that is loaded, it is handed over by the scene that starts the game.
+var map_path: String
+
 var sea_level: int
 var city_coords_feature: CityCoordsFeature
 
 func _ready():
 	assert(world_constants is WorldConstants, "world_constants is not of type WorldConstants")
+	assert(map_catalog is MapCatalog, "map_catalog is not of type MapCatalog")
 
 	self.networks.loading_progress.connect(self._on_child_progress)
 	self.buildings.spawn_point_encountered.connect(self._on_spawn_point_encountered)
@@ -43,19 +53,18 @@ func _ready():
 
 
 func _ready_deferred():
-	var file := FileAccess.open("res://resources/Maps/career/city0.sc2.mpz", FileAccess.READ)
+	if self.map_path.is_empty():
+		self.map_path = self.map_catalog.default_map()
+
+	var city: CityHandle = await CityLoader.load_async(self.map_path).completed
 
-	var city_bytes := file.get_buffer(file.get_length()).decompress_dynamic(-1, FileAccess.COMPRESSION_DEFLATE)
-	var city_result: Dictionary = MsgPack.decode(city_bytes)
-	var city: Dictionary = city_result.result
-	var buildings: Dictionary = city.buildings
-	var networks: Dictionary = city.networks
+	assert(city != null, "failed to load city %s" % self.map_path)
 
-	self.sea_level = city.simulator_settings["GlobalSeaLevel"]
+	self.sea_level = city.sea_level()
 	self.city_coords_feature = CityCoordsFeature.new(self.world_constants, self.sea_level)
 	self.terrain.init(city)
 	
-	self.loading_scale.emit(buildings.size() + networks.size() + self.terrain.load_steps() + self.gi_probes.load_steps() + 1)
+	self.loading_scale.emit(city.building_count() + city.network_count() + self.terrain.load_steps() + self.gi_probes.load_steps() + 1)
 	self._load_map_async(city)
 
 
@@ -67,11 +76,11 @@ func _on_spawn_point_encountered(tile_coords: Array[int], size: int, altitude: i
 	self._insert_spawn_point(tile_coords, size, altitude)
 
 
-func _load_map_async(city: Dictionary):	
-	var city_size: int = city.get("city_size")
+func _load_map_async(city: CityHandle):	
+	var city_size: int = city.city_size()
 
 	await self.terrain.build_async()
-	await self.networks.build_async(city)
+	await self.networks.build_async(city).completed
 	await self.buildings.build_async(city).completed	
 	
 
@@ -87,6 +96,13 @@ func _load_map_async(city: Dictionary):
 
 	await self.get_tree().process_frame
 	self.loading_progress.emit(1)
+	self.missions.start(city, self.map_path)
+	self.fire_department.start(city)
+	self.water_tank.set_city(city)
+
+	if restore_session:
+		restore_session = false
+		self.session.restore(self.map_path)
 
 
 func _create_snapshot() -> void:
@@ -118,7 +134,25 @@ func _insert_spawn_point(building_coords: Array[int], building_size: int, altitu
 	self.add_child(spawn_host)
 
 
+## Reloads the current scene with the same map.
+func _reload_scene() -> void:
+	var current_scene := self.get_tree().current_scene
+	var scene: PackedScene = load(current_scene.scene_file_path)
+	var scene_root := scene.instantiate()
+
+	scene_root.get_node(current_scene.get_path_to(self)).set("map_path", self.map_path)
+	self.get_tree().change_scene_to_node(scene_root)
+
+
 func _input(event: InputEvent) -> void:
+	if event.is_action_pressed("quick_save", false, true):
+		self.session.save(self.map_path)
+
+	if event.is_action_pressed("quick_load", false, true) and self.session.has_save():
+		# sessions are always restored on top of a freshly built city.
+		restore_session = true
+		self._reload_scene()
+
 	if not OS.has_feature("debug"):
 		return
 